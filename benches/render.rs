@@ -0,0 +1,43 @@
+//! 渲染热路径的基准测试：`cargo bench --bench render`
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use notionrs_types::prelude::Block;
+use rsnotablog05::renderer::{HtmlRenderer, RenderOptions};
+use std::hint::black_box;
+
+/// 一篇长文：覆盖各类 Block 的测试数据重复 200 次
+fn large_page() -> Vec<Block> {
+    let blocks: Vec<Block> = serde_json::from_str(include_str!("fixtures/blocks.json")).expect("测试数据无效");
+    std::iter::repeat_n(blocks, 200).flatten().collect()
+}
+
+fn render(c: &mut Criterion) {
+    let blocks = large_page();
+    let renderer = HtmlRenderer::new(RenderOptions::default());
+    let mut group = c.benchmark_group("render");
+    group.throughput(Throughput::Elements(blocks.len() as u64));
+    // 旧的用法：每个 Block 返回一个 String 再拼接
+    group.bench_function("render_block", |b| {
+        b.iter(|| {
+            let mut html = String::new();
+            for block in &blocks {
+                html.push_str(&renderer.render_block(black_box(block)));
+            }
+            html
+        })
+    });
+    // 复用同一个缓冲区
+    group.bench_function("render_block_into", |b| {
+        let mut html = String::new();
+        b.iter(|| {
+            html.clear();
+            for block in &blocks {
+                renderer.render_block_into(&mut html, black_box(block));
+            }
+            html.len()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, render);
+criterion_main!(benches);
@@ -0,0 +1,87 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::model::{PostMetadata, PostMetadataWithContent};
+use crate::site::BuildResult;
+
+/// JSON 接口的输出目录 (相对于站点根目录)
+pub const API_DIR: &str = "api";
+
+/// `api/posts.json` 中的一篇文章：元数据与摘要，正文在 `api/<slug>.json` 中
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PostSummary<'a> {
+    #[serde(flatten)]
+    meta: &'a PostMetadata,
+    description: Option<&'a str>,
+    /// 该文章完整数据的地址 (相对于站点根目录)
+    json_url: String,
+}
+
+/// 已发布文章的列表，按日期降序。未发布的文章不在 `result.posts` 中，
+/// `hiddenTags` 中的标签也不会序列化，与站点页面一致
+pub fn posts_json(result: &BuildResult) -> Result<String> {
+    let mut posts: Vec<&PostMetadataWithContent> = result.posts.iter().collect();
+    posts.sort_by(|a, b| b.meta.date.cmp(&a.meta.date));
+    let summaries: Vec<PostSummary> = posts
+        .iter()
+        .map(|post| PostSummary {
+            meta: &post.meta,
+            description: post.description.as_deref(),
+            json_url: post_json_path(&post.meta),
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&summaries)?)
+}
+
+/// 一篇文章的完整数据：元数据与渲染后的正文 HTML (`content`)。
+/// 先转为 `Value` 再输出：`series` 等字段与展开的元数据同名，与模板上下文一致取后者
+pub fn post_json(post: &PostMetadataWithContent) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&serde_json::to_value(post)?)?)
+}
+
+/// 文章 JSON 的路径 (相对于站点根目录)
+pub fn post_json_path(meta: &PostMetadata) -> String {
+    format!("{}/{}.json", API_DIR, meta.slug)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::output::Output;
+    use crate::site::Site;
+    use crate::test_support;
+    use serde_json::{json, Value};
+
+    #[tokio::test]
+    async fn posts_api_lists_published_posts_and_post_json_has_content() {
+        let date = |day: &str| json!({ "date": { "type": "date", "date": { "start": day, "end": null, "time_zone": null } } });
+        let mut draft = date("2024-03-01");
+        draft["publish"] = json!({ "type": "checkbox", "checkbox": false });
+        let pages = vec![
+            test_support::page("p1", "older", &[], date("2024-01-01")),
+            test_support::page("p2", "newer", &["Rust", "Internal"], date("2024-02-01")),
+            test_support::page("p3", "draft", &[], draft),
+        ];
+        let server = test_support::notion_site(pages, vec![test_support::paragraph("b1", "Hello world")]).await;
+        let config = test_support::config(json!({ "jsonApi": true, "hiddenTags": ["Internal"] }));
+        let result = Site::build_from(&server.url, &config).await.unwrap();
+        let mut output = Output::new(true).with_contents();
+        Site::render_to(&config, &result, &mut output).unwrap();
+        let read = |path: &str| -> Value { serde_json::from_slice(output.contents(path).expect(path)).unwrap() };
+
+        let list = read("public/api/posts.json");
+        let slugs: Vec<&str> = list.as_array().unwrap().iter().map(|p| p["slug"].as_str().unwrap()).collect();
+        assert_eq!(slugs, ["newer", "older"]);
+        let newer = &list[0];
+        assert_eq!(newer["jsonUrl"], "api/newer.json");
+        assert_eq!(newer["wordCount"], 2);
+        assert_eq!(newer["tags"][0]["name"], "Rust");
+        assert!(newer.get("hiddenTags").is_none() && newer.get("content").is_none(), "{}", newer);
+        assert!(!list.to_string().contains("Internal"), "{}", list);
+        assert!(output.contents("public/api/draft.json").is_none());
+
+        let post = read("public/api/newer.json");
+        assert_eq!(post["slug"], "newer");
+        assert!(post["content"].as_str().unwrap().contains("Hello world"), "{}", post);
+    }
+}
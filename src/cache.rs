@@ -0,0 +1,167 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
+
+/// 下载内容的本地缓存，多次构建间复用 (如字体文件)。
+///
+/// 内容按哈希存放在 `objects/` 下，相同内容只存一份；`refs/` 记录 URL 对应的内容哈希。
+/// 写入时先写临时文件再重命名，多个进程同时写入同一条目也不会留下写了一半的文件
+pub struct Cache {
+    root: PathBuf,
+}
+
+/// 同一进程内临时文件名的序号
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+impl Cache {
+    /// 打开 (必要时创建) 缓存目录，并按 `max_age` / `max_size` 清理旧条目
+    pub fn open(root: impl AsRef<Path>, max_age: Option<Duration>, max_size: Option<u64>) -> Result<Self> {
+        let cache = Self { root: root.as_ref().to_path_buf() };
+        fs::create_dir_all(cache.objects_dir())
+            .with_context(|| format!("无法创建缓存目录: {}", cache.root.display()))?;
+        fs::create_dir_all(cache.refs_dir())?;
+        let evicted = cache.evict(max_age, max_size)?;
+        if evicted > 0 {
+            println!(">>> 已清理 {} 个过期的缓存文件", evicted);
+        }
+        Ok(cache)
+    }
+
+    /// 读取 `key` (通常为 URL) 对应的缓存内容，不存在或已被清理时返回 None
+    pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let hash = fs::read_to_string(self.ref_path(key)).ok()?;
+        let object = self.objects_dir().join(hash.trim());
+        let data = fs::read(&object).ok()?;
+        // 更新修改时间，清理时按最近使用的时间保留
+        if let Ok(file) = fs::File::options().write(true).open(&object) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        Some(data)
+    }
+
+    /// 写入 `key` 对应的内容
+    pub fn put(&self, key: &str, data: &[u8]) -> Result<()> {
+        let hash = hash_hex(data);
+        let object = self.objects_dir().join(&hash);
+        if !object.exists() {
+            write_atomic(&object, data)?;
+        }
+        write_atomic(&self.ref_path(key), hash.as_bytes())
+    }
+
+    /// 先查缓存，未命中时调用 `fetch` 下载并写入缓存
+    pub async fn get_or_fetch<F, Fut>(&self, key: &str, fetch: F) -> Result<Vec<u8>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<Vec<u8>>>,
+    {
+        if let Some(data) = self.get(key) {
+            return Ok(data);
+        }
+        let data = fetch().await?;
+        self.put(key, &data)?;
+        Ok(data)
+    }
+
+    fn objects_dir(&self) -> PathBuf {
+        self.root.join("objects")
+    }
+
+    fn refs_dir(&self) -> PathBuf {
+        self.root.join("refs")
+    }
+
+    fn ref_path(&self, key: &str) -> PathBuf {
+        self.refs_dir().join(hash_hex(key.as_bytes()))
+    }
+
+    /// 删除超过 `max_age` 未使用的内容，总大小超过 `max_size` 时再从最久未使用的开始删除。
+    /// 指向已删除内容的 ref 在读取时视为未命中，这里一并清掉
+    fn evict(&self, max_age: Option<Duration>, max_size: Option<u64>) -> Result<usize> {
+        if max_age.is_none() && max_size.is_none() {
+            return Ok(0);
+        }
+        let now = SystemTime::now();
+        let mut objects: Vec<(PathBuf, SystemTime, u64)> = Vec::new();
+        for entry in fs::read_dir(self.objects_dir())? {
+            let entry = entry?;
+            let meta = entry.metadata()?;
+            if meta.is_file() {
+                objects.push((entry.path(), meta.modified().unwrap_or(now), meta.len()));
+            }
+        }
+        // 从最近使用的开始累计大小，超出部分与过期的内容一起删除
+        objects.sort_by_key(|(_, modified, _)| std::cmp::Reverse(*modified));
+        let mut kept = 0u64;
+        let mut evicted = 0;
+        for (path, modified, size) in objects {
+            let expired = max_age.is_some_and(|age| now.duration_since(modified).unwrap_or_default() > age);
+            let over_size = max_size.is_some_and(|limit| kept + size > limit);
+            if expired || over_size {
+                // 其他进程可能已经删除了同一个文件
+                if fs::remove_file(&path).is_ok() {
+                    evicted += 1;
+                }
+            } else {
+                kept += size;
+            }
+        }
+        if evicted > 0 {
+            for entry in fs::read_dir(self.refs_dir())? {
+                let path = entry?.path();
+                let dangling = fs::read_to_string(&path)
+                    .map(|hash| !self.objects_dir().join(hash.trim()).exists())
+                    .unwrap_or(false);
+                if dangling {
+                    let _ = fs::remove_file(&path);
+                }
+            }
+        }
+        Ok(evicted)
+    }
+}
+
+/// 写入同目录下的临时文件后重命名为目标文件名；重命名是原子的，读者只会看到完整的文件
+fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(format!(".tmp-{}-{}", std::process::id(), TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)));
+    let temp = PathBuf::from(temp);
+    fs::write(&temp, data).with_context(|| format!("无法写入缓存文件: {}", temp.display()))?;
+    if let Err(e) = fs::rename(&temp, path) {
+        let _ = fs::remove_file(&temp);
+        return Err(e).with_context(|| format!("无法写入缓存文件: {}", path.display()));
+    }
+    Ok(())
+}
+
+/// 64 位 FNV-1a 哈希的十六进制表示，用作缓存文件名
+fn hash_hex(data: &[u8]) -> String {
+    let hash = data.iter().fold(0xcbf29ce484222325u64, |h, &b| (h ^ b as u64).wrapping_mul(0x100000001b3));
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn size_limit_evicts_least_recently_used_entries() {
+        let dir = test_support::temp_dir("cache-evict");
+        let cache = Cache::open(&dir, None, None).unwrap();
+        cache.put("old", &[1u8; 600]).unwrap();
+        let object = dir.join("objects").join(hash_hex(&[1u8; 600]));
+        fs::File::options().write(true).open(&object).unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(3_600)).unwrap();
+        cache.put("new", &[2u8; 600]).unwrap();
+
+        let cache = Cache::open(&dir, None, Some(1_000)).unwrap();
+        assert_eq!(cache.get("old"), None);
+        assert_eq!(cache.get("new"), Some(vec![2u8; 600]));
+        assert_eq!(fs::read_dir(dir.join("refs")).unwrap().count(), 1, "指向已删除内容的 ref 应一并清理");
+        // 没有遗留临时文件
+        assert!(fs::read_dir(dir.join("objects")).unwrap().all(|e| !e.unwrap().file_name().to_string_lossy().contains(".tmp-")));
+    }
+}
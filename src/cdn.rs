@@ -0,0 +1,126 @@
+use crate::config::Config;
+use crate::feed;
+use crate::output::{OUTPUT_DIR, Output, OutputFile};
+use anyhow::Result;
+use serde::Deserialize;
+
+/// 部署平台的规则文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CdnFormat {
+    Netlify,
+    Cloudflare,
+}
+
+/// 生成 `_headers` 与 `_redirects`，须在其他文件都写出之后调用 (按已生成的文件补充 Content-Type)。
+/// 两个平台的语法基本一致；区别在于 Netlify 需要 `301!` 强制跳转，
+/// 否则旧地址上仍存在的文件会优先于跳转规则，而 Cloudflare Pages 不支持 `!`。
+pub fn write_rules(config: &Config, format: CdnFormat, output: &mut Output) -> Result<()> {
+    let headers = headers(config, output.files());
+    output.write("public/_headers", headers)?;
+    output.write("public/_redirects", redirects(config, format))?;
+    Ok(())
+}
+
+/// `cacheRules` 中的缓存规则，以及 XML、JSON 与文本文件的 Content-Type。
+/// 部分 CDN 与对象存储只按扩展名猜测类型且不带字符集，中文内容会显示为乱码
+fn headers(config: &Config, files: &[OutputFile]) -> String {
+    let mut s = String::new();
+    for (path, cache_control) in &config.cache_rules {
+        s.push_str(&format!("{}\n  Cache-Control: {}\n", absolute_path(path), cache_control));
+    }
+    for file in files {
+        let Ok(relative) = file.path.strip_prefix(OUTPUT_DIR) else { continue };
+        let path = relative.to_string_lossy().replace('\\', "/");
+        if let Some(content_type) = content_type(&path) {
+            s.push_str(&format!("/{}\n  Content-Type: {}\n", path, content_type));
+        }
+    }
+    s.push_str("/*\n  X-Content-Type-Options: nosniff\n");
+    s
+}
+
+/// 需要明确声明类型的文件 (HTML、CSS、图片等交给平台按扩展名判断)
+fn content_type(path: &str) -> Option<&'static str> {
+    if path == feed::RSS_PATH || path == feed::INDEX_PATH {
+        return Some("application/rss+xml; charset=utf-8");
+    }
+    match path.rsplit_once('.')?.1 {
+        "xml" => Some("application/xml; charset=utf-8"),
+        "json" => Some("application/json; charset=utf-8"),
+        "txt" => Some("text/plain; charset=utf-8"),
+        _ => None,
+    }
+}
+
+/// 由 `aliases` 配置 (旧路径 → 新路径) 生成的永久跳转
+fn redirects(config: &Config, format: CdnFormat) -> String {
+    let status = match format {
+        CdnFormat::Netlify => "301!",
+        CdnFormat::Cloudflare => "301",
+    };
+    config
+        .aliases
+        .iter()
+        .map(|(from, to)| format!("{} {} {}\n", absolute_path(from), absolute_path(to), status))
+        .collect()
+}
+
+/// 规则文件中的路径必须以 `/` 开头；完整 URL 原样保留
+fn absolute_path(path: &str) -> String {
+    if path.starts_with('/') || path.contains("://") {
+        path.to_string()
+    } else {
+        format!("/{}", path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use serde_json::json;
+
+    fn aliases_config() -> Config {
+        test_support::config(json!({
+            "aliases": { "old-post.html": "new-post.html", "/2019/hello": "https://example.com/hello" }
+        }))
+    }
+
+    #[test]
+    fn aliases_produce_redirect_lines() {
+        let config = aliases_config();
+        assert_eq!(
+            redirects(&config, CdnFormat::Netlify),
+            "/2019/hello https://example.com/hello 301!\n/old-post.html /new-post.html 301!\n"
+        );
+        assert_eq!(
+            redirects(&config, CdnFormat::Cloudflare),
+            "/2019/hello https://example.com/hello 301\n/old-post.html /new-post.html 301\n"
+        );
+    }
+
+    #[test]
+    fn headers_follow_cache_rules_and_generated_files() {
+        let config = test_support::config(json!({ "cacheRules": { "/assets/*": "public, max-age=600" } }));
+        let mut output = Output::new(true);
+        for path in ["public/index.html", "public/sitemap.xml", "public/feed.xml", "public/api/posts.json", "public/robots.txt"] {
+            output.write(path, "x").unwrap();
+        }
+        assert_eq!(
+            headers(&config, output.files()),
+            "/assets/*\n  Cache-Control: public, max-age=600\n\
+             /sitemap.xml\n  Content-Type: application/xml; charset=utf-8\n\
+             /feed.xml\n  Content-Type: application/rss+xml; charset=utf-8\n\
+             /api/posts.json\n  Content-Type: application/json; charset=utf-8\n\
+             /robots.txt\n  Content-Type: text/plain; charset=utf-8\n\
+             /*\n  X-Content-Type-Options: nosniff\n"
+        );
+    }
+
+    #[test]
+    fn default_cache_rules_keep_assets_immutable() {
+        let headers = headers(&aliases_config(), &[]);
+        assert!(headers.starts_with("/assets/*\n  Cache-Control: public, max-age=31536000, immutable\n/main.css\n"), "{}", headers);
+    }
+}
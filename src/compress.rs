@@ -0,0 +1,108 @@
+use anyhow::Result;
+use serde::Deserialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::output::Output;
+
+/// 预压缩文件的格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    /// `.gz`
+    Gzip,
+    /// `.br`
+    Brotli,
+}
+
+impl Encoding {
+    fn extension(self) -> &'static str {
+        match self {
+            Encoding::Gzip => "gz",
+            Encoding::Brotli => "br",
+        }
+    }
+
+    fn compress(self, data: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Encoding::Gzip => {
+                let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+                encoder.write_all(data)?;
+                Ok(encoder.finish()?)
+            }
+            Encoding::Brotli => {
+                let mut compressed = Vec::new();
+                {
+                    let mut writer = brotli::CompressorWriter::new(&mut compressed, 4096, 11, 22);
+                    writer.write_all(data)?;
+                }
+                Ok(compressed)
+            }
+        }
+    }
+}
+
+/// 需要预压缩的文本文件类型
+const COMPRESSIBLE: &[&str] = &["html", "css", "js", "xml", "json", "svg", "txt"];
+
+/// 为本次生成的文本文件写出预压缩的副本 (`index.html` -> `index.html.gz` / `index.html.br`)，
+/// 供支持直接发送预压缩文件的静态托管使用。文件清单来自 `output`，小于 `min_size` 字节的文件跳过
+pub fn precompress(output: &mut Output, encodings: &[Encoding], min_size: u64) -> Result<usize> {
+    let targets: Vec<PathBuf> = output
+        .files()
+        .iter()
+        .filter(|f| f.size >= min_size && is_compressible(&f.path))
+        .map(|f| f.path.clone())
+        .collect();
+
+    let mut written = 0;
+    for path in targets {
+        // 优先使用内存中保留的内容；dry-run 时文件并未写出，没有保留内容就只记录计划生成的文件
+        let data = match output.contents(&path) {
+            Some(contents) => contents.to_vec(),
+            None if output.is_dry_run() => Vec::new(),
+            None => std::fs::read(&path)?,
+        };
+        for &encoding in encodings {
+            let mut target = path.clone().into_os_string();
+            target.push(".");
+            target.push(encoding.extension());
+            output.write(PathBuf::from(target), encoding.compress(&data)?)?;
+            written += 1;
+        }
+    }
+    Ok(written)
+}
+
+fn is_compressible(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| COMPRESSIBLE.contains(&ext))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use std::io::Read;
+
+    #[test]
+    fn gzip_companions_decompress_to_the_original() {
+        let dir = test_support::temp_dir("precompress");
+        let index = "<html>".to_string() + &"<p>Hello</p>".repeat(200) + "</html>";
+        let mut output = Output::new(false);
+        output.write(dir.join("index.html"), &index).unwrap();
+        output.write(dir.join("small.css"), "a{}").unwrap();
+        output.write(dir.join("photo.png"), vec![0u8; 4096]).unwrap();
+
+        let written = precompress(&mut output, &[Encoding::Gzip], 1024).unwrap();
+        assert_eq!(written, 1);
+        assert!(!dir.join("small.css.gz").exists());
+        assert!(!dir.join("photo.png.gz").exists());
+
+        let compressed = std::fs::read(dir.join("index.html.gz")).unwrap();
+        let mut decompressed = String::new();
+        flate2::read::GzDecoder::new(compressed.as_slice()).read_to_string(&mut decompressed).unwrap();
+        assert_eq!(decompressed, index);
+    }
+}
@@ -0,0 +1,596 @@
+use crate::cdn::CdnFormat;
+use crate::compress::Encoding;
+use crate::error::BuildError;
+use crate::images::ImageFormat;
+use crate::renderer::{EmptyParagraph, RenderOptions, TodoStyle};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+// -----------------------------------------------------------
+// 0. 配置结构
+// -----------------------------------------------------------
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Config {
+    pub url: String,
+    pub notion_token: String,
+    pub theme: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// 输出 `slug/index.html` 并以 `slug/` 链接，代替 `slug.html`
+    #[serde(default)]
+    pub clean_urls: bool,
+    /// 站内链接结尾的斜杠策略，统一作用于文章、标签页、系列页与首页链接；未设置时保持原有行为
+    #[serde(default)]
+    pub trailing_slash: Option<TrailingSlash>,
+    /// 按标签名配置的标签元数据 (描述、封面、颜色覆盖)
+    #[serde(default)]
+    pub tag_meta: HashMap<String, TagMeta>,
+    /// 隐藏的标签：不出现在标签云、标签页与文章的标签列表中，文章本身照常发布
+    #[serde(default)]
+    pub hidden_tags: Vec<String>,
+    /// 允许以 iframe 嵌入的域名白名单，未设置时不限制
+    #[serde(default)]
+    pub embed_allowed_hosts: Option<Vec<String>>,
+    /// 为每个系列生成 `series/<slug>.html` 列表页
+    #[serde(default)]
+    pub series_pages: bool,
+    /// 标签页每页的文章数，未设置时一个标签的文章全部显示在一页
+    #[serde(default)]
+    pub posts_per_page: Option<usize>,
+    /// 生成按标签分组列出全部文章的 `topics.html`
+    #[serde(default)]
+    pub topics_page: bool,
+    /// 渲染页面的线程数，未设置时取 CPU 核数；设为 1 时串行渲染
+    #[serde(default)]
+    pub render_threads: Option<usize>,
+    /// To-do 渲染方式：`checkbox` (默认) 或 `tasklist`
+    #[serde(default)]
+    pub todo_style: TodoStyle,
+    /// 空段落的渲染方式：`spacer` (默认)、`paragraph` 或 `br`
+    #[serde(default)]
+    pub empty_paragraph: EmptyParagraph,
+    /// 子 Block 的最大递归深度
+    #[serde(default = "default_max_block_depth")]
+    pub max_block_depth: usize,
+    /// 每篇文章最多抓取的 Block 数 (含嵌套的子 Block)，超出部分截断；未设置时不限制
+    #[serde(default)]
+    pub max_blocks_per_page: Option<usize>,
+    /// 手动摘要分隔标记：`divider` 或一段独占一行的文本 (如 `<!--more-->`)
+    #[serde(default)]
+    pub excerpt_delimiter: Option<String>,
+    /// 有子内容的列表项渲染为可折叠的 toggle
+    #[serde(default)]
+    pub toggle_list_items: bool,
+    /// 旧路径 → 新路径，用于生成 `_redirects`
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+    /// 生成部署平台的 `_headers` / `_redirects`：`netlify` 或 `cloudflare`
+    #[serde(default)]
+    pub cdn_rules: Option<CdnFormat>,
+    /// `_headers` 中的缓存规则：路径模式 → `Cache-Control` 的值
+    #[serde(default = "default_cache_rules")]
+    pub cache_rules: BTreeMap<String, String>,
+    /// 输出文件的权限 (八进制，如 `644`)；未设置时由系统的 umask 决定
+    #[serde(default)]
+    pub file_mode: Option<String>,
+    /// 输出目录的权限 (八进制，如 `755`)
+    #[serde(default)]
+    pub dir_mode: Option<String>,
+    /// 字体样式表 URL (如 Google Fonts)，构建时下载到本地并改写引用
+    #[serde(default)]
+    pub self_host_fonts: Vec<String>,
+    /// 以数据库图标生成 favicon (Emoji 生成 SVG，PNG 图片缩放为多个尺寸)
+    #[serde(default)]
+    pub favicon: bool,
+    /// 下载缓存的目录，多次构建间复用已下载的文件
+    #[serde(default = "default_cache_dir")]
+    pub cache_dir: String,
+    /// 封面图片转换的目标格式：`original` 不转换 (默认)，`webp`、`avif` 下载并转换，原图作为 `<picture>` 的回退
+    #[serde(default)]
+    pub image_format: ImageFormat,
+    /// 转换图片的编码质量 (1-100)
+    #[serde(default = "default_image_quality")]
+    pub image_quality: u8,
+    /// 缓存文件超过该天数未使用时清理
+    #[serde(default)]
+    pub cache_max_age_days: Option<u64>,
+    /// 缓存总大小上限 (字节)，超出时从最久未使用的文件开始清理
+    #[serde(default)]
+    pub cache_max_size: Option<u64>,
+    /// 按显示宽度截断预览 (CJK 字符计为 2)，未设置时按 150 个字符截断
+    #[serde(default)]
+    pub preview_width: Option<usize>,
+    /// 分栏之间的间距 (CSS 长度)
+    #[serde(default = "default_column_gap")]
+    pub column_gap: String,
+    /// 视口宽度不超过该值 (px) 时，分栏改为上下堆叠
+    #[serde(default = "default_column_breakpoint")]
+    pub column_breakpoint: u32,
+    /// 站点 Feed 的完整地址，`--notify` 时通知给 hub 与 Webhook；未设置时由 `siteUrl` 推导
+    #[serde(default)]
+    pub feed_url: Option<String>,
+    /// 同时以 `index.xml` 输出 Feed (Netlify、Hugo 等约定的合并 Feed 地址)
+    #[serde(default)]
+    pub index_feed: bool,
+    /// `--notify` 时通知的 WebSub (PubSubHubbub) hub 地址
+    #[serde(default)]
+    pub websub_hubs: Vec<String>,
+    /// `--notify` 时 POST `{"feedUrl": ...}` 的 Webhook 地址
+    #[serde(default)]
+    pub notify_webhook: Option<String>,
+    /// 为行间公式按文章自动编号，并解析 `\eqref{...}` 引用
+    #[serde(default)]
+    pub number_equations: bool,
+    /// 额外拷贝到输出目录的文件或目录：源路径 (相对于项目根目录) -> 目标路径 (相对于 `public/`)
+    #[serde(default)]
+    pub extra_static: BTreeMap<String, String>,
+    /// 为正文 HTML 换行缩进，便于调试主题与比较差异
+    #[serde(default)]
+    pub pretty_html: bool,
+    /// 在文章上下文中附带原始 Block 树 (`post.blocks`)
+    #[serde(default)]
+    pub include_raw_blocks: bool,
+    /// 按文章的 `language` 属性生成 `public/<lang>/` 下的首页与标签页
+    #[serde(default)]
+    pub i18n: bool,
+    /// 文章日期的来源：`date` 属性 (默认)、创建时间 `created` 或最后编辑时间 `edited`
+    #[serde(default)]
+    pub date_source: DateSource,
+    /// 查询数据库时交给 Notion 的过滤与排序条件；未设置时取回全部页面
+    #[serde(default)]
+    pub query: QueryConfig,
+    /// 站点的公开地址 (如 `https://blog.example.com`)，用于 sitemap 等需要绝对地址的场合
+    #[serde(default)]
+    pub site_url: Option<String>,
+    /// robots.txt 的生成规则
+    #[serde(default)]
+    pub robots: RobotsConfig,
+    /// 生成 `api/posts.json` 与每篇文章的 `api/<slug>.json`
+    #[serde(default)]
+    pub json_api: bool,
+    /// 设置后生成 `humans.txt`
+    #[serde(default)]
+    pub humans: Option<HumansConfig>,
+    /// 设置后生成 `.well-known/security.txt`
+    #[serde(default)]
+    pub security: Option<SecurityConfig>,
+    /// 超过该行数的代码块折叠为 `<details>`，点击「显示更多」展开；未设置时不折叠
+    #[serde(default)]
+    pub code_collapse_lines: Option<usize>,
+    /// 在代码块的标题栏中输出复制按钮
+    #[serde(default)]
+    pub code_copy_button: bool,
+    /// 为没有封面的文章生成分享卡片 `og/<slug>.png` (标题 + 站点名称)，用作 `og:image`
+    #[serde(default)]
+    pub generate_og_images: bool,
+    /// 绘制分享卡片所用的字体文件 (TTF / OTF)；启用 `generateOgImages` 时必填
+    #[serde(default)]
+    pub og_image_font: Option<String>,
+    /// 浏览器界面的主题色，输出为 `<meta name="theme-color">`
+    #[serde(default)]
+    pub theme_color: Option<String>,
+    /// 配色方案：`auto` (跟随系统，默认)、`light` 或 `dark`
+    #[serde(default)]
+    pub color_scheme: ColorScheme,
+    /// 获取每篇文章未解决的评论数 (`commentCount`)；需要 Integration 具有读取评论的权限
+    #[serde(default)]
+    pub fetch_comments: bool,
+    /// 请求地址没有扩展名的文件 Block 的响应头，按 Content-Type 渲染为图片或带扩展名的下载链接
+    #[serde(default)]
+    pub sniff_file_types: bool,
+    /// 在正文前以表格展示的数据库属性名 (按顺序)，支持单选、多选、数字与日期
+    #[serde(default)]
+    pub properties_table: Vec<String>,
+    /// `siteMeta.buildTime` 的格式 (UTC)，支持 `%Y` `%m` `%d` `%H` `%M` `%S`
+    #[serde(default = "default_build_time_format")]
+    pub build_time_format: String,
+    /// 在标题旁输出指向其锚点的 `#` 链接，便于复制段落链接
+    #[serde(default)]
+    pub heading_anchors: bool,
+    /// 引用的子段落以 `—` 开头且较短时视为出处，渲染为引用内的 `<cite>`
+    #[serde(default)]
+    pub quote_citations: bool,
+    /// 模板按钮的处理方式：`omit` 省略 (默认)，`inline` 展开其模板内容
+    #[serde(default)]
+    pub template_blocks: TemplateBlocks,
+    /// 为生成的 HTML / CSS / JS 等文本文件写出预压缩副本：`gzip` (`.gz`)、`brotli` (`.br`)
+    #[serde(default)]
+    pub precompress: Vec<Encoding>,
+    /// 小于该字节数的文件不预压缩
+    #[serde(default = "default_precompress_min_size")]
+    pub precompress_min_size: u64,
+    /// Feed 中包含的文章数 (按日期从新到旧)
+    #[serde(default = "default_feed_limit")]
+    pub feed_limit: usize,
+    /// 带有这些标签的文章不进入 Feed (不区分大小写)
+    #[serde(default)]
+    pub feed_exclude_tags: Vec<String>,
+    /// 带有 `hiddenTags` 中标签的文章不进入 Feed
+    #[serde(default)]
+    pub feed_exclude_hidden_tags: bool,
+    /// 只有勾选了 `inFeed` 属性的文章进入 Feed
+    #[serde(default)]
+    pub feed_require_in_feed: bool,
+    /// 在页面 <head> 中输出 Feed 的自动发现链接 (`siteMeta.feeds`)
+    #[serde(default)]
+    pub feed_discovery: bool,
+    /// 在文章页输出 BreadcrumbList 结构化数据 (需要设置 `siteUrl`)
+    #[serde(default)]
+    pub breadcrumbs: bool,
+    /// 阅读模式 (由 `--format reader` 或其别名 `--format amp` 设置，不从配置文件读取)
+    #[serde(skip)]
+    pub reader_mode: bool,
+    /// dry-run (由 `--dry-run` 设置)：不写入任何文件，也不记录构建日志
+    #[serde(skip)]
+    pub dry_run: bool,
+    /// 严格模式 (由 `--strict` 设置)：遇到不支持的 Block 时构建失败
+    #[serde(skip)]
+    pub strict: bool,
+}
+
+fn default_max_block_depth() -> usize {
+    20
+}
+
+fn default_column_gap() -> String {
+    "1.5em".to_string()
+}
+
+fn default_column_breakpoint() -> u32 {
+    640
+}
+
+fn default_cache_rules() -> BTreeMap<String, String> {
+    BTreeMap::from([
+        ("/assets/*".to_string(), "public, max-age=31536000, immutable".to_string()),
+        ("/main.css".to_string(), "public, max-age=86400".to_string()),
+    ])
+}
+
+fn default_build_time_format() -> String {
+    "%Y-%m-%d %H:%M UTC".to_string()
+}
+
+fn default_feed_limit() -> usize {
+    20
+}
+
+fn default_precompress_min_size() -> u64 {
+    1024
+}
+
+fn default_image_quality() -> u8 {
+    80
+}
+
+fn default_cache_dir() -> String {
+    ".cache".to_string()
+}
+
+/// robots.txt 配置
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RobotsConfig {
+    /// 设为 false 时不生成 robots.txt
+    pub enabled: bool,
+    pub allow: Vec<String>,
+    pub disallow: Vec<String>,
+}
+
+impl Default for RobotsConfig {
+    fn default() -> Self {
+        Self { enabled: true, allow: Vec::new(), disallow: Vec::new() }
+    }
+}
+
+/// humans.txt 的内容，每项一行
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HumansConfig {
+    /// 作者与贡献者，如 `"Author: Alice"`
+    pub team: Vec<String>,
+    /// 致谢
+    pub thanks: Vec<String>,
+    /// 站点信息，如 `"Language: 中文"`
+    pub site: Vec<String>,
+}
+
+/// security.txt (RFC 9116) 的字段
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SecurityConfig {
+    /// 安全问题的联系方式；邮箱地址会自动加上 `mailto:`
+    pub contact: Vec<String>,
+    /// 失效日期 (`YYYY-MM-DD` 或完整的 ISO 8601 时间)，未设置时为构建当天起一年后
+    pub expires: Option<String>,
+    pub policy: Option<String>,
+    pub encryption: Option<String>,
+    pub preferred_languages: Option<String>,
+}
+
+/// 数据库查询条件，在 Notion 端过滤与排序，减少取回的页面
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct QueryConfig {
+    /// 只取回该复选框属性已勾选的页面 (如 `publish`)
+    pub filter_checkbox: Option<String>,
+    /// 按该属性排序 (如 `date`)
+    pub sort_property: Option<String>,
+    pub sort_direction: SortDirection,
+}
+
+/// 排序方向
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    Ascending,
+    #[default]
+    Descending,
+}
+
+/// 文章日期的来源
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DateSource {
+    /// 数据库的 `date` 属性
+    #[default]
+    Date,
+    /// 页面的创建时间
+    Created,
+    /// 页面的最后编辑时间
+    Edited,
+}
+
+/// 站点的配色方案，以 `theme-<scheme>` 类名输出到 `<html>` 上
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorScheme {
+    /// 跟随系统设置
+    #[default]
+    Auto,
+    Light,
+    Dark,
+}
+
+/// 站内链接结尾的斜杠策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrailingSlash {
+    /// 链接为 `slug/`，输出 `slug/index.html`
+    Always,
+    /// 链接为 `slug.html`；同时启用 `cleanUrls` 时为 `slug` (需要托管平台支持省略 `.html`)
+    Never,
+}
+
+/// 模板按钮 Block 的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateBlocks {
+    /// 不输出
+    #[default]
+    Omit,
+    /// 直接展开按钮中的模板内容
+    Inline,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagMeta {
+    pub description: Option<String>,
+    pub cover: Option<String>,
+    pub color: Option<String>,
+}
+
+impl Config {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, BuildError> {
+        let content = fs::read_to_string(path).context("无法读取配置文件").map_err(BuildError::Config)?;
+        let config: Config = serde_json::from_str(&content).context("解析配置文件失败").map_err(BuildError::Config)?;
+        Ok(config)
+    }
+
+    /// 生成渲染器选项
+    pub fn render_options(&self) -> RenderOptions {
+        RenderOptions {
+            embed_allowed_hosts: self.embed_allowed_hosts.clone(),
+            todo_style: self.todo_style,
+            empty_paragraph: self.empty_paragraph,
+            reader_mode: self.reader_mode,
+            code_collapse_lines: self.code_collapse_lines,
+            code_copy_button: self.code_copy_button,
+        }
+    }
+
+    /// 站点公开地址 (去掉末尾的 `/`)
+    pub fn site_url(&self) -> Option<&str> {
+        self.site_url.as_deref().map(|u| u.trim_end_matches('/')).filter(|u| !u.is_empty())
+    }
+
+    /// 站点标题
+    pub fn site_title(&self) -> String {
+        self.title.clone().unwrap_or_else(|| "My Blog".to_string())
+    }
+
+    /// 从 URL 中提取 Notion ID (32位十六进制字符串)
+    pub fn get_notion_id(&self) -> Result<String> {
+        let url = self.url.trim();
+        let parts: Vec<&str> = url.split('/').collect();
+        let last_part = parts.last().ok_or_else(|| anyhow::anyhow!("无效的 URL: '{}'", url))?;
+        
+        // 处理带查询参数的 URL (例如 ?v=...)
+        let id_part = last_part.split('?').next().unwrap_or(last_part);
+        
+        // Notion ID 应该是 32 位字符
+        // 有些 URL 可能是 .../Some-Title-1234567890abcdef1234567890abcdef
+        // 这种情况下我们需要提取最后 32 位
+        let clean_id = id_part.replace("-", ""); 
+        
+        if clean_id.len() >= 32 {
+            // 取最后 32 位
+            Ok(clean_id[clean_id.len()-32..].to_string())
+        } else {
+            Err(anyhow::anyhow!("无法从 URL ('{}') 中提取有效的 Notion ID. 解析到的 ID 部分: '{}'", url, id_part))
+        }
+    }
+
+    /// 文章的链接地址 (相对于站点根目录)
+    pub fn post_url(&self, slug: &str) -> String {
+        self.page_url(slug)
+    }
+
+    /// 文章的输出文件路径
+    pub fn post_output_path(&self, slug: &str) -> String {
+        self.page_output_path(slug)
+    }
+
+    /// 页面 `name` (不含扩展名，如 `my-post`、`tag/rust`) 的链接，按 `trailingSlash` 与 `cleanUrls` 决定形式
+    pub fn page_url(&self, name: &str) -> String {
+        match (self.trailing_slash, self.clean_urls) {
+            (Some(TrailingSlash::Always), _) | (None, true) => format!("{}/", name),
+            (Some(TrailingSlash::Never), true) => name.to_string(),
+            (Some(TrailingSlash::Never), false) | (None, false) => format!("{}.html", name),
+        }
+    }
+
+    /// 页面 `name` 的输出文件路径，与 `page_url` 对应
+    pub fn page_output_path(&self, name: &str) -> String {
+        match (self.trailing_slash, self.clean_urls) {
+            (Some(TrailingSlash::Always), _) | (None, true) => format!("public/{}/index.html", name),
+            _ => format!("public/{}.html", name),
+        }
+    }
+
+    /// 标签页、系列页等汇总页面的链接：未设置 `trailingSlash` 时始终为 `name.html`，不受 `cleanUrls` 影响
+    pub fn listing_url(&self, name: &str) -> String {
+        match self.trailing_slash {
+            Some(_) => self.page_url(name),
+            None => format!("{}.html", name),
+        }
+    }
+
+    /// 汇总页面的输出文件路径，与 `listing_url` 对应
+    pub fn listing_output_path(&self, name: &str) -> String {
+        match self.trailing_slash {
+            Some(_) => self.page_output_path(name),
+            None => format!("public/{}.html", name),
+        }
+    }
+
+    /// 首页链接 (相对于站点根目录)：`index.html`；设置了 `trailingSlash` 且链接不带 `.html` 时为空 (即站点根目录)
+    pub fn home_url(&self) -> &'static str {
+        match (self.trailing_slash, self.clean_urls) {
+            (Some(TrailingSlash::Always), _) | (Some(TrailingSlash::Never), true) => "",
+            _ => "index.html",
+        }
+    }
+
+    /// 标签是否在 `hiddenTags` 中 (不区分大小写)
+    pub fn is_hidden_tag(&self, name: &str) -> bool {
+        self.hidden_tags.iter().any(|t| t.eq_ignore_ascii_case(name))
+    }
+
+    /// 缓存文件的最长保留时间 (`cacheMaxAgeDays`)
+    pub fn cache_max_age(&self) -> Option<std::time::Duration> {
+        self.cache_max_age_days.map(|days| std::time::Duration::from_secs(days * 24 * 60 * 60))
+    }
+
+    /// 标签颜色：优先使用配置中的覆盖值
+    pub fn tag_color(&self, name: &str, notion_color: &impl std::fmt::Debug) -> String {
+        self.tag_meta
+            .get(name)
+            .and_then(|m| m.color.clone())
+            .unwrap_or_else(|| format!("{:?}", notion_color).to_lowercase())
+    }
+
+    /// 输出文件与目录的权限 (`fileMode` / `dirMode`)，未设置的为 None
+    pub fn output_modes(&self) -> Result<(Option<u32>, Option<u32>)> {
+        let parse = |name: &str, value: &Option<String>| -> Result<Option<u32>> {
+            let Some(value) = value else { return Ok(None) };
+            let digits = value.trim().trim_start_matches("0o");
+            match u32::from_str_radix(digits, 8) {
+                Ok(mode) if mode <= 0o7777 => Ok(Some(mode)),
+                _ => anyhow::bail!("{} 应为八进制权限 (如 644)，实际为 {}", name, value),
+            }
+        };
+        Ok((parse("fileMode", &self.file_mode)?, parse("dirMode", &self.dir_mode)?))
+    }
+
+    /// 渲染页面使用的线程数，至少为 1
+    pub fn render_workers(&self) -> usize {
+        self.render_threads
+            .or_else(|| std::thread::available_parallelism().ok().map(|n| n.get()))
+            .unwrap_or(1)
+            .max(1)
+    }
+
+    /// 文章页面到站点根目录的相对路径，由文章的输出路径的目录深度决定
+    pub fn post_root_path(&self) -> String {
+        crate::output::root_path(&self.post_output_path("post"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support;
+    use serde_json::json;
+
+    #[test]
+    fn clean_urls_write_index_html_and_link_with_trailing_slash() {
+        let config = test_support::config(json!({ "cleanUrls": true }));
+        assert_eq!(config.post_url("my-post"), "my-post/");
+        assert_eq!(config.post_output_path("my-post"), "public/my-post/index.html");
+        assert_eq!(config.post_root_path(), "..");
+    }
+
+    #[test]
+    fn default_urls_keep_html_extension() {
+        let config = test_support::config(json!({}));
+        assert_eq!(config.post_url("my-post"), "my-post.html");
+        assert_eq!(config.post_output_path("my-post"), "public/my-post.html");
+        assert_eq!(config.post_root_path(), ".");
+    }
+
+    #[test]
+    fn output_modes_parse_octal() {
+        let config = test_support::config(json!({ "fileMode": "644", "dirMode": "0o755" }));
+        assert_eq!(config.output_modes().unwrap(), (Some(0o644), Some(0o755)));
+        assert_eq!(test_support::config(json!({})).output_modes().unwrap(), (None, None));
+        assert!(test_support::config(json!({ "fileMode": "rw-r--r--" })).output_modes().is_err());
+    }
+
+    #[test]
+    fn malformed_config_is_a_config_error() {
+        let dir = test_support::temp_dir("config-error");
+        let path = dir.join("config.json");
+        std::fs::write(&path, "{ \"url\": ").unwrap();
+        let error = super::Config::load(&path).unwrap_err();
+        assert!(matches!(error, crate::BuildError::Config(_)), "{:?}", error);
+        let source = std::error::Error::source(&error).expect("应保留错误来源").to_string();
+        assert!(source.contains("解析配置文件失败"), "{}", source);
+
+        let missing = super::Config::load(dir.join("missing.json")).unwrap_err();
+        assert!(matches!(missing, crate::BuildError::Config(_)), "{:?}", missing);
+    }
+
+    #[test]
+    fn trailing_slash_policy_shapes_every_link() {
+        let always = test_support::config(json!({ "trailingSlash": "always" }));
+        assert_eq!(always.post_url("my-post"), "my-post/");
+        assert_eq!(always.post_output_path("my-post"), "public/my-post/index.html");
+        assert_eq!(always.listing_url("tag/rust"), "tag/rust/");
+        assert_eq!(always.home_url(), "");
+
+        let never = test_support::config(json!({ "trailingSlash": "never" }));
+        assert_eq!(never.post_url("my-post"), "my-post.html");
+        assert_eq!(never.listing_url("tag/rust"), "tag/rust.html");
+        assert_eq!(never.home_url(), "index.html");
+
+        let extensionless = test_support::config(json!({ "trailingSlash": "never", "cleanUrls": true }));
+        assert_eq!(extensionless.post_url("my-post"), "my-post");
+        assert_eq!(extensionless.post_output_path("my-post"), "public/my-post.html");
+        assert_eq!(extensionless.home_url(), "");
+    }
+}
@@ -0,0 +1,66 @@
+/// 库接口 (`Config::load` 与 `Site` 的构建、渲染) 返回的错误。
+///
+/// 内部仍以 anyhow 逐层附加上下文，在库的边界按所处阶段与错误来源归类，
+/// 调用方可以按类型分别处理 (例如只在 Notion 错误时重试)。完整的上下文链可通过 `source()` 取得
+#[derive(Debug, thiserror::Error)]
+pub enum BuildError {
+    /// 配置文件无法读取或解析，或配置项无效
+    #[error("配置错误")]
+    Config(#[source] anyhow::Error),
+    /// 请求 Notion API 或抓取页面内容失败
+    #[error("从 Notion 抓取数据失败")]
+    Notion(#[source] anyhow::Error),
+    /// 生成页面内容失败
+    #[error("渲染失败")]
+    Render(#[source] anyhow::Error),
+    /// 读写文件失败
+    #[error("文件读写失败")]
+    Io(#[source] anyhow::Error),
+    /// 模板加载或渲染失败
+    #[error("模板错误")]
+    Template(#[source] anyhow::Error),
+}
+
+impl BuildError {
+    /// 抓取阶段的错误：文件读写错误归为 `Io`，其余归为 `Notion`
+    pub(crate) fn fetch(err: anyhow::Error) -> Self {
+        if caused_by::<std::io::Error>(&err) {
+            BuildError::Io(err)
+        } else {
+            BuildError::Notion(err)
+        }
+    }
+
+    /// 渲染阶段的错误：模板错误归为 `Template`，文件读写错误归为 `Io`，其余归为 `Render`
+    pub(crate) fn render(err: anyhow::Error) -> Self {
+        if caused_by::<tera::Error>(&err) {
+            BuildError::Template(err)
+        } else if caused_by::<std::io::Error>(&err) {
+            BuildError::Io(err)
+        } else {
+            BuildError::Render(err)
+        }
+    }
+}
+
+/// 错误链中是否有类型为 `E` 的错误
+fn caused_by<E: std::error::Error + Send + Sync + 'static>(err: &anyhow::Error) -> bool {
+    err.chain().any(|e| e.is::<E>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_errors_are_classified_by_cause() {
+        let template: anyhow::Error = tera::Error::msg("bad template").into();
+        assert!(matches!(BuildError::render(template.context("渲染首页失败")), BuildError::Template(_)));
+        let io: anyhow::Error = std::io::Error::other("disk full").into();
+        assert!(matches!(BuildError::render(io), BuildError::Io(_)));
+        assert!(matches!(BuildError::render(anyhow::anyhow!("other")), BuildError::Render(_)));
+        let io: anyhow::Error = std::io::Error::other("disk full").into();
+        assert!(matches!(BuildError::fetch(io), BuildError::Io(_)));
+        assert!(matches!(BuildError::fetch(anyhow::anyhow!("unauthorized")), BuildError::Notion(_)));
+    }
+}
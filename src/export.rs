@@ -0,0 +1,129 @@
+use crate::model::PostMetadataWithContent;
+use crate::site::BuildResult;
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+
+/// 导出为 Hugo 的 page bundle：`<out_dir>/content/posts/<slug>/index.md`，
+/// 正文中的图片下载到同一目录并改写为相对路径
+pub async fn export_hugo(result: &BuildResult, out_dir: &Path) -> Result<()> {
+    let http = reqwest::Client::new();
+    for post in &result.posts {
+        println!(">>> 正在导出: {}", post.meta.title);
+        let bundle = out_dir.join("content").join("posts").join(&post.meta.slug);
+        fs::create_dir_all(&bundle)?;
+
+        let body = localize_images(&http, &post.markdown, &bundle).await;
+        let mut md = front_matter(post);
+        md.push_str(&body);
+        md.push('\n');
+        fs::write(bundle.join("index.md"), md)?;
+    }
+    Ok(())
+}
+
+/// Hugo front matter (YAML)。字符串统一用 JSON 转义，JSON 字符串同时也是合法的 YAML
+fn front_matter(post: &PostMetadataWithContent) -> String {
+    let quote = |s: &str| serde_json::to_string(s).unwrap_or_default();
+    let meta = &post.meta;
+
+    let mut fm = String::from("---\n");
+    fm.push_str(&format!("title: {}\n", quote(&meta.title)));
+    if !meta.date.is_empty() {
+        fm.push_str(&format!("date: {}\n", meta.date));
+    }
+    let tags: Vec<&str> = meta.tags.iter().map(|t| t.name.as_str()).collect();
+    fm.push_str(&format!("tags: {}\n", serde_json::to_string(&tags).unwrap_or_default()));
+    fm.push_str(&format!("draft: {}\n", !meta.publish));
+    if let Some(cover) = &meta.cover {
+        fm.push_str(&format!("cover: {}\n", quote(cover)));
+    }
+    if let Some(description) = &post.description {
+        fm.push_str(&format!("description: {}\n", quote(description)));
+    }
+    fm.push_str("---\n\n");
+    fm
+}
+
+/// 下载 Markdown 中引用的远程图片到 bundle 目录，并替换为本地文件名。
+/// 下载失败时保留原链接并打印警告
+async fn localize_images(http: &reqwest::Client, markdown: &str, bundle: &Path) -> String {
+    let mut output = markdown.to_string();
+    let mut index = 0;
+    for url in image_urls(markdown) {
+        if !url.starts_with("http") {
+            continue;
+        }
+        index += 1;
+        let filename = format!("image-{}.{}", index, extension_of(&url));
+        match download(http, &url, &bundle.join(&filename)).await {
+            Ok(()) => output = output.replace(&format!("]({})", url), &format!("]({})", filename)),
+            Err(e) => println!(">>> 警告: 下载图片失败 {}: {}", url, e),
+        }
+    }
+    output
+}
+
+async fn download(http: &reqwest::Client, url: &str, dest: &Path) -> Result<()> {
+    let bytes = http.get(url).send().await?.error_for_status()?.bytes().await?;
+    fs::write(dest, &bytes)?;
+    Ok(())
+}
+
+/// 提取 `![alt](url)` 中的 url，去重并保持出现顺序
+fn image_urls(markdown: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    let mut rest = markdown;
+    while let Some(start) = rest.find("![") {
+        rest = &rest[start + 2..];
+        let Some(mid) = rest.find("](") else { break };
+        rest = &rest[mid + 2..];
+        let Some(end) = rest.find(')') else { break };
+        let url = rest[..end].to_string();
+        if !urls.contains(&url) {
+            urls.push(url);
+        }
+        rest = &rest[end..];
+    }
+    urls
+}
+
+/// 从 URL 路径推断扩展名，无法推断时默认为 png
+fn extension_of(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let last = path.rsplit('/').next().unwrap_or("");
+    match last.rsplit_once('.') {
+        Some((_, ext)) if !ext.is_empty() && ext.len() <= 5 && ext.chars().all(|c| c.is_ascii_alphanumeric()) => {
+            ext.to_lowercase()
+        }
+        _ => "png".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[tokio::test]
+    async fn post_exports_as_page_bundle_with_downloaded_images() {
+        let server = test_support::MockServer::start("image/png", b"\x89PNG fake".to_vec()).await;
+        let config = test_support::config(serde_json::json!({}));
+        let mut meta = test_support::meta("hello-hugo", "2024-05-01");
+        meta.tags = vec![test_support::tag("Rust")];
+        let mut post = test_support::post(meta, "");
+        post.markdown = format!("Intro\n\n![diagram]({}/files/diagram.png?sig=1)", server.url);
+        let result = test_support::result(&config, vec![post]);
+
+        let out = test_support::temp_dir("hugo");
+        export_hugo(&result, &out).await.unwrap();
+
+        let bundle = out.join("content/posts/hello-hugo");
+        let md = fs::read_to_string(bundle.join("index.md")).unwrap();
+        assert!(md.starts_with("---\ntitle: \"hello hugo\"\ndate: 2024-05-01\ntags: [\"Rust\"]\ndraft: false\n"), "{}", md);
+        assert!(md.contains("![diagram](image-1.png)"), "{}", md);
+        assert_eq!(fs::read(bundle.join("image-1.png")).unwrap(), b"\x89PNG fake");
+        assert!(server.requests()[0].starts_with("GET /files/diagram.png?sig=1 "));
+        let _ = fs::remove_dir_all(out);
+    }
+}
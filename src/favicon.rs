@@ -0,0 +1,252 @@
+use anyhow::{Context, Result};
+
+use crate::cache::Cache;
+use crate::model::FaviconLink;
+use crate::renderer::{escape_html, IconHtml};
+
+/// 由站点图标生成的 favicon：`<link>` 标签信息与写入 `public/` 根目录的文件
+#[derive(Debug, Default)]
+pub struct FaviconBundle {
+    pub links: Vec<FaviconLink>,
+    pub files: Vec<FaviconFile>,
+}
+
+#[derive(Debug)]
+pub struct FaviconFile {
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+/// PNG 图标的输出尺寸：(文件名, 边长, rel)
+const PNG_SIZES: &[(&str, u32, &str)] = &[
+    ("favicon-16.png", 16, "icon"),
+    ("favicon-32.png", 32, "icon"),
+    ("apple-touch-icon.png", 180, "apple-touch-icon"),
+];
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// 生成 favicon：Emoji 图标写为包含该 Emoji 的 SVG；图片图标下载后 (经 `cache`)，
+/// PNG 缩放为多个尺寸及 `apple-touch-icon`，SVG 原样使用。其他格式无法缩放，不生成
+pub async fn generate(icon: &IconHtml, cache: &Cache) -> Result<FaviconBundle> {
+    let mut bundle = FaviconBundle::default();
+    match icon {
+        IconHtml::Emoji(emoji) => bundle.push_svg(emoji_svg(emoji).into_bytes()),
+        IconHtml::Image(url) => {
+            println!(">>> 正在下载站点图标: {}", url);
+            let data = cache.get_or_fetch(url, || async {
+                let bytes = reqwest::get(url).await?.error_for_status()?.bytes().await
+                    .with_context(|| format!("下载站点图标失败: {}", url))?;
+                Ok(bytes.to_vec())
+            }).await?;
+            if data.starts_with(PNG_SIGNATURE) {
+                let image = Rgba::decode_png(&data).context("无法解析站点图标")?;
+                for &(name, size, rel) in PNG_SIZES {
+                    bundle.files.push(FaviconFile { name: name.to_string(), data: image.resize(size).encode_png()? });
+                    bundle.links.push(FaviconLink {
+                        rel: rel.to_string(),
+                        mime_type: "image/png".to_string(),
+                        sizes: Some(format!("{0}x{0}", size)),
+                        href: name.to_string(),
+                    });
+                }
+            } else if is_svg(&data) {
+                bundle.push_svg(data);
+            } else {
+                println!(">>> 站点图标不是 PNG 或 SVG，跳过生成 favicon: {}", url);
+            }
+        }
+    }
+    Ok(bundle)
+}
+
+impl FaviconBundle {
+    fn push_svg(&mut self, data: Vec<u8>) {
+        self.files.push(FaviconFile { name: "favicon.svg".to_string(), data });
+        self.links.push(FaviconLink {
+            rel: "icon".to_string(),
+            mime_type: "image/svg+xml".to_string(),
+            sizes: None,
+            href: "favicon.svg".to_string(),
+        });
+    }
+}
+
+/// Emoji 居中绘制在 100×100 的画布上，由浏览器使用系统的 Emoji 字体渲染
+fn emoji_svg(emoji: &str) -> String {
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100"><text x="50" y="50" font-size="80" text-anchor="middle" dominant-baseline="central">{}</text></svg>"#,
+        escape_html(emoji)
+    )
+}
+
+fn is_svg(data: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&data[..data.len().min(512)]);
+    let head = head.trim_start();
+    head.starts_with("<svg") || (head.starts_with("<?xml") && head.contains("<svg"))
+}
+
+/// RGBA 图像
+struct Rgba {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Rgba {
+    /// 解码 PNG，各种颜色类型与位深统一转换为 8 位 RGBA
+    fn decode_png(data: &[u8]) -> Result<Self> {
+        let mut decoder = png::Decoder::new(data);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf)?;
+        let samples = &buf[..info.buffer_size()];
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => samples.to_vec(),
+            png::ColorType::Rgb => samples.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+            png::ColorType::GrayscaleAlpha => samples.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+            png::ColorType::Grayscale => samples.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+            png::ColorType::Indexed => anyhow::bail!("未展开的调色板图像"),
+        };
+        Ok(Self { width: info.width, height: info.height, pixels })
+    }
+
+    /// 缩放为 `size`×`size` 的正方形：非正方形的图像居中放置，四周透明。
+    /// 每个目标像素取其覆盖的源像素的平均值 (按透明度加权，避免透明边缘发黑)
+    fn resize(&self, size: u32) -> Self {
+        let side = self.width.max(self.height) as f32;
+        let scale = side / size as f32;
+        let offset_x = (side - self.width as f32) / 2.0;
+        let offset_y = (side - self.height as f32) / 2.0;
+        let mut pixels = vec![0u8; (size * size * 4) as usize];
+        for ty in 0..size {
+            for tx in 0..size {
+                // 目标像素在源图像上覆盖的范围，至少包含一个源像素
+                let x0 = tx as f32 * scale - offset_x;
+                let y0 = ty as f32 * scale - offset_y;
+                let sx0 = x0.floor().max(0.0) as u32;
+                let sy0 = y0.floor().max(0.0) as u32;
+                let sx1 = ((x0 + scale).ceil().max(0.0) as u32).min(self.width).max(sx0 + 1);
+                let sy1 = ((y0 + scale).ceil().max(0.0) as u32).min(self.height).max(sy0 + 1);
+                if sx0 >= self.width || sy0 >= self.height || x0 + scale <= 0.0 || y0 + scale <= 0.0 {
+                    continue;
+                }
+                let mut sum = [0f32; 4];
+                let mut count = 0f32;
+                for sy in sy0..sy1 {
+                    for sx in sx0..sx1 {
+                        let i = ((sy * self.width + sx) * 4) as usize;
+                        let alpha = self.pixels[i + 3] as f32;
+                        for (c, total) in sum.iter_mut().take(3).enumerate() {
+                            *total += self.pixels[i + c] as f32 * alpha;
+                        }
+                        sum[3] += alpha;
+                        count += 1.0;
+                    }
+                }
+                let i = ((ty * size + tx) * 4) as usize;
+                if sum[3] > 0.0 {
+                    for c in 0..3 {
+                        pixels[i + c] = (sum[c] / sum[3]).round() as u8;
+                    }
+                }
+                pixels[i + 3] = (sum[3] / count).round() as u8;
+            }
+        }
+        Self { width: size, height: size, pixels }
+    }
+
+    fn encode_png(&self) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        let mut encoder = png::Encoder::new(&mut data, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        writer.finish()?;
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::Output;
+    use crate::site::Site;
+    use crate::test_support;
+
+    #[tokio::test]
+    async fn second_build_reuses_the_cached_icon() {
+        let svg = b"<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 1 1\"></svg>".to_vec();
+        let server = test_support::MockServer::start("image/svg+xml", svg.clone()).await;
+        let icon = IconHtml::Image(format!("{}/icon.svg", server.url));
+        let dir = test_support::temp_dir("favicon-cache");
+
+        for _ in 0..2 {
+            // 每次构建重新打开缓存目录，与两次独立运行一致
+            let cache = Cache::open(&dir, None, None).unwrap();
+            let bundle = generate(&icon, &cache).await.unwrap();
+            assert_eq!(bundle.files.len(), 1);
+            assert_eq!(bundle.files[0].data, svg);
+        }
+        assert_eq!(server.requests().len(), 1, "第二次构建应直接使用缓存");
+    }
+
+    #[tokio::test]
+    async fn png_icon_produces_resized_favicons_and_links() {
+        let mut png = Vec::new();
+        image::RgbaImage::from_pixel(64, 64, image::Rgba([255, 0, 0, 255]))
+            .write_with_encoder(image::codecs::png::PngEncoder::new(&mut png))
+            .unwrap();
+        let server = test_support::MockServer::start("image/png", png).await;
+        let cache = Cache::open(test_support::temp_dir("favicon-png"), None, None).unwrap();
+        let bundle = generate(&IconHtml::Image(format!("{}/icon.png", server.url)), &cache).await.unwrap();
+
+        let names: Vec<&str> = bundle.files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["favicon-16.png", "favicon-32.png", "apple-touch-icon.png"]);
+        let favicon = image::load_from_memory(&bundle.files[1].data).unwrap();
+        assert_eq!((favicon.width(), favicon.height()), (32, 32));
+
+        let link = serde_json::to_value(&bundle.links[1]).unwrap();
+        assert_eq!(
+            link,
+            serde_json::json!({ "rel": "icon", "mimeType": "image/png", "sizes": "32x32", "href": "favicon-32.png" })
+        );
+        assert_eq!(bundle.links[2].rel, "apple-touch-icon");
+    }
+
+    #[tokio::test]
+    async fn emoji_icon_produces_an_svg_favicon() {
+        let cache = Cache::open(test_support::temp_dir("favicon-emoji"), None, None).unwrap();
+        let bundle = generate(&IconHtml::Emoji("🦀".to_string()), &cache).await.unwrap();
+        assert_eq!(bundle.files.len(), 1);
+        assert_eq!(bundle.files[0].name, "favicon.svg");
+        assert!(String::from_utf8_lossy(&bundle.files[0].data).contains("🦀"));
+        assert_eq!(bundle.links[0].mime_type, "image/svg+xml");
+    }
+
+    #[tokio::test]
+    async fn database_icon_becomes_a_favicon_in_every_head() {
+        let pages = vec![test_support::page("p1", "my-post", &[], serde_json::json!({}))];
+        let mut data_source: serde_json::Value = serde_json::from_slice(&test_support::data_source(&pages)).unwrap();
+        data_source["icon"] = serde_json::json!({ "type": "emoji", "emoji": "🦀" });
+        let server = test_support::MockServer::with_routes(vec![
+            (test_support::QUERY_PATH, "application/json", test_support::list(pages)),
+            ("/data_sources/", "application/json", data_source.to_string().into_bytes()),
+            ("/blocks/", "application/json", test_support::list(Vec::new())),
+        ])
+        .await;
+        let config = test_support::config(serde_json::json!({ "favicon": true }));
+        let result = Site::build_from(&server.url, &config).await.unwrap();
+        assert_eq!(result.site_meta.icon_url.as_deref(), Some("🦀"));
+
+        let mut output = Output::new(true).with_contents();
+        Site::render_to(&config, &result, &mut output).unwrap();
+        assert!(String::from_utf8_lossy(output.contents("public/favicon.svg").unwrap()).contains("🦀"));
+        for path in ["public/index.html", "public/my-post.html"] {
+            let html = String::from_utf8_lossy(output.contents(path).unwrap()).replace("&#x2F;", "/");
+            assert!(html.contains("<link rel=\"icon\" type=\"image/svg+xml\" href=\"./favicon.svg\">"), "{}: {}", path, html);
+        }
+    }
+}
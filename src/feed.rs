@@ -0,0 +1,273 @@
+use crate::config::Config;
+use crate::model::{FeedLink, PostMetadata, PostMetadataWithContent};
+use crate::renderer::escape_html;
+use crate::site::BuildResult;
+
+/// Feed 的输出路径 (相对于 `public/`)
+pub const RSS_PATH: &str = "feed.xml";
+/// 开启 `indexFeed` 时合并 Feed 的输出路径，内容与 `RSS_PATH` 相同
+pub const INDEX_PATH: &str = "index.xml";
+
+/// 对外公布的 Feed 地址：`feedUrl`，未设置时由 `siteUrl` 推导 (开启 `indexFeed` 时指向 `index.xml`)
+pub fn feed_url(config: &Config) -> Option<String> {
+    if let Some(url) = &config.feed_url {
+        return Some(url.clone());
+    }
+    let path = if config.index_feed { INDEX_PATH } else { RSS_PATH };
+    config.site_url().map(|site_url| format!("{}/{}", site_url, path))
+}
+
+/// 本次构建生成的 Feed (设置了 `siteUrl` 时生成 RSS)；关闭 `feedDiscovery` 时为空
+pub fn feed_links(config: &Config) -> Vec<FeedLink> {
+    match config.site_url() {
+        Some(site_url) if config.feed_discovery => vec![FeedLink {
+            title: config.site_title(),
+            url: format!("{}/{}", site_url, RSS_PATH),
+            mime_type: "application/rss+xml".to_string(),
+        }],
+        _ => Vec::new(),
+    }
+}
+
+/// 生成 RSS 2.0 Feed：最新的 `feedLimit` 篇文章，内容为完整正文。
+/// 正文中的相对地址在 Feed 阅读器中无法解析，统一改写为基于 `site_url` 的绝对地址
+pub fn rss_xml(config: &Config, site_url: &str, result: &BuildResult) -> String {
+    let mut s = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    s.push_str("<rss version=\"2.0\">\n<channel>\n");
+    s.push_str(&format!("  <title>{}</title>\n", escape_html(&result.site_meta.title)));
+    s.push_str(&format!("  <link>{}/</link>\n", escape_html(site_url)));
+    // 频道描述取站点的 `description`，未设置或为空时退回站点标题 (RSS 2.0 要求该元素非空)
+    let description = config.description.as_deref().filter(|d| !d.trim().is_empty()).unwrap_or(&result.site_meta.title);
+    s.push_str(&format!("  <description>{}</description>\n", escape_html(description)));
+    for post in feed_posts(config, result) {
+        let link = format!("{}/{}", site_url, post.meta.url);
+        s.push_str("  <item>\n");
+        s.push_str(&format!("    <title>{}</title>\n", escape_html(&post.meta.title)));
+        s.push_str(&format!("    <link>{}</link>\n", escape_html(&link)));
+        s.push_str(&format!("    <guid>{}</guid>\n", escape_html(&link)));
+        if let Some(date) = rfc822_date(&post.meta.date) {
+            s.push_str(&format!("    <pubDate>{}</pubDate>\n", date));
+        }
+        let content = absolutize_urls(&post.content, &link);
+        s.push_str(&format!("    <description>{}</description>\n", escape_html(&content)));
+        s.push_str("  </item>\n");
+    }
+    s.push_str("</channel>\n</rss>\n");
+    s
+}
+
+/// 进入 Feed 的文章：出现在列表中、且未被 Feed 过滤条件排除的文章，按日期从新到旧取前 `feedLimit` 篇。
+/// 未发布的草稿不在 `result.posts` 中，不会进入 Feed
+fn feed_posts<'a>(config: &Config, result: &'a BuildResult) -> Vec<&'a PostMetadataWithContent> {
+    let mut posts: Vec<_> = result.posts.iter().filter(|p| p.meta.in_list && in_feed(config, &p.meta)).collect();
+    posts.sort_by(|a, b| b.meta.date.cmp(&a.meta.date));
+    posts.truncate(config.feed_limit);
+    posts
+}
+
+/// 文章是否通过 Feed 的过滤条件 (`feedExcludeTags`、`feedExcludeHiddenTags`、`feedRequireInFeed`)
+fn in_feed(config: &Config, meta: &PostMetadata) -> bool {
+    if config.feed_require_in_feed && !meta.in_feed {
+        return false;
+    }
+    if config.feed_exclude_hidden_tags && !meta.hidden_tags.is_empty() {
+        return false;
+    }
+    let excluded = |name: &str| config.feed_exclude_tags.iter().any(|t| t.eq_ignore_ascii_case(name));
+    !meta.tags.iter().chain(&meta.hidden_tags).any(|t| excluded(&t.name))
+}
+
+/// 将 HTML 中 `src` / `href` 的相对地址改写为绝对地址。
+/// `page_url` 为该 HTML 所在页面的绝对地址：`./a.png`、`../tag/x.html` 相对于页面所在目录，
+/// `/a.png` 相对于站点的域名，`#id` 指向页面本身；已带协议的地址 (含 `mailto:`、`data:`) 与 `//` 开头的地址不变
+pub fn absolutize_urls(html: &str, page_url: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some((start, attr)) = next_url_attr(rest) {
+        let value_start = start + attr.len();
+        let Some(len) = rest[value_start..].find('"') else { break };
+        out.push_str(&rest[..value_start]);
+        out.push_str(&resolve_url(page_url, &rest[value_start..value_start + len]));
+        rest = &rest[value_start + len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// 下一个 `src="` 或 `href="` 的位置
+fn next_url_attr(html: &str) -> Option<(usize, &'static str)> {
+    [" src=\"", " href=\""]
+        .into_iter()
+        .filter_map(|attr| html.find(attr).map(|i| (i, attr)))
+        .min_by_key(|(i, _)| *i)
+}
+
+/// 以 `page_url` 为基准解析地址
+fn resolve_url(page_url: &str, url: &str) -> String {
+    if url.is_empty() || url.starts_with("//") || has_scheme(url) {
+        return url.to_string();
+    }
+    if url.starts_with('#') {
+        let page = page_url.split('#').next().unwrap_or(page_url);
+        return format!("{}{}", page, url);
+    }
+    let origin_end = page_url.find("://").map_or(0, |i| i + 3);
+    let origin_end = page_url[origin_end..].find('/').map_or(page_url.len(), |i| origin_end + i);
+    let origin = &page_url[..origin_end];
+    if url.starts_with('/') {
+        return format!("{}{}", origin, url);
+    }
+
+    // 页面所在目录的路径段，再依次应用相对路径中的 `.` 与 `..`
+    let path = &page_url[origin_end..];
+    let mut segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    if !path.ends_with('/') {
+        segments.pop();
+    }
+    let (relative, suffix) = match url.find(['?', '#']) {
+        Some(i) => url.split_at(i),
+        None => (url, ""),
+    };
+    let mut parts = relative.split('/').peekable();
+    while let Some(part) = parts.next() {
+        match part {
+            "." => {}
+            ".." => {
+                segments.pop();
+            }
+            // 末尾的空段表示目录 (如 `tag/`)，保留结尾的 `/`
+            "" if parts.peek().is_none() => segments.push(""),
+            "" => {}
+            part => segments.push(part),
+        }
+    }
+    format!("{}/{}{}", origin, segments.join("/"), suffix)
+}
+
+/// 地址是否以 `scheme:` 开头 (如 `https:`、`mailto:`、`data:`)
+fn has_scheme(url: &str) -> bool {
+    match url.find(':') {
+        Some(i) => {
+            let scheme = &url[..i];
+            !scheme.is_empty()
+                && scheme.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+                && scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        }
+        None => false,
+    }
+}
+
+/// `YYYY-MM-DD` (或以其开头的时间) 转为 RFC 822 日期，日期无效时返回 None
+fn rfc822_date(date: &str) -> Option<String> {
+    const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+    let date = date.get(..10)?;
+    let mut parts = date.split('-');
+    let year: u32 = parts.next()?.parse().ok()?;
+    let month: usize = parts.next()?.parse().ok()?;
+    let day: u32 = parts.next()?.parse().ok()?;
+    let month = MONTHS.get(month.checked_sub(1)?)?;
+    Some(format!("{:02} {} {} 00:00:00 +0000", day, month, year))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::Output;
+    use crate::site::Site;
+    use crate::test_support;
+    use serde_json::json;
+
+    /// 按 `pages` 模拟 Notion 数据库构建并渲染整站，返回 `index.html` 与 `feed.xml`
+    async fn index_and_feed(config: &Config, pages: Vec<serde_json::Value>) -> (String, String) {
+        let server = test_support::notion_site(pages, Vec::new()).await;
+        let result = Site::build_from(&server.url, config).await.unwrap();
+        let mut output = Output::new(true).with_contents();
+        Site::render_to(config, &result, &mut output).unwrap();
+        let read = |path: &str| String::from_utf8_lossy(output.contents(path).unwrap()).replace("&#x2F;", "/");
+        (read("public/index.html"), read("public/feed.xml"))
+    }
+
+    #[test]
+    fn relative_urls_in_feed_content_are_absolutized() {
+        let config = test_support::config(json!({ "siteUrl": "https://blog.example.com" }));
+        let post = test_support::post(
+            test_support::meta("photos", "2024-01-01"),
+            "<p><img src=\"images/cat.png\"><a href=\"/about.html\">About</a><a href=\"https://x.org/\">X</a></p>",
+        );
+        let result = test_support::result(&config, vec![post]);
+        let xml = rss_xml(&config, "https://blog.example.com", &result);
+        assert!(xml.contains("src=&quot;https://blog.example.com/images/cat.png&quot;"), "{}", xml);
+        assert!(xml.contains("href=&quot;https://blog.example.com/about.html&quot;"), "{}", xml);
+        assert!(xml.contains("href=&quot;https://x.org/&quot;"), "{}", xml);
+    }
+
+    #[test]
+    fn channel_description_falls_back_to_title() {
+        let config = test_support::config(json!({ "description": "Notes on Rust" }));
+        let result = test_support::result(&config, Vec::new());
+        let xml = rss_xml(&config, "https://blog.example.com", &result);
+        assert!(xml.contains("<description>Notes on Rust</description>"), "{}", xml);
+
+        let config = test_support::config(json!({ "description": "  " }));
+        let xml = rss_xml(&config, "https://blog.example.com", &result);
+        assert!(xml.contains("<description>Test Blog</description>"), "{}", xml);
+    }
+
+    #[tokio::test]
+    async fn hidden_tag_post_is_listed_but_left_out_of_the_feed() {
+        let config = test_support::config(json!({
+            "siteUrl": "https://blog.example.com",
+            "hiddenTags": ["Internal"],
+            "feedExcludeHiddenTags": true
+        }));
+        let pages = vec![
+            test_support::page("p1", "public-post", &[], json!({})),
+            test_support::page("p2", "internal-post", &["Internal"], json!({})),
+        ];
+        let (index, feed) = index_and_feed(&config, pages).await;
+        assert!(index.contains("internal-post.html") && index.contains("public-post.html"), "{}", index);
+        assert!(feed.contains("public-post.html"), "{}", feed);
+        assert!(!feed.contains("internal-post"), "{}", feed);
+    }
+
+    #[tokio::test]
+    async fn feed_require_in_feed_keeps_only_checked_posts() {
+        let config = test_support::config(json!({ "siteUrl": "https://blog.example.com", "feedRequireInFeed": true }));
+        let in_feed = json!({ "inFeed": { "type": "checkbox", "checkbox": true } });
+        let pages = vec![
+            test_support::page("p1", "chosen", &[], in_feed),
+            test_support::page("p2", "other", &[], json!({})),
+        ];
+        let (index, feed) = index_and_feed(&config, pages).await;
+        assert!(index.contains("other.html"), "{}", index);
+        assert!(feed.contains("chosen.html") && !feed.contains("other.html"), "{}", feed);
+    }
+
+    #[test]
+    fn feed_links_list_the_rss_feed_when_discovery_is_on() {
+        let config = test_support::config(json!({ "siteUrl": "https://blog.example.com/", "feedDiscovery": true }));
+        let links = serde_json::to_value(feed_links(&config)).unwrap();
+        assert_eq!(
+            links,
+            json!([{ "title": "Test Blog", "url": "https://blog.example.com/feed.xml", "mimeType": "application/rss+xml" }])
+        );
+
+        assert!(feed_links(&test_support::config(json!({ "siteUrl": "https://blog.example.com" }))).is_empty());
+        assert!(feed_links(&test_support::config(json!({ "feedDiscovery": true }))).is_empty());
+    }
+
+    #[tokio::test]
+    async fn feed_discovery_link_is_rendered_in_head() {
+        let config = test_support::config(json!({ "siteUrl": "https://blog.example.com", "feedDiscovery": true }));
+        let server = test_support::notion_site(vec![test_support::page("p1", "my-post", &[], json!({}))], Vec::new()).await;
+        let result = Site::build_from(&server.url, &config).await.unwrap();
+        assert_eq!(result.site_meta.feeds.len(), 1);
+        let mut output = Output::new(true).with_contents();
+        Site::render_to(&config, &result, &mut output).unwrap();
+        let link = "<link rel=\"alternate\" type=\"application/rss+xml\" title=\"Test Blog\" href=\"https://blog.example.com/feed.xml\">";
+        for path in ["public/index.html", "public/my-post.html"] {
+            let page = String::from_utf8_lossy(output.contents(path).unwrap()).replace("&#x2F;", "/");
+            assert!(page.contains(link), "{}: {}", path, page);
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use anyhow::{Context, Result};
+
+use crate::cache::Cache;
+
+/// 本地化后的字体：改写过的样式表与字体文件，写入 `public/assets/fonts/`
+#[derive(Debug, Default)]
+pub struct FontBundle {
+    /// 合并后的样式表，`@font-face` 的 src 已指向同目录下的字体文件
+    pub css: String,
+    pub files: Vec<FontFile>,
+}
+
+#[derive(Debug)]
+pub struct FontFile {
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+/// 样式表与字体文件的输出目录 (相对于站点根目录)
+pub const FONTS_DIR: &str = "assets/fonts";
+pub const FONTS_CSS: &str = "assets/fonts/fonts.css";
+
+/// Google Fonts 按 User-Agent 决定返回的字体格式，伪装成现代浏览器以获取 woff2
+const USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0 Safari/537.36";
+
+/// 下载字体样式表及其引用的字体文件，并把 `url(...)` 改写为本地文件名。
+/// 字体文件的地址带有版本，下载结果存入 `cache`，再次构建时直接复用
+pub async fn self_host(css_urls: &[String], cache: &Cache) -> Result<FontBundle> {
+    let http = reqwest::Client::builder().user_agent(USER_AGENT).build()?;
+    let mut bundle = FontBundle::default();
+    for css_url in css_urls {
+        println!(">>> 正在下载字体样式表: {}", css_url);
+        let css = http.get(css_url).send().await?.error_for_status()?.text().await
+            .with_context(|| format!("下载字体样式表失败: {}", css_url))?;
+
+        let mut rewritten = css.clone();
+        for url in font_urls(&css) {
+            let name = format!("font-{}.{}", bundle.files.len(), extension_of(&url));
+            let absolute = resolve_url(css_url, &url);
+            let data = cache.get_or_fetch(&absolute, || async {
+                let bytes = http.get(&absolute).send().await?.error_for_status()?.bytes().await
+                    .with_context(|| format!("下载字体失败: {}", absolute))?;
+                Ok(bytes.to_vec())
+            }).await?;
+            rewritten = rewritten.replace(&url, &name);
+            bundle.files.push(FontFile { name, data });
+        }
+        bundle.css.push_str(&rewritten);
+        bundle.css.push('\n');
+    }
+    Ok(bundle)
+}
+
+/// 提取样式表中 `url(...)` 引用的地址 (去掉引号)，去重并保持出现顺序
+fn font_urls(css: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    let mut rest = css;
+    while let Some(start) = rest.find("url(") {
+        rest = &rest[start + 4..];
+        let Some(end) = rest.find(')') else { break };
+        let url = rest[..end].trim().trim_matches(['"', '\'']).to_string();
+        if !url.is_empty() && !url.starts_with("data:") && !urls.contains(&url) {
+            urls.push(url);
+        }
+        rest = &rest[end..];
+    }
+    urls
+}
+
+/// 把样式表中的相对地址解析为完整 URL
+fn resolve_url(base: &str, url: &str) -> String {
+    if url.contains("://") {
+        return url.to_string();
+    }
+    if let Some(rest) = url.strip_prefix("//") {
+        let scheme = base.split("://").next().unwrap_or("https");
+        return format!("{}://{}", scheme, rest);
+    }
+    let (scheme, after) = base.split_once("://").unwrap_or(("https", base));
+    let host = after.split('/').next().unwrap_or(after);
+    if url.starts_with('/') {
+        format!("{}://{}{}", scheme, host, url)
+    } else {
+        let dir = base.split(['?', '#']).next().unwrap_or(base);
+        let dir = &dir[..dir.rfind('/').map_or(dir.len(), |i| i + 1)];
+        format!("{}{}", dir, url)
+    }
+}
+
+/// 从 URL 路径推断字体扩展名，无法推断时默认为 woff2
+fn extension_of(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let last = path.rsplit('/').next().unwrap_or("");
+    match last.rsplit_once('.') {
+        Some((_, ext)) if !ext.is_empty() && ext.len() <= 5 && ext.chars().all(|c| c.is_ascii_alphanumeric()) => {
+            ext.to_lowercase()
+        }
+        _ => "woff2".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[tokio::test]
+    async fn font_face_urls_are_rewritten_to_local_files() {
+        let css = "@font-face { font-family: 'Inter'; src: url(/s/inter/v1/inter.woff2) format('woff2'); }\n\
+                   @font-face { font-family: 'Mono'; src: url(\"fonts/mono.ttf\"); }";
+        let server = test_support::MockServer::with_routes(vec![
+            ("/css", "text/css", css.as_bytes().to_vec()),
+            ("/s/inter/v1/inter.woff2", "font/woff2", b"woff2-data".to_vec()),
+            ("/fonts/mono.ttf", "font/ttf", b"ttf-data".to_vec()),
+        ])
+        .await;
+
+        let cache = Cache::open(test_support::temp_dir("fonts-rewrite"), None, None).unwrap();
+        let bundle = self_host(&[format!("{}/css?family=Inter", server.url)], &cache).await.unwrap();
+        assert!(bundle.css.contains("src: url(font-0.woff2) format('woff2');"), "{}", bundle.css);
+        assert!(bundle.css.contains("src: url(\"font-1.ttf\");"), "{}", bundle.css);
+        assert!(!bundle.css.contains("/s/inter"), "{}", bundle.css);
+        let files: Vec<(&str, &[u8])> = bundle.files.iter().map(|f| (f.name.as_str(), f.data.as_slice())).collect();
+        assert_eq!(files, [("font-0.woff2", &b"woff2-data"[..]), ("font-1.ttf", &b"ttf-data"[..])]);
+    }
+}
@@ -0,0 +1,206 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::cache::Cache;
+use crate::model::{Picture, PictureSource};
+use crate::text;
+
+/// 转换后的图片及作为回退的原图的输出目录 (相对于站点根目录)
+pub const IMAGES_DIR: &str = "assets/images";
+
+/// AVIF 编码速度 (1 最慢、压缩率最高，10 最快)
+const AVIF_SPEED: u8 = 6;
+
+/// 封面图片转换的目标格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    /// 不下载、不转换，直接引用 Notion 中的地址
+    #[default]
+    Original,
+    Webp,
+    Avif,
+}
+
+impl ImageFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Original => "",
+            ImageFormat::Webp => "webp",
+            ImageFormat::Avif => "avif",
+        }
+    }
+
+    fn mime_type(self) -> &'static str {
+        match self {
+            ImageFormat::Original => "",
+            ImageFormat::Webp => "image/webp",
+            ImageFormat::Avif => "image/avif",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ImageFile {
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+/// 转换格式后的图片：模板使用的 `<picture>` 数据，与写入 `IMAGES_DIR` 的文件 (转换结果与原图)
+#[derive(Debug)]
+pub struct ConvertedImage {
+    pub picture: Picture,
+    pub files: Vec<ImageFile>,
+}
+
+/// 下载 (经 `cache`，再次构建时直接复用) 并转换图片，不需要转换时返回 None
+pub async fn localize(url: &str, cache: &Cache, format: ImageFormat, quality: u8) -> Result<Option<ConvertedImage>> {
+    if format == ImageFormat::Original {
+        return Ok(None);
+    }
+    let data = cache.get_or_fetch(url, || async {
+        let bytes = reqwest::get(url).await?.error_for_status()?.bytes().await
+            .with_context(|| format!("下载图片失败: {}", url))?;
+        Ok(bytes.to_vec())
+    }).await?;
+    convert(url, &data, format, quality)
+}
+
+/// 将图片转换为 `format`，原图保留为 `<picture>` 中的回退。文件名由 `key` (通常为原图地址) 的哈希决定。
+/// `original`、GIF (可能是动图)、SVG (矢量图)、已是目标格式或无法识别的图片不转换，返回 None
+pub fn convert(key: &str, data: &[u8], format: ImageFormat, quality: u8) -> Result<Option<ConvertedImage>> {
+    let Ok(source) = image::guess_format(data) else {
+        return Ok(None);
+    };
+    let fallback_extension = match source {
+        image::ImageFormat::Jpeg => "jpg",
+        image::ImageFormat::Png => "png",
+        image::ImageFormat::WebP => "webp",
+        _ => return Ok(None),
+    };
+    if format == ImageFormat::Original || fallback_extension == format.extension() {
+        return Ok(None);
+    }
+
+    let image = image::load_from_memory_with_format(data, source).context("无法解析图片")?;
+    let quality = quality.clamp(1, 100);
+    let encoded = match format {
+        ImageFormat::Webp if image.color().has_alpha() => {
+            let rgba = image.to_rgba8();
+            webp::Encoder::from_rgba(&rgba, rgba.width(), rgba.height()).encode(f32::from(quality)).to_vec()
+        }
+        ImageFormat::Webp => {
+            let rgb = image.to_rgb8();
+            webp::Encoder::from_rgb(&rgb, rgb.width(), rgb.height()).encode(f32::from(quality)).to_vec()
+        }
+        ImageFormat::Avif => {
+            let mut out = Vec::new();
+            let encoder = image::codecs::avif::AvifEncoder::new_with_speed_quality(&mut out, AVIF_SPEED, quality);
+            image.write_with_encoder(encoder).context("AVIF 编码失败")?;
+            out
+        }
+        ImageFormat::Original => unreachable!(),
+    };
+
+    let stem = format!("{}-{}", text::short_hash(key), quality);
+    let converted = format!("{}.{}", stem, format.extension());
+    let fallback = format!("{}.{}", stem, fallback_extension);
+    Ok(Some(ConvertedImage {
+        picture: Picture {
+            sources: vec![PictureSource {
+                srcset: format!("{}/{}", IMAGES_DIR, converted),
+                mime_type: format.mime_type().to_string(),
+            }],
+            fallback: format!("{}/{}", IMAGES_DIR, fallback),
+        },
+        files: vec![
+            ImageFile { name: converted, data: encoded },
+            ImageFile { name: fallback, data: data.to_vec() },
+        ],
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::Output;
+    use crate::site::Site;
+    use crate::test_support::{self, MockServer};
+    use serde_json::json;
+
+    /// 4×4 的纯色 JPEG
+    fn jpeg() -> Vec<u8> {
+        let image = image::RgbImage::from_pixel(4, 4, image::Rgb([200, 80, 40]));
+        let mut data = Vec::new();
+        image.write_with_encoder(image::codecs::jpeg::JpegEncoder::new(&mut data)).unwrap();
+        data
+    }
+
+    #[test]
+    fn jpeg_converts_to_webp_with_jpeg_fallback() {
+        let converted = convert("https://example.com/cover.jpg", &jpeg(), ImageFormat::Webp, 80).unwrap().unwrap();
+        let [webp, fallback] = converted.files.as_slice() else { panic!("{:?}", converted.files) };
+        assert!(webp.name.ends_with(".webp"), "{}", webp.name);
+        assert_eq!((&webp.data[..4], &webp.data[8..12]), (&b"RIFF"[..], &b"WEBP"[..]));
+        assert!(fallback.name.ends_with(".jpg"), "{}", fallback.name);
+        assert_eq!(fallback.data, jpeg());
+
+        assert_eq!(converted.picture.sources.len(), 1);
+        assert_eq!(converted.picture.sources[0].srcset, format!("{}/{}", IMAGES_DIR, webp.name));
+        assert_eq!(converted.picture.sources[0].mime_type, "image/webp");
+        assert_eq!(converted.picture.fallback, format!("{}/{}", IMAGES_DIR, fallback.name));
+    }
+
+    #[test]
+    fn gif_svg_and_original_are_not_converted() {
+        let mut gif = Vec::new();
+        image::RgbaImage::from_pixel(2, 2, image::Rgba([0, 0, 0, 255]))
+            .write_with_encoder(image::codecs::gif::GifEncoder::new(&mut gif))
+            .unwrap();
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="1" height="1"></svg>"#;
+        assert!(convert("a.gif", &gif, ImageFormat::Webp, 80).unwrap().is_none());
+        assert!(convert("a.svg", svg, ImageFormat::Webp, 80).unwrap().is_none());
+        assert!(convert("a.jpg", &jpeg(), ImageFormat::Original, 80).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn localize_downloads_once_through_the_cache() {
+        let server = MockServer::start("image/jpeg", jpeg()).await;
+        let url = format!("{}/cover.jpg", server.url);
+        let cache = Cache::open(test_support::temp_dir("images-localize"), None, None).unwrap();
+
+        assert!(localize(&url, &cache, ImageFormat::Original, 80).await.unwrap().is_none());
+        assert!(server.requests().is_empty(), "original 不应下载图片");
+
+        for _ in 0..2 {
+            let converted = localize(&url, &cache, ImageFormat::Webp, 80).await.unwrap().expect("JPEG 应转换为 WebP");
+            assert_eq!(converted.files.len(), 2);
+            assert_eq!(converted.files[1].data, jpeg());
+        }
+        assert_eq!(server.requests().len(), 1, "第二次应直接使用缓存");
+    }
+
+    #[tokio::test]
+    async fn build_converts_covers_and_render_writes_a_picture() {
+        let images = MockServer::start("image/jpeg", jpeg()).await;
+        let mut page = test_support::page("p1", "a", &[], json!({}));
+        page["cover"] = json!({ "type": "external", "external": { "url": format!("{}/cover.jpg", images.url) } });
+        let server = test_support::notion_site(vec![page], Vec::new()).await;
+        let cache_dir = test_support::temp_dir("images-build");
+        let config = test_support::config(json!({ "imageFormat": "webp", "cacheDir": cache_dir.to_string_lossy() }));
+
+        let result = Site::build_from(&server.url, &config).await.unwrap();
+        let picture = result.posts[0].meta.cover_picture.as_ref().expect("封面应转换");
+        assert_eq!(picture.sources[0].mime_type, "image/webp");
+        assert_eq!(result.images.len(), 2);
+
+        let mut output = Output::new(true).with_contents();
+        Site::render_to(&config, &result, &mut output).unwrap();
+        let html = String::from_utf8_lossy(output.contents("public/a.html").unwrap()).replace("&#x2F;", "/");
+        assert!(html.contains(&format!("<source srcset=\"./{}\" type=\"image/webp\">", picture.sources[0].srcset)), "{}", html);
+        assert!(html.contains(&format!("<img src=\"./{}\"", picture.fallback)), "{}", html);
+        for file in &result.images {
+            assert!(output.contents(format!("public/{}/{}", IMAGES_DIR, file.name)).is_some(), "{}", file.name);
+        }
+    }
+}
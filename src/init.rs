@@ -0,0 +1,68 @@
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+
+/// 初始化时写出的文件：相对路径 -> 内容
+const STARTER_FILES: &[(&str, &str)] = &[
+    ("config.json", include_str!("../starter/config.json")),
+    ("templates/index.html", include_str!("../starter/templates/index.html")),
+    ("templates/post.html", include_str!("../starter/templates/post.html")),
+    ("templates/tag.html", include_str!("../starter/templates/tag.html")),
+    ("templates/main.css", include_str!("../starter/templates/main.css")),
+];
+
+/// 内置的默认模板 (与 `--config-init` 生成的模板相同)，主题缺少必需模板时使用
+pub(crate) fn builtin_template(name: &str) -> Option<&'static str> {
+    let path = format!("templates/{}", name);
+    STARTER_FILES.iter().find(|(p, _)| *p == path).map(|(_, content)| *content)
+}
+
+/// 阅读模式的页面模板：样式内联、不引用任何外部脚本
+pub(crate) const READER_TEMPLATE: &str = include_str!("../starter/reader.html");
+/// 阅读模式内联的样式
+pub(crate) const READER_CSS: &str = include_str!("../starter/templates/main.css");
+
+/// 在 `dir` 下生成初始配置与最小模板集，使首次构建即可运行。
+/// 已存在的文件默认跳过，`force` 为 true 时覆盖。返回实际写入的文件。
+pub fn init(dir: &Path, force: bool) -> Result<Vec<String>> {
+    let mut written = Vec::new();
+    for (path, content) in STARTER_FILES {
+        let dest = dir.join(path);
+        if dest.exists() && !force {
+            println!(">>> 已存在，跳过: {} (使用 --force 覆盖)", path);
+            continue;
+        }
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&dest, content)?;
+        written.push(path.to_string());
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn init_creates_starter_files_once() {
+        let dir = test_support::temp_dir("init");
+        let written = init(&dir, false).unwrap();
+        assert_eq!(written, ["config.json", "templates/index.html", "templates/post.html", "templates/tag.html", "templates/main.css"]);
+        for path in &written {
+            assert!(dir.join(path).is_file(), "缺少 {}", path);
+        }
+        // 生成的配置可以直接加载，模板可以被 Tera 解析
+        crate::Config::load(dir.join("config.json")).unwrap();
+        tera::Tera::new(&format!("{}/templates/**/*.html", dir.display())).unwrap();
+
+        fs::write(dir.join("templates/post.html"), "custom").unwrap();
+        assert!(init(&dir, false).unwrap().is_empty());
+        assert_eq!(fs::read_to_string(dir.join("templates/post.html")).unwrap(), "custom");
+        assert_eq!(init(&dir, true).unwrap().len(), 5);
+        assert_ne!(fs::read_to_string(dir.join("templates/post.html")).unwrap(), "custom");
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,117 @@
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+/// 构建日志的位置：每抓取完一篇文章追加一行，构建成功后删除
+const JOURNAL_PATH: &str = ".rsnotablog-build.jsonl";
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry<T> {
+    page_id: String,
+    page: T,
+}
+
+/// 可恢复构建的日志。
+/// 抓取结果逐篇追加写入 (JSON Lines)，构建中断后以 `--resume` 重新运行时，
+/// 日志中已有的文章直接使用记录的结果，不再请求 Notion。
+/// 只记录文章正文；数据库查询的分页游标不记录，恢复时重新查询文章列表
+pub struct Journal<T> {
+    path: PathBuf,
+    file: File,
+    done: HashMap<String, T>,
+}
+
+impl<T: Serialize + DeserializeOwned> Journal<T> {
+    /// `resume` 为 true 时读取上次未完成构建的记录并继续追加，否则清空日志重新开始
+    pub fn open(resume: bool) -> Result<Self> {
+        Self::open_at(Path::new(JOURNAL_PATH), resume)
+    }
+
+    /// 同 `open`，日志位于 `path`
+    pub(crate) fn open_at(path: &Path, resume: bool) -> Result<Self> {
+        let mut done = HashMap::new();
+        if resume && path.exists() {
+            let reader = BufReader::new(File::open(path)?);
+            for line in reader.lines() {
+                let line = line?;
+                // 中断时可能留下写了一半的最后一行，忽略即可
+                if let Ok(entry) = serde_json::from_str::<Entry<T>>(&line) {
+                    done.insert(entry.page_id, entry.page);
+                }
+            }
+            println!(">>> 从上次中断的构建恢复，已完成 {} 篇文章", done.len());
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(resume)
+            .write(true)
+            .truncate(!resume)
+            .open(path)
+            .context("无法创建构建日志")?;
+        Ok(Self { path: path.to_path_buf(), file, done })
+    }
+
+    /// 取出上次构建中已完成的页面
+    pub fn take(&mut self, page_id: &str) -> Option<T> {
+        self.done.remove(page_id)
+    }
+
+    /// 记录一个已完成的页面，立即写入磁盘
+    pub fn record(&mut self, page_id: &str, page: &T) -> Result<()> {
+        let line = serde_json::to_string(&Entry { page_id: page_id.to_string(), page })?;
+        writeln!(self.file, "{}", line)?;
+        self.file.flush()?;
+        Ok(())
+    }
+
+    /// 构建成功，删除日志
+    pub fn finish(self) -> Result<()> {
+        drop(self.file);
+        fs::remove_file(&self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn resumes_after_an_interrupted_build() {
+        let path = test_support::temp_dir("journal").join("build.jsonl");
+
+        // 第一次构建：完成两篇后中断，最后一行只写了一半
+        let mut journal = Journal::<String>::open_at(&path, false).unwrap();
+        journal.record("page-a", &"<p>A</p>".to_string()).unwrap();
+        journal.record("page-b", &"<p>B</p>".to_string()).unwrap();
+        drop(journal);
+        OpenOptions::new().append(true).open(&path).unwrap().write_all(b"{\"pageId\":\"page-c\",\"pa").unwrap();
+
+        // --resume：已完成的两篇直接取出，未完成的需要重新抓取
+        let mut journal = Journal::<String>::open_at(&path, true).unwrap();
+        assert_eq!(journal.take("page-a").as_deref(), Some("<p>A</p>"));
+        assert_eq!(journal.take("page-b").as_deref(), Some("<p>B</p>"));
+        assert_eq!(journal.take("page-c"), None);
+        journal.record("page-c", &"<p>C</p>".to_string()).unwrap();
+        journal.finish().unwrap();
+        assert!(!path.exists(), "构建成功后应删除日志");
+    }
+
+    #[test]
+    fn fresh_build_discards_the_old_journal() {
+        let path = test_support::temp_dir("journal-fresh").join("build.jsonl");
+        let mut journal = Journal::<String>::open_at(&path, false).unwrap();
+        journal.record("page-a", &"<p>A</p>".to_string()).unwrap();
+        drop(journal);
+
+        Journal::<String>::open_at(&path, false).unwrap();
+        let mut journal = Journal::<String>::open_at(&path, true).unwrap();
+        assert_eq!(journal.take("page-a"), None);
+    }
+}
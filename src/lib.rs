@@ -0,0 +1,40 @@
+//! rsnotablog 库入口。
+//!
+//! 构建流程分为两步：`Site::build` 负责从 Notion 抓取数据并整理为结构化结果，
+//! `Site::render` 负责用 Tera 模板渲染并写出静态文件。二者分离后，
+//! 既可以作为二进制直接运行，也可以作为库嵌入到其他程序中。
+pub mod api;
+pub mod cache;
+pub mod cdn;
+pub mod compress;
+pub mod config;
+pub mod error;
+pub mod export;
+pub mod favicon;
+pub mod feed;
+pub mod fonts;
+pub mod images;
+pub mod init;
+pub mod journal;
+pub mod links;
+pub mod markdown;
+pub mod model;
+pub mod notion;
+pub mod notify;
+pub mod og;
+pub mod output;
+pub mod pretty;
+pub mod renderer;
+pub mod retry;
+pub mod schema;
+pub mod seo;
+pub mod site;
+pub mod text;
+
+#[cfg(test)]
+mod test_support;
+
+pub use config::Config;
+pub use error::BuildError;
+pub use output::Output;
+pub use site::{BuildResult, Site};
@@ -0,0 +1,131 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 指向不存在文件的站内链接
+#[derive(Debug, Clone)]
+pub struct BrokenLink {
+    /// 包含该链接的页面
+    pub source: PathBuf,
+    /// 链接原文
+    pub target: String,
+}
+
+/// 扫描 `root` 下所有 HTML 文件的 `href` / `src`，检查站内链接指向的文件是否存在。
+/// 外部链接、页内锚点、`mailto:` 等跳过
+pub fn check_links(root: &Path) -> Result<Vec<BrokenLink>> {
+    let mut broken = Vec::new();
+    for page in html_files(root)? {
+        let html = fs::read_to_string(&page)?;
+        let dir = page.parent().unwrap_or(root);
+        for target in link_targets(&html) {
+            if is_external(&target) {
+                continue;
+            }
+            if !resolve(root, dir, &target).exists() {
+                broken.push(BrokenLink { source: page.clone(), target });
+            }
+        }
+    }
+    Ok(broken)
+}
+
+fn html_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(html_files(&path)?);
+        } else if path.extension().is_some_and(|ext| ext == "html") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// 提取 `href="..."` 与 `src="..."` 的值 (去重，保持出现顺序)
+fn link_targets(html: &str) -> Vec<String> {
+    let mut targets: Vec<String> = Vec::new();
+    for attr in ["href=\"", "src=\""] {
+        let mut rest = html;
+        while let Some(start) = rest.find(attr) {
+            // 避免把 data-src= 之类的属性当成 src=
+            let preceded_by_name = rest[..start].chars().last().is_some_and(|c| c.is_alphanumeric() || c == '-');
+            rest = &rest[start + attr.len()..];
+            let Some(end) = rest.find('"') else { break };
+            let value = decode_entities(rest[..end].trim());
+            rest = &rest[end..];
+            if !preceded_by_name && !value.is_empty() && !targets.contains(&value) {
+                targets.push(value);
+            }
+        }
+    }
+    targets
+}
+
+/// 还原属性值中的字符引用：Tera 自动转义会把链接中的 `/` 写成 `&#x2F;`
+fn decode_entities(value: &str) -> String {
+    value
+        .replace("&#x2F;", "/")
+        .replace("&#47;", "/")
+        .replace("&#x27;", "'")
+        .replace("&quot;", "\"")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+fn is_external(target: &str) -> bool {
+    target.starts_with('#')
+        || target.starts_with("//")
+        || target.contains("://")
+        || target.contains("{{")
+        || ["mailto:", "tel:", "data:", "javascript:"].iter().any(|p| target.starts_with(p))
+}
+
+/// 把链接解析为输出目录中的文件：以 `/` 开头的相对于站点根目录，其余相对于页面所在目录；
+/// 以 `/` 结尾的目录链接指向其中的 index.html
+fn resolve(root: &Path, dir: &Path, target: &str) -> PathBuf {
+    let path = target.split(['?', '#']).next().unwrap_or(target).replace("%20", " ");
+    let mut resolved = match path.strip_prefix('/') {
+        Some(absolute) => root.join(absolute),
+        None => dir.join(&path),
+    };
+    if path.is_empty() || path.ends_with('/') {
+        resolved.push("index.html");
+    }
+    // 省略 `.html` 的链接 (`trailingSlash: never` 且启用 cleanUrls) 由托管平台映射到同名的 .html 文件
+    if !resolved.exists() && resolved.extension().is_none() {
+        resolved.set_extension("html");
+    }
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn reports_broken_internal_links_with_their_page() {
+        let root = test_support::temp_dir("links");
+        fs::create_dir_all(root.join("tag")).unwrap();
+        fs::write(root.join("main.css"), "").unwrap();
+        fs::write(root.join("tag/rust.html"), "<a href=\"../index.html\">Home</a>").unwrap();
+        fs::write(
+            root.join("index.html"),
+            concat!(
+                "<link href=\"main.css?v=1\" rel=\"stylesheet\">",
+                "<a href=\"tag&#x2F;rust.html\">Rust</a>",
+                "<a href=\"#top\">Top</a><a href=\"https://example.com/missing.html\">External</a>",
+                "<a href=\"missing-post.html\">Broken</a>",
+            ),
+        )
+        .unwrap();
+
+        let broken = check_links(&root).unwrap();
+        let found: Vec<(PathBuf, &str)> = broken.iter().map(|b| (b.source.clone(), b.target.as_str())).collect();
+        assert_eq!(found, [(root.join("index.html"), "missing-post.html")]);
+    }
+}
@@ -1,464 +1,206 @@
-mod renderer;
-
 use anyhow::{Context, Result};
-use notionrs::Client;
-use notionrs_types::prelude::*;
-use renderer::HtmlRenderer;
-use serde::{Deserialize, Serialize};
-use std::fs;
+use rsnotablog05::{export, init, links, notify, Config, Output, Site};
 use std::path::Path;
-use std::collections::HashMap;
-
-// -----------------------------------------------------------
-// 0. 配置结构
-// -----------------------------------------------------------
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct Config {
-    url: String,
-    notion_token: String,
-    theme: String,
-    title: Option<String>,
-    description: Option<String>,
-}
-
-impl Config {
-    fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let content = fs::read_to_string(path).context("无法读取配置文件")?;
-        let config: Config = serde_json::from_str(&content).context("解析配置文件失败")?;
-        Ok(config)
-    }
-
-    /// 从 URL 中提取 Notion ID (32位十六进制字符串)
-    fn get_notion_id(&self) -> Result<String> {
-        let url = self.url.trim();
-        let parts: Vec<&str> = url.split('/').collect();
-        let last_part = parts.last().ok_or_else(|| anyhow::anyhow!("无效的 URL: '{}'", url))?;
-        
-        // 处理带查询参数的 URL (例如 ?v=...)
-        let id_part = last_part.split('?').next().unwrap_or(last_part);
-        
-        // Notion ID 应该是 32 位字符
-        // 有些 URL 可能是 .../Some-Title-1234567890abcdef1234567890abcdef
-        // 这种情况下我们需要提取最后 32 位
-        let clean_id = id_part.replace("-", ""); 
-        
-        if clean_id.len() >= 32 {
-            // 取最后 32 位
-            Ok(clean_id[clean_id.len()-32..].to_string())
-        } else {
-            Err(anyhow::anyhow!("无法从 URL ('{}') 中提取有效的 Notion ID. 解析到的 ID 部分: '{}'", url, id_part))
-        }
-    }
-}
-
-/// 递归拷贝目录
-fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
-    if !dst.exists() {
-        fs::create_dir_all(dst)?;
-    }
-
-    for entry in fs::read_dir(src)? {
-        let entry = entry?;
-        let ty = entry.file_type()?;
-        let src_path = entry.path();
-        let dst_path = dst.join(entry.file_name());
-
-        if ty.is_dir() {
-            copy_dir_recursive(&src_path, &dst_path)?;
-        } else {
-            fs::copy(&src_path, &dst_path)?;
-        }
-    }
-    Ok(())
-}
-
-// -----------------------------------------------------------
-// 0.5 渲染上下文
-// -----------------------------------------------------------
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct SiteMeta {
-    title: String,
-    icon_url: Option<String>,
-    pages: Vec<PostMetadata>,
-}
 
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct PageContext {
-    site_meta: SiteMeta,
-    post: PostMetadataWithContent,
-    root_path: String,
+/// 命令行参数
+#[derive(Debug, Default)]
+struct Args {
+    /// 导出格式 (目前支持 `hugo`)，设置后不生成 HTML 站点
+    export: Option<String>,
+    /// 完整执行抓取与渲染，但不写入任何文件，只列出将要生成的文件
+    dry_run: bool,
+    /// 生成初始配置与模板后退出
+    config_init: bool,
+    /// 配合 `--config-init`：覆盖已存在的文件
+    force: bool,
+    /// 只抓取并渲染 slug 或页面 ID 为该值的一篇文章
+    only: Option<String>,
+    /// 构建完成后通知 WebSub hub 与 Webhook
+    notify: bool,
+    /// 输出格式：`html` (默认) 或 `reader` (阅读模式，`amp` 为其别名)
+    format: Option<String>,
+    /// 跳过上次中断的构建中已抓取完成的文章
+    resume: bool,
+    /// 生成后检查站内链接与资源是否都指向存在的文件
+    check_links: bool,
+    /// 只渲染一个页面 ID 或本地 JSON 文件中的 Block 树，将正文 HTML 输出到标准输出
+    output_stdout: Option<String>,
+    /// 遇到不支持的 Block 时构建失败，而不是省略
+    strict: bool,
+    /// 只检查配置与 Notion 连接，不构建
+    validate: bool,
 }
 
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct PostMetadataWithContent {
-    title: String,
-    content: String,
-    date: String,
-    tags: Vec<Tag>,
-    cover: Option<String>,
-    icon_url: Option<String>,
-    description: Option<String>,
-}
-
-#[derive(Debug, Serialize, Clone)]
-#[serde(rename_all = "camelCase")]
-struct PostMetadata {
-    title: String,
-    url: String,
-    date: String,
-    tags: Vec<Tag>,
-    preview: String,
-    publish: bool,
-    in_menu: bool,
-    in_list: bool,
-    icon_url: Option<String>,
-    cover: Option<String>,
-}
-
-#[derive(Debug, Serialize, Clone)]
-#[serde(rename_all = "camelCase")]
-struct Tag {
-    name: String,
-    color: String,
-    slug: String,
-}
-
-#[derive(Debug, Serialize, Clone)]
-#[serde(rename_all = "camelCase")]
-struct TagStat {
-    name: String,
-    slug: String,
-    count: usize,
-    color: String,
-}
-
-fn slugify(s: &str) -> String {
-    s.trim()
-        .replace(' ', "-")
-        .replace('/', "-")
-        .replace('?', "")
-        .replace(':', "")
-        .replace('*', "")
-        .replace('"', "")
-        .replace('<', "")
-        .replace('>', "")
-        .replace('|', "")
-        .to_lowercase()
-}
-
-// -----------------------------------------------------------
-// 1. 数据结构 (Notion API 响应映射)
-// -----------------------------------------------------------
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct MyProperties {
-    #[serde(rename = "title")]
-    pub title: PageTitleProperty,
-
-    #[serde(rename = "tags")]
-    pub tags: PageMultiSelectProperty,
-
-    #[serde(rename = "template")]
-    pub template: PageSelectProperty,
-
-    #[serde(rename = "publish")]
-    pub publish: PageCheckboxProperty,
-
-    #[serde(rename = "inMenu")]
-    pub in_menu: PageCheckboxProperty,
-
-    #[serde(rename = "inList")]
-    pub in_list: PageCheckboxProperty,
-
-    #[serde(rename = "date")]
-    pub date: PageDateProperty,
-}
-
-async fn get_page_html(client: &Client, page_id: &str) -> Result<(String, String)> {
-    let mut html = String::new();
-    let mut plain_text = String::new();
-    let response = client
-        .get_block_children()
-        .block_id(page_id)
-        .send()
-        .await
-        .map_err(|e| anyhow::anyhow!(e))?;
-
-    for block_res in response.results {
-        let block_html = HtmlRenderer::render_block(&block_res.block);
-        
-        // 特殊处理 Toggle：我们需要把子内容放进 details 标签内部
-        if let Block::Toggle { .. } = &block_res.block {
-             // 移除末尾的 </details>
-             let open_tag = block_html.strip_suffix("</details>").unwrap_or(&block_html);
-             html.push_str(open_tag);
-             
-             if block_res.has_children {
-                 let (children_html, children_text) = Box::pin(get_page_html(client, &block_res.id)).await?;
-                 html.push_str("<div class=\"details-content\" style=\"padding-left: 1.2em;\">");
-                 html.push_str(&children_html);
-                 html.push_str("</div>");
-                 if plain_text.len() < 200 {
-                    plain_text.push_str(&children_text);
-                 }
-             }
-             html.push_str("</details>");
-        } else {
-            // 普通 Block
-            html.push_str(&block_html);
-            html.push('\n');
-            
-            // 提取纯文本用于预览
-            if plain_text.len() < 200 {
-                plain_text.push_str(&block_res.block.to_string());
-                plain_text.push(' ');
-            }
-            
-            if block_res.has_children {
-                let (children_html, children_text) = Box::pin(get_page_html(client, &block_res.id)).await?;
-                html.push_str("<div style=\"margin-left: 20px;\">");
-                html.push_str(&children_html);
-                html.push_str("</div>");
-                if plain_text.len() < 200 {
-                    plain_text.push_str(&children_text);
+impl Args {
+    fn parse() -> Result<Self> {
+        let mut args = Args::default();
+        let mut iter = std::env::args().skip(1);
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--export" => {
+                    args.export = Some(iter.next().context("--export 需要指定格式，例如 --export hugo")?);
                 }
+                "--dry-run" => args.dry_run = true,
+                "--config-init" => args.config_init = true,
+                "--force" => args.force = true,
+                "--notify" => args.notify = true,
+                "--resume" => args.resume = true,
+                "--check-links" => args.check_links = true,
+                "--strict" => args.strict = true,
+                "--validate" => args.validate = true,
+                "--format" => {
+                    args.format = Some(iter.next().context("--format 需要指定格式，例如 --format reader")?);
+                }
+                "--output-stdout" => {
+                    args.output_stdout = Some(iter.next().context("--output-stdout 需要指定页面 ID 或 JSON 文件")?);
+                }
+                "--only" => {
+                    args.only = Some(iter.next().context("--only 需要指定文章的 slug 或页面 ID")?);
+                }
+                other => anyhow::bail!("未知参数: {}", other),
             }
         }
+        Ok(args)
     }
-    Ok((html, plain_text))
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let args = Args::parse()?;
+
+    if args.config_init {
+        let written = init::init(Path::new("."), args.force)?;
+        println!(">>> 已生成 {} 个文件: {}", written.len(), written.join(", "));
+        println!(">>> 请在 config.json 中填写 url 与 notionToken 后运行 cargo run");
+        return Ok(());
+    }
+
     // 1. 加载配置
     let config_path = "config.json"; // 改为当前目录下的 config.json 或通过环境变量
-    
-    let (notion_token, data_source_url, site_title) = if let Ok(config) = Config::load(config_path) {
-        println!(">>> 已加载配置文件: {}", config_path);
-        (config.notion_token, config.url, config.title.unwrap_or_else(|| "My Blog".to_string()))
+
+    // 输出到标准输出时不打印进度信息，保证输出只有 HTML
+    let quiet = args.output_stdout.is_some();
+    let mut config = if let Ok(config) = Config::load(config_path) {
+        if !quiet {
+            println!(">>> 已加载配置文件: {}", config_path);
+        }
+        config
     } else {
-        println!(">>> 未找到配置文件或解析失败，尝试从环境变量读取...");
+        if !quiet {
+            println!(">>> 未找到配置文件或解析失败，尝试从环境变量读取...");
+        }
         let token = std::env::var("NOTION_TOKEN").context("环境变量 NOTION_TOKEN 未设置")?;
         let url = std::env::var("NOTION_PAGE_URL").context("环境变量 NOTION_PAGE_URL 未设置")?;
         let title = std::env::var("SITE_TITLE").unwrap_or_else(|_| "My Blog".to_string());
-        (token, url, title)
-    };
-
-    let client = Client::new(&notion_token);
-    
-    // 构造一个临时的 Config 来获取 Notion ID
-    let temp_config = Config {
-        url: data_source_url,
-        notion_token: notion_token.clone(),
-        theme: "".to_string(),
-        title: Some(site_title.clone()),
-        description: None,
+        // 经由 serde 构造，使其余配置项与配置文件一样取默认值
+        let value = serde_json::json!({
+            "url": url,
+            "notionToken": token,
+            "theme": "",
+            "title": title,
+        });
+        serde_json::from_value::<Config>(value).context("构造配置失败")?
     };
-    let data_source_id = temp_config.get_notion_id()?;
-
-    // 2. 初始化 Tera 模板引擎
-    let mut tera = tera::Tera::new("templates/**/*")?;
-    tera.full_reload()?;
-
-    // 3. 获取所有文章元数据
-    println!(">>> 正在获取文章列表...");
-    let filter = Filter::timestamp_is_not_empty();
-    let response = client
-        .query_data_source()
-        .data_source_id(&data_source_id)
-        .filter(filter)
-        .send::<MyProperties>()
-        .await
-        .map_err(|e| anyhow::anyhow!(e))?;
 
-    let mut all_posts = Vec::new();
-    for page in response.results {
-        let p = page.properties;
-        let title = p.title.to_string();
-        let safe_title = title.replace(" ", "_").replace("/", "-")
-            .replace("?", "").replace(":", "").replace("*", "").replace("\"", "")
-            .replace("<", "").replace(">", "").replace("|", "");
-        let filename = format!("{}.html", safe_title);
-        
-        let date_str = p.date.date.as_ref()
-            .and_then(|d| d.start.as_ref())
-            .map(|dt| dt.to_string())
-            .unwrap_or_else(|| "".to_string());
+    config.dry_run = args.dry_run;
+    config.strict = args.strict;
 
-        // 提取页面图标 (Emoji 或 URL)
-        let icon_url = match &page.icon {
-            Some(Icon::Emoji(emoji)) => Some(emoji.emoji.clone()),
-            Some(Icon::File(file_enum)) => match file_enum {
-                // 尝试解构 external 字段
-                File::External(ext_file) => Some(ext_file.external.url.clone()), 
-                _ => None, 
-            },
-            Some(Icon::CustomEmoji(custom)) => Some(custom.custom_emoji.url.clone()),
-            None => None,
-        };
+    // dry-run 不读写构建日志，无从恢复
+    if args.resume && args.dry_run {
+        anyhow::bail!("--resume 不能与 --dry-run 同时使用");
+    }
 
-        // 提取封面图片 URL
-        let cover = page.cover.as_ref().map(|c| c.to_string());
+    // --check-links 与 --notify 针对写入 public/ 的站点，导出与 dry-run 都不会写入 public/
+    if (args.check_links || args.notify) && (args.export.is_some() || args.dry_run) {
+        anyhow::bail!("--check-links 与 --notify 不能与 --export 或 --dry-run 同时使用");
+    }
 
-        all_posts.push((page.id.to_string(), PostMetadata {
-            title,
-            url: filename,
-            date: date_str,
-            tags: p.tags.multi_select.iter().map(|opt| Tag { 
-                name: opt.name.clone(), 
-                color: format!("{:?}", opt.color).to_lowercase(),
-                slug: slugify(&opt.name)
-            }).collect(),
-            preview: "".to_string(), // 稍后填充
-            publish: p.publish.checkbox,
-            in_menu: p.in_menu.checkbox,
-            in_list: p.in_list.checkbox,
-            icon_url,
-            cover,
-        }));
+    match args.format.as_deref() {
+        None | Some("html") => {}
+        // amp 为早期的名称，输出的是阅读模式页面，并不是 AMP 规范页面
+        Some("reader" | "amp") => config.reader_mode = true,
+        Some(other) => anyhow::bail!("不支持的输出格式: {}", other),
     }
 
-    let site_meta = SiteMeta {
-        title: site_title,
-        icon_url: None,
-        pages: all_posts.iter().map(|(_, m)| m.clone()).collect(),
-    };
+    if args.validate {
+        Site::validate(&config).await?;
+        println!(">>> 配置与 Notion 连接检查通过");
+        return Ok(());
+    }
 
-    fs::create_dir_all("public")?;
+    if let Some(source) = &args.output_stdout {
+        print!("{}", Site::render_blocks(&config, source).await?);
+        return Ok(());
+    }
 
-    // 4. 遍历处理每篇文章
-    let mut posts_meta_for_index = Vec::new();
-    for (page_id, mut meta) in all_posts {
-        if !meta.publish {
-            continue;
-        }
-        
-        println!(">>> 正在处理: {}", meta.title);
-        let (content_html, plain_text) = get_page_html(&client, &page_id).await?;
-        
-        let preview = if plain_text.chars().count() > 150 {
-            format!("{}...", plain_text.chars().take(150).collect::<String>())
+    // 只构建一篇文章：跳过其余文章与汇总页面
+    if let Some(only) = &args.only {
+        let result = Site::build_only(&config, only).await?;
+        let mut output = Output::new(args.dry_run);
+        if config.reader_mode {
+            Site::render_reader(&result, &mut output)?;
         } else {
-            plain_text
-        };
-        meta.preview = preview;
-
-        let post_context = PostMetadataWithContent {
-            title: meta.title.clone(),
-            content: content_html,
-            date: meta.date.clone(),
-            tags: meta.tags.clone(),
-            cover: meta.cover.clone(),
-            icon_url: meta.icon_url.clone(),
-            description: Some(meta.preview.clone()),
-        };
-
-        let context = PageContext {
-            site_meta: SiteMeta {
-                title: site_meta.title.clone(),
-                icon_url: site_meta.icon_url.clone(),
-                pages: site_meta.pages.clone(),
-            },
-            post: post_context,
-            root_path: ".".to_string(),
-        };
-        
-        let rendered = tera.render("post.html", &tera::Context::from_serialize(&context)?)?;
-        fs::write(format!("public/{}", meta.url), rendered)?;
-        
-        if meta.in_list {
-            posts_meta_for_index.push(meta);
+            Site::render_posts(&config, &result, &mut output)?;
+        }
+        for file in output.files() {
+            println!(">>> 已生成: {}", file.path.display());
         }
+        after_render(&config, &args).await?;
+        return Ok(());
     }
 
-    // 5. 渲染首页
-    println!(">>> 正在生成首页...");
-    let mut index_context = tera::Context::new();
-    index_context.insert("siteMeta", &site_meta);
-    index_context.insert("pages", &posts_meta_for_index); // Changed from "posts" to "pages" to match articleList.html
-    index_context.insert("rootPath", ".");
-    let index_html = tera.render("index.html", &index_context)?;
-    fs::write("public/index.html", index_html)?;
+    // 2. 抓取数据
+    let result = if args.resume {
+        Site::build_resume(&config).await?
+    } else {
+        Site::build(&config).await?
+    };
 
-    // 6. 生成标签页
-    println!(">>> 正在生成标签页...");
-    fs::create_dir_all("public/tag")?;
-    
-    // 按标签分组文章
-    let mut tags_map: HashMap<String, Vec<PostMetadata>> = HashMap::new();
-    for post in &posts_meta_for_index {
-        for tag in &post.tags {
-            tags_map.entry(tag.name.clone())
-                .or_insert_with(Vec::new)
-                .push(post.clone());
+    // 3. 渲染并写出静态文件，或导出到其他格式
+    match args.export.as_deref() {
+        Some("hugo") => {
+            export::export_hugo(&result, Path::new("hugo")).await?;
+            println!(">>> 导出完成！请查看 hugo/content/posts");
+        }
+        Some(other) => anyhow::bail!("不支持的导出格式: {}", other),
+        None if config.reader_mode => {
+            let mut output = Output::new(args.dry_run);
+            Site::render_reader(&result, &mut output)?;
+            println!(">>> 已生成 {} 个阅读模式页面，请查看 public/reader", output.files().len());
+            after_render(&config, &args).await?;
+        }
+        None if args.dry_run => {
+            let mut output = Output::new(true);
+            Site::render_to(&config, &result, &mut output)?;
+            println!(">>> [dry-run] 将生成 {} 个文件:", output.files().len());
+            for file in output.files() {
+                println!("    {} ({} bytes)", file.path.display(), file.size);
+            }
+        }
+        None => {
+            Site::render(&config, &result)?;
+            println!(">>> 全部完成！请查看 public/index.html");
+            after_render(&config, &args).await?;
         }
     }
 
-    // 计算标签统计信息
-    let mut all_tags: Vec<TagStat> = Vec::new();
-    for (tag_name, posts) in &tags_map {
-        // 找到对应的标签颜色
-        let color = posts.first()
-            .and_then(|p| p.tags.iter().find(|t| t.name == *tag_name))
-            .map(|t| t.color.clone())
-            .unwrap_or_else(|| "default".to_string());
-            
-        all_tags.push(TagStat {
-            name: tag_name.clone(),
-            slug: slugify(tag_name),
-            count: posts.len(),
-            color,
-        });
-    }
-    // 按数量降序排序
-    all_tags.sort_by(|a, b| b.count.cmp(&a.count));
-
-    // 渲染每个标签的页面
-    for (tag_name, tag_posts) in tags_map {
-        let safe_tag_name = slugify(&tag_name);
-        let filename = format!("public/tag/{}.html", safe_tag_name);
-        
-        let tag_site_meta = SiteMeta {
-            title: format!("Tag: {}", tag_name),
-            icon_url: None, 
-            pages: tag_posts.clone(),
-        };
-
-        let mut context = tera::Context::new();
-        context.insert("siteMeta", &tag_site_meta);
-        context.insert("tagName", &tag_name); // 传入 tagName 供模板使用
-        context.insert("pages", &tag_posts);
-        context.insert("allTags", &all_tags); // 传入所有标签列表
-        context.insert("rootPath", "..");
-        
-        // 优先使用 tag.html，如果没有则回退到 index.html
-        let template_name = if tera.get_template_names().any(|t| t == "tag.html") {
-            "tag.html"
-        } else {
-            "index.html"
-        };
-
-        let html = tera.render(template_name, &context)?;
-        fs::write(filename, html)?;
-    }
+    Ok(())
+}
 
-    // 7. 拷贝静态资源
-    if Path::new("templates/main.css").exists() {
-        fs::copy("templates/main.css", "public/main.css")?;
+/// 写出 `public/` 之后的步骤：`--check-links` 检查站内链接，`--notify` 通知 hub 与 Webhook
+async fn after_render(config: &Config, args: &Args) -> Result<()> {
+    if args.check_links {
+        let broken = links::check_links(Path::new("public"))?;
+        for link in &broken {
+            println!(">>> 失效链接: {} -> {}", link.source.display(), link.target);
+        }
+        if !broken.is_empty() {
+            anyhow::bail!("发现 {} 个失效的站内链接", broken.len());
+        }
+        println!(">>> 站内链接检查通过");
     }
-    
-    // 自动拷贝 templates/assets 到 public/assets
-    let assets_src = Path::new("templates/assets");
-    if assets_src.exists() {
-        println!(">>> 正在拷贝静态资源...");
-        let assets_dst = Path::new("public/assets");
-        copy_dir_recursive(assets_src, assets_dst)?;
+    if args.notify {
+        notify::notify(config).await;
     }
-
-    println!(">>> 全部完成！请查看 public/index.html");
-
     Ok(())
 }
@@ -0,0 +1,127 @@
+use notionrs_types::prelude::*;
+use crate::renderer::{render_icon, IconHtml};
+
+/// 将 Notion Block 转换为 Markdown，用于导出到 Hugo 等静态站点生成器
+pub struct MarkdownRenderer;
+
+impl MarkdownRenderer {
+    pub fn render_block(block: &Block) -> String {
+        match block {
+            Block::Paragraph { paragraph } => Self::render_rich_text(&paragraph.rich_text),
+            Block::Heading1 { heading_1 } => format!("# {}", Self::render_rich_text(&heading_1.rich_text)),
+            Block::Heading2 { heading_2 } => format!("## {}", Self::render_rich_text(&heading_2.rich_text)),
+            Block::Heading3 { heading_3 } => format!("### {}", Self::render_rich_text(&heading_3.rich_text)),
+            Block::BulletedListItem { bulleted_list_item } => {
+                format!("- {}", Self::render_rich_text(&bulleted_list_item.rich_text))
+            }
+            Block::NumberedListItem { numbered_list_item } => {
+                format!("1. {}", Self::render_rich_text(&numbered_list_item.rich_text))
+            }
+            Block::ToDo { to_do } => {
+                let mark = if to_do.checked { "x" } else { " " };
+                format!("- [{}] {}", mark, Self::render_rich_text(&to_do.rich_text))
+            }
+            Block::Code { code } => {
+                let text: String = code.rich_text.iter().map(|rt| rt.to_string()).collect();
+                format!("```{}\n{}\n```", code.language, text)
+            }
+            Block::Quote { quote } => Self::quote(&Self::render_rich_text(&quote.rich_text)),
+            Block::Callout { callout } => {
+                let emoji = match render_icon(&callout.icon) {
+                    Some(IconHtml::Emoji(emoji)) => format!("{} ", emoji),
+                    Some(IconHtml::Image(url)) => format!("![]({}) ", url),
+                    None => String::new(),
+                };
+                Self::quote(&format!("{}{}", emoji, Self::render_rich_text(&callout.rich_text)))
+            }
+            Block::Image { image } => format!("![]({})", image),
+            Block::Video { video } => format!("<{}>", video),
+            Block::Audio { audio } => format!("<{}>", audio),
+            Block::File { file } => format!("<{}>", file),
+            Block::Pdf { pdf } => format!("<{}>", pdf),
+            Block::Embed { embed } => format!("<{}>", embed.url),
+            Block::Bookmark { bookmark } => format!("<{}>", bookmark.url),
+            Block::LinkPreview { link_preview } => format!("<{}>", link_preview.url),
+            Block::Toggle { toggle } => format!("**{}**", Self::render_rich_text(&toggle.rich_text)),
+            Block::Equation { equation } => format!("$$\n{}\n$$", equation.expression),
+            Block::Divider { .. } => "---".to_string(),
+            _ => "<!-- Unsupported block type -->".to_string(),
+        }
+    }
+
+    /// 渲染 GFM 表格；没有列标题时以空表头占位 (GFM 要求必须有表头)
+    pub fn render_table(rows: &[Vec<Vec<RichText>>], width: usize, has_column_header: bool) -> String {
+        let columns = rows.iter().map(|r| r.len()).max().unwrap_or(0).max(width);
+        if columns == 0 {
+            return String::new();
+        }
+        let line = |cells: Vec<String>| format!("| {} |", cells.join(" | "));
+        let row_cells = |row: &Vec<Vec<RichText>>| {
+            (0..columns)
+                .map(|col| row.get(col).map(|cell| Self::render_rich_text(cell).replace('|', "\\|")).unwrap_or_default())
+                .collect::<Vec<_>>()
+        };
+
+        let mut lines = Vec::new();
+        let body = if has_column_header && !rows.is_empty() {
+            lines.push(line(row_cells(&rows[0])));
+            &rows[1..]
+        } else {
+            lines.push(line(vec![String::new(); columns]));
+            rows
+        };
+        lines.push(line(vec!["---".to_string(); columns]));
+        lines.extend(body.iter().map(|row| line(row_cells(row))));
+        lines.join("\n")
+    }
+
+    pub fn render_rich_text(rich_texts: &[RichText]) -> String {
+        let mut md = String::new();
+        for rt in rich_texts {
+            match rt {
+                RichText::Text { text, annotations, .. } => {
+                    let mut content = text.content.clone();
+                    if content.trim().is_empty() {
+                        md.push_str(&content);
+                        continue;
+                    }
+                    // 行内代码忽略其他样式，与 HTML 渲染一致
+                    if annotations.code {
+                        content = format!("`{}`", content);
+                    } else {
+                        if annotations.bold {
+                            content = format!("**{}**", content);
+                        }
+                        if annotations.italic {
+                            content = format!("*{}*", content);
+                        }
+                        if annotations.strikethrough {
+                            content = format!("~~{}~~", content);
+                        }
+                    }
+                    if let Some(link) = &text.link {
+                        content = format!("[{}]({})", content, link.url);
+                    }
+                    md.push_str(&content);
+                }
+                RichText::Equation { equation, .. } => {
+                    md.push_str(&format!("${}$", equation.expression));
+                }
+                RichText::Mention { plain_text, .. } => md.push_str(plain_text),
+            }
+        }
+        md
+    }
+
+    /// 将文本的每一行缩进，用于列表项的子内容
+    pub fn indent(md: &str, prefix: &str) -> String {
+        md.lines()
+            .map(|line| if line.is_empty() { String::new() } else { format!("{}{}", prefix, line) })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn quote(text: &str) -> String {
+        text.lines().map(|line| format!("> {}", line)).collect::<Vec<_>>().join("\n")
+    }
+}
@@ -0,0 +1,424 @@
+use notionrs_types::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::config::ColorScheme;
+use crate::text;
+
+// -----------------------------------------------------------
+// 0.5 渲染上下文
+// -----------------------------------------------------------
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteMeta {
+    pub title: String,
+    pub icon_url: Option<String>,
+    pub pages: Vec<PostMetadata>,
+    /// 本地化字体样式表的路径 (相对于站点根目录)，未启用 `selfHostFonts` 时为 None
+    pub font_css: Option<String>,
+    /// 浏览器界面的主题色 (`themeColor`)
+    pub theme_color: Option<String>,
+    /// 配色方案 (`colorScheme`)，模板以 `theme-{{ siteMeta.colorScheme }}` 作为根元素的类名
+    pub color_scheme: ColorScheme,
+    /// 构建时间 (UTC，格式由 `buildTimeFormat` 决定)，可用于显示「最后更新」
+    pub build_time: String,
+    /// 每次构建不同的短 ID，可附加到资源地址 (`?v={{ siteMeta.buildId }}`) 以刷新缓存
+    pub build_id: String,
+    /// 首页链接 (相对于站点根目录)，由 `trailingSlash` 决定为 `index.html` 或空
+    pub home_url: String,
+    /// 站点生成的 Feed，供主题在 <head> 中输出自动发现链接
+    pub feeds: Vec<FeedLink>,
+    /// 由站点图标生成的 favicon (启用 `favicon` 时)
+    pub favicons: Vec<FaviconLink>,
+}
+
+/// Feed 的自动发现信息：`<link rel="alternate" type="{{ mimeType }}" title="{{ title }}" href="{{ url }}">`
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedLink {
+    pub title: String,
+    /// 绝对地址
+    pub url: String,
+    pub mime_type: String,
+}
+
+/// favicon 的 `<link>` 标签：`<link rel="{{ rel }}" type="{{ mimeType }}" sizes="{{ sizes }}" href="{{ href }}">`
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FaviconLink {
+    pub rel: String,
+    pub mime_type: String,
+    /// 如 `32x32`，SVG 图标为空
+    pub sizes: Option<String>,
+    /// 相对于站点根目录
+    pub href: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageContext<'a> {
+    pub site_meta: &'a SiteMeta,
+    pub post: &'a PostMetadataWithContent,
+    /// 正文中的标题，供主题渲染侧边目录
+    pub headings: &'a [Heading],
+    /// `propertiesTable` 中配置的属性，供主题在正文前渲染为表格
+    pub properties_table: &'a [PropertyRow],
+    /// BreadcrumbList 结构化数据 (启用 `breadcrumbs` 且设置了 `siteUrl` 时)
+    pub breadcrumb_json_ld: Option<String>,
+    /// 文章的完整地址 (设置了 `siteUrl` 时)，形式与站内链接一致，遵循 `trailingSlash`
+    pub canonical_url: Option<String>,
+    pub root_path: String,
+}
+
+/// 属性表中的一行：属性名与格式化后的值
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PropertyRow {
+    pub name: String,
+    pub value: String,
+}
+
+impl PropertyRow {
+    /// 由 Notion 返回的原始属性构造一行。支持单选、多选、数字与日期，
+    /// 其他类型或值为空时返回 None
+    pub fn from_property(name: &str, property: &serde_json::Value) -> Option<Self> {
+        let value = match property.get("type")?.as_str()? {
+            "select" => property["select"]["name"].as_str()?.to_string(),
+            "multi_select" => {
+                let names: Vec<&str> = property["multi_select"].as_array()?
+                    .iter()
+                    .filter_map(|opt| opt["name"].as_str())
+                    .collect();
+                names.join(", ")
+            }
+            "number" => match property["number"].as_f64()? {
+                n if n.fract() == 0.0 => format!("{}", n as i64),
+                n => n.to_string(),
+            },
+            "date" => {
+                let start = property["date"]["start"].as_str()?;
+                match property["date"]["end"].as_str() {
+                    Some(end) => format!("{} → {}", start, end),
+                    None => start.to_string(),
+                }
+            }
+            _ => return None,
+        };
+        (!value.is_empty()).then(|| Self { name: name.to_string(), value })
+    }
+}
+
+/// 正文中的一个标题，`id` 与页面中该标题的锚点一致
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Heading {
+    pub level: u8,
+    pub text: String,
+    pub id: String,
+}
+
+/// 文章元数据 + 渲染后的正文
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PostMetadataWithContent {
+    #[serde(flatten)]
+    pub meta: PostMetadata,
+    pub content: String,
+    /// Markdown 正文，仅用于导出，不进入模板上下文
+    #[serde(skip)]
+    pub markdown: String,
+    pub description: Option<String>,
+    /// 用于 `<title>` 的标题，截断到约 60 个字符 (单词边界)
+    pub meta_title: String,
+    /// 用于 `<meta name="description">` 的摘要，截断到约 155 个字符 (单词边界)
+    pub meta_description: Option<String>,
+    /// 所属系列 (同系列文章按日期排序)
+    pub series: Option<SeriesInfo>,
+    /// 正文中的标题 (按出现顺序)，经由 `PageContext.headings` 提供给模板
+    #[serde(skip)]
+    pub headings: Vec<Heading>,
+    /// 本文的其他语言版本，供主题渲染语言切换链接
+    pub translations: Vec<Translation>,
+    /// 原始 Block 树 (开启 `includeRawBlocks` 时)，供模板或下游工具自定义渲染
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blocks: Option<Vec<serde_json::Value>>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Translation {
+    pub language: String,
+    pub title: String,
+    pub url: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PostMetadata {
+    pub title: String,
+    pub slug: String,
+    pub url: String,
+    pub date: String,
+    /// 展示用的标签 (已去除 `hiddenTags` 中的标签)
+    pub tags: Vec<Tag>,
+    /// 被 `hiddenTags` 隐藏的标签，不参与展示与标签页生成
+    #[serde(skip)]
+    pub hidden_tags: Vec<Tag>,
+    pub preview: String,
+    /// 摘要 HTML：手动摘要为分隔标记前的正文，否则为截断后的预览文本
+    pub excerpt_html: String,
+    /// 是否找到了手动摘要分隔标记
+    pub manual_excerpt: bool,
+    /// 字数 (CJK 按字、其余按空白分词)
+    pub word_count: usize,
+    /// 不含空白的字符数
+    pub char_count: usize,
+    pub publish: bool,
+    pub in_menu: bool,
+    pub in_list: bool,
+    /// 不显示默认的标题 (`hideTitle` 复选框)，供正文自定义页首
+    pub hide_title: bool,
+    /// 不显示默认的日期与标签栏 (`hideMeta` 复选框)
+    pub hide_meta: bool,
+    /// 勾选了 `inFeed` 复选框 (配合 `feedRequireInFeed`)
+    pub in_feed: bool,
+    /// 首页排序用的 `order` 数字属性，有值的文章按其升序排在前面
+    pub order: Option<f64>,
+    pub icon_url: Option<String>,
+    pub cover: Option<String>,
+    /// 封面在卡片中的裁剪位置，供主题用作 `object-position`，默认 `center`
+    pub cover_position: String,
+    /// 转换格式后的封面 (`imageFormat` 不为 `original` 时)，主题据此输出 `<picture>`
+    pub cover_picture: Option<Picture>,
+    /// 生成的分享卡片地址 (启用 `generateOgImages` 且文章没有封面时)
+    pub og_image: Option<String>,
+    pub series: Option<String>,
+    /// 页面 `head` 属性中的额外 HTML (CSS / 脚本)，原样插入 <head>
+    pub extra_head: Option<String>,
+    /// To-do 完成情况，文章中没有 To-do 时为 None
+    pub todo_progress: Option<TodoProgress>,
+    /// 文章语言 (`language` 单选属性)，如 `en`、`zh`
+    pub language: Option<String>,
+    /// 同一篇文章不同语言版本共用的标识
+    pub translation_key: Option<String>,
+    /// 未解决的评论数 (启用 `fetchComments` 时)
+    pub comment_count: Option<usize>,
+    /// `propertiesTable` 中配置的属性 (按配置顺序，跳过空值)
+    #[serde(skip)]
+    pub properties_table: Vec<PropertyRow>,
+}
+
+/// `<picture>` 的数据，地址均相对于站点根目录
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Picture {
+    /// 按优先顺序排列的 `<source>`
+    pub sources: Vec<PictureSource>,
+    /// 不支持以上格式时使用的 `<img>` 地址 (原图)
+    pub fallback: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PictureSource {
+    pub srcset: String,
+    pub mime_type: String,
+}
+
+/// 文章中 To-do 的完成数 / 总数 (含嵌套的 To-do)
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoProgress {
+    pub completed: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesInfo {
+    pub name: String,
+    pub slug: String,
+    /// 当前文章在系列中的位置 (从 1 开始)
+    pub position: usize,
+    pub total: usize,
+    pub posts: Vec<SeriesEntry>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesEntry {
+    pub title: String,
+    pub url: String,
+    pub current: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Tag {
+    pub name: String,
+    pub color: String,
+    pub slug: String,
+    /// 标签页链接 (相对于站点根目录)
+    pub url: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TagStat {
+    pub name: String,
+    pub slug: String,
+    /// 标签页链接 (相对于站点根目录)
+    pub url: String,
+    pub count: usize,
+    pub color: String,
+    pub description: Option<String>,
+    pub cover: Option<String>,
+}
+
+/// 列表页的分页信息：页码从 1 开始，上一页 / 下一页的链接相对于站点根目录
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Pagination {
+    pub current: usize,
+    pub total: usize,
+    pub prev_url: Option<String>,
+    pub next_url: Option<String>,
+}
+
+/// 按标签分组的文章，用于 “按主题浏览” 页面
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicGroup<'a> {
+    pub tag: &'a TagStat,
+    pub pages: Vec<&'a PostMetadata>,
+}
+
+/// 文章的文件名：去掉 Emoji 与文件名中不允许的字符，空格替换为 `_`。
+/// 标题只有 Emoji 或符号时以 `post-<页面 ID 前 8 位>` 兜底；显示标题不受影响
+pub fn safe_title(title: &str, page_id: &str) -> String {
+    let name: String = title.chars().filter(|c| !text::is_emoji(*c)).collect::<String>()
+        .trim()
+        .replace(' ', "_").replace('/', "-")
+        .replace(['?', ':', '*', '"', '<', '>', '|'], "");
+    if name.trim_matches(['_', '-', '.']).is_empty() {
+        let id: String = page_id.chars().filter(|c| *c != '-').take(8).collect();
+        format!("post-{}", id)
+    } else {
+        name
+    }
+}
+
+/// 标签、系列、标题锚点等的 slug；去掉 Emoji 后为空时以 `u-<哈希>` 兜底，保证不同取值不会重名
+pub fn slugify(s: &str) -> String {
+    let slug = slug_chars(s);
+    if slug.trim_matches('-').is_empty() && !s.trim().is_empty() {
+        format!("u-{}", text::short_hash(s.trim()))
+    } else {
+        slug
+    }
+}
+
+fn slug_chars(s: &str) -> String {
+    s.chars().filter(|c| !text::is_emoji(*c)).collect::<String>()
+        .trim()
+        .replace([' ', '/'], "-")
+        .replace(['?', ':', '*', '"', '<', '>', '|'], "")
+        .to_lowercase()
+}
+
+// -----------------------------------------------------------
+// 1. 数据结构 (Notion API 响应映射)
+// -----------------------------------------------------------
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MyProperties {
+    #[serde(rename = "title")]
+    pub title: PageTitleProperty,
+
+    #[serde(rename = "tags")]
+    pub tags: PageMultiSelectProperty,
+
+    #[serde(rename = "template")]
+    pub template: PageSelectProperty,
+
+    #[serde(rename = "publish")]
+    pub publish: PageCheckboxProperty,
+
+    #[serde(rename = "inMenu")]
+    pub in_menu: PageCheckboxProperty,
+
+    #[serde(rename = "inList")]
+    pub in_list: PageCheckboxProperty,
+
+    #[serde(rename = "date")]
+    pub date: PageDateProperty,
+
+    #[serde(rename = "series", default)]
+    pub series: Option<PageSelectProperty>,
+
+    #[serde(rename = "head", default)]
+    pub head: Option<PageRichTextProperty>,
+
+    #[serde(rename = "coverFocus", default)]
+    pub cover_focus: Option<PageRichTextProperty>,
+
+    #[serde(rename = "publishWindow", default)]
+    pub publish_window: Option<PageDateProperty>,
+
+    #[serde(rename = "language", default)]
+    pub language: Option<PageSelectProperty>,
+
+    #[serde(rename = "translationKey", default)]
+    pub translation_key: Option<PageRichTextProperty>,
+
+    #[serde(rename = "hideTitle", default)]
+    pub hide_title: Option<PageCheckboxProperty>,
+
+    #[serde(rename = "hideMeta", default)]
+    pub hide_meta: Option<PageCheckboxProperty>,
+
+    #[serde(rename = "inFeed", default)]
+    pub in_feed: Option<PageCheckboxProperty>,
+
+    #[serde(default)]
+    pub order: Option<PageNumberProperty>,
+
+    /// 其余属性的原始数据，供 `propertiesTable` 按名称读取
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn emoji_titles_yield_valid_filenames() {
+        let name = super::safe_title("🚀🎉", "1a2b3c4d-5e6f-7081-92a3-b4c5d6e7f809");
+        assert_eq!(name, "post-1a2b3c4d");
+
+        assert_eq!(super::safe_title("🚀 Launch Day", "id"), "Launch_Day");
+
+        let slug = super::slugify("🚀");
+        assert!(slug.starts_with("u-") && slug.len() > 2, "{}", slug);
+        assert_ne!(slug, super::slugify("🎉"));
+        assert!(slug.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'), "{}", slug);
+    }
+
+    #[test]
+    fn properties_table_formats_supported_types() {
+        let rating = serde_json::json!({ "id": "a1", "type": "number", "number": 4.5 });
+        let row = super::PropertyRow::from_property("Rating", &rating).expect("数字属性应生成一行");
+        assert_eq!((row.name.as_str(), row.value.as_str()), ("Rating", "4.5"));
+
+        let value = |property: serde_json::Value| super::PropertyRow::from_property("P", &property).map(|r| r.value);
+        assert_eq!(value(serde_json::json!({ "type": "number", "number": 30 })).as_deref(), Some("30"));
+        assert_eq!(value(serde_json::json!({ "type": "select", "select": { "name": "Easy" } })).as_deref(), Some("Easy"));
+        assert_eq!(
+            value(serde_json::json!({ "type": "multi_select", "multi_select": [{ "name": "Vegan" }, { "name": "Quick" }] })).as_deref(),
+            Some("Vegan, Quick")
+        );
+        assert_eq!(
+            value(serde_json::json!({ "type": "date", "date": { "start": "2024-01-01", "end": "2024-01-03" } })).as_deref(),
+            Some("2024-01-01 → 2024-01-03")
+        );
+        assert_eq!(value(serde_json::json!({ "type": "number", "number": null })), None);
+        assert_eq!(value(serde_json::json!({ "type": "checkbox", "checkbox": true })), None);
+    }
+}
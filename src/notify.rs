@@ -0,0 +1,67 @@
+use crate::config::Config;
+use crate::feed;
+
+/// 构建完成后通知 WebSub hub 与 Webhook，让订阅者尽快收到更新。
+/// 通知失败只打印警告，不影响构建结果。
+pub async fn notify(config: &Config) {
+    let Some(feed_url) = feed::feed_url(config) else {
+        println!(">>> 警告: 未配置 feedUrl 或 siteUrl，跳过通知");
+        return;
+    };
+    let feed_url = feed_url.as_str();
+    let http = reqwest::Client::new();
+
+    for hub in &config.websub_hubs {
+        // WebSub 规定的发布通知：hub.mode=publish，hub.url 为更新的 Feed 地址
+        let params = [("hub.mode", "publish"), ("hub.url", feed_url)];
+        match http.post(hub).form(&params).send().await.and_then(|r| r.error_for_status()) {
+            Ok(_) => println!(">>> 已通知 hub: {}", hub),
+            Err(e) => println!(">>> 警告: 通知 hub 失败 {}: {}", hub, e),
+        }
+    }
+
+    if let Some(webhook) = &config.notify_webhook {
+        let body = serde_json::json!({ "feedUrl": feed_url }).to_string();
+        let request = http.post(webhook).header("Content-Type", "application/json").body(body);
+        match request.send().await.and_then(|r| r.error_for_status()) {
+            Ok(_) => println!(">>> 已调用 Webhook: {}", webhook),
+            Err(e) => println!(">>> 警告: 调用 Webhook 失败 {}: {}", webhook, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn pings_hubs_and_webhook_with_the_feed_url() {
+        let server = test_support::MockServer::start("text/plain", Vec::new()).await;
+        let config = test_support::config(json!({
+            "siteUrl": "https://blog.example.com",
+            "indexFeed": true,
+            "websubHubs": [format!("{}/hub", server.url)],
+            "notifyWebhook": format!("{}/webhook", server.url),
+        }));
+        notify(&config).await;
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2, "{:?}", requests);
+        assert!(requests[0].starts_with("POST /hub "), "{}", requests[0]);
+        assert!(requests[0].contains("hub.mode=publish"), "{}", requests[0]);
+        assert!(requests[0].contains("hub.url=https%3A%2F%2Fblog.example.com%2Findex.xml"), "{}", requests[0]);
+        assert!(requests[1].starts_with("POST /webhook "), "{}", requests[1]);
+        assert!(requests[1].contains(r#"{"feedUrl":"https://blog.example.com/index.xml"}"#), "{}", requests[1]);
+    }
+
+    #[tokio::test]
+    async fn failed_pings_do_not_panic() {
+        let config = test_support::config(json!({
+            "feedUrl": "https://blog.example.com/feed.xml",
+            "websubHubs": ["http://127.0.0.1:9/hub"],
+        }));
+        notify(&config).await;
+    }
+}
@@ -0,0 +1,100 @@
+use anyhow::Result;
+use notionrs_types::prelude::*;
+use serde::de::DeserializeOwned;
+
+/// Notion API 的地址与版本，与 notionrs 一致
+pub const NOTION_API: &str = "https://api.notion.com/v1";
+pub const NOTION_VERSION: &str = "2025-09-03";
+
+/// 直接以 HTTP 请求 Notion API 的客户端。
+/// notionrs 把地址写死为 api.notion.com，这里的 `base` 可以替换 (测试时指向本地模拟服务)
+pub struct NotionApi {
+    http: reqwest::Client,
+    base: String,
+    token: String,
+}
+
+impl NotionApi {
+    pub fn new(token: &str) -> Self {
+        Self::with_base(NOTION_API, token)
+    }
+
+    pub fn with_base(base: &str, token: &str) -> Self {
+        NotionApi {
+            http: reqwest::Client::new(),
+            base: base.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+        }
+    }
+
+    /// 查询数据源中符合 `filter` 的一页页面，按 `sorts` 排序 (为空时使用 Notion 的默认顺序)；
+    /// `start_cursor` 为上一页返回的 `next_cursor`
+    pub async fn query_data_source<T: DeserializeOwned + Clone + Send + 'static>(
+        &self,
+        data_source_id: &str,
+        filter: &Filter,
+        sorts: &[Sort],
+        start_cursor: Option<&str>,
+    ) -> Result<ListResponse<PageResponse<T>>> {
+        let mut body = serde_json::json!({ "filter": filter });
+        if !sorts.is_empty() {
+            body["sorts"] = serde_json::to_value(sorts)?;
+        }
+        if let Some(cursor) = start_cursor {
+            body["start_cursor"] = cursor.into();
+        }
+        let body = body.to_string();
+        let request = self
+            .http
+            .post(format!("{}/data_sources/{}/query", self.base, data_source_id))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body);
+        self.send(request).await
+    }
+
+    /// 取回数据源的定义 (含属性名与类型)；只用于校验，保留为原始 JSON
+    pub async fn retrieve_data_source(&self, data_source_id: &str) -> Result<serde_json::Value> {
+        let request = self.http.get(format!("{}/data_sources/{}", self.base, data_source_id));
+        self.send(request).await
+    }
+
+    /// 取回一个 Block 的一页子 Block；`start_cursor` 为上一页返回的 `next_cursor`
+    pub async fn get_block_children(&self, block_id: &str, start_cursor: Option<&str>) -> Result<ListResponse<BlockResponse>> {
+        let mut request = self.http.get(format!("{}/blocks/{}/children", self.base, block_id));
+        if let Some(cursor) = start_cursor {
+            request = request.query(&[("start_cursor", cursor)]);
+        }
+        self.send(request).await
+    }
+
+    /// 取回单个 Block 的原始数据；用于 notionrs 无法识别的 Block 类型 (如「链接到页面」)
+    pub async fn retrieve_block(&self, block_id: &str) -> Result<serde_json::Value> {
+        let request = self.http.get(format!("{}/blocks/{}", self.base, block_id));
+        self.send(request).await
+    }
+
+    /// 取回页面上一页未解决的评论；`start_cursor` 为上一页返回的 `next_cursor`。只用于计数，保留为原始 JSON
+    pub async fn list_comments(&self, block_id: &str, start_cursor: Option<&str>) -> Result<serde_json::Value> {
+        let mut request = self.http.get(format!("{}/comments", self.base)).query(&[("block_id", block_id)]);
+        if let Some(cursor) = start_cursor {
+            request = request.query(&[("start_cursor", cursor)]);
+        }
+        self.send(request).await
+    }
+
+    /// 附带鉴权与版本头发送请求；错误状态码连同响应正文 (含 Notion 的错误码) 一起返回，
+    /// 交给 `retry::describe_notion_error` 判断
+    async fn send<R: DeserializeOwned>(&self, request: reqwest::RequestBuilder) -> Result<R> {
+        let response = request
+            .bearer_auth(&self.token)
+            .header("Notion-Version", NOTION_VERSION)
+            .send()
+            .await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            anyhow::bail!("HTTP {}: {}", status, body);
+        }
+        Ok(serde_json::from_str(&body)?)
+    }
+}
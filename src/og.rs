@@ -0,0 +1,157 @@
+use ab_glyph::{point, Font, FontVec, PxScale, ScaleFont};
+use anyhow::{Context, Result};
+
+/// 分享卡片的输出目录 (相对于站点根目录)
+pub const OG_DIR: &str = "og";
+
+/// 卡片尺寸：Open Graph 推荐的 1200×630
+const WIDTH: u32 = 1200;
+const HEIGHT: u32 = 630;
+const PADDING: f32 = 80.0;
+const TITLE_SIZE: f32 = 64.0;
+const SITE_SIZE: f32 = 32.0;
+/// 标题最多显示的行数，超出部分以省略号结尾
+const MAX_TITLE_LINES: usize = 4;
+
+const BACKGROUND: [u8; 3] = [0xf7, 0xf6, 0xf3];
+const TITLE_COLOR: [u8; 3] = [0x37, 0x35, 0x2f];
+const SITE_COLOR: [u8; 3] = [0x78, 0x77, 0x74];
+const ACCENT: [u8; 3] = [0x52, 0x9c, 0xca];
+
+/// 文章分享卡片的渲染器：标题 + 站点名称，绘制为 PNG
+pub struct CardRenderer {
+    font: FontVec,
+}
+
+impl CardRenderer {
+    /// 加载字体文件 (TTF / OTF)；标题含中文时需要使用包含 CJK 字形的字体
+    pub fn load(font_path: &str) -> Result<Self> {
+        let data = std::fs::read(font_path).with_context(|| format!("无法读取字体文件: {}", font_path))?;
+        let font = FontVec::try_from_vec(data).map_err(|_| anyhow::anyhow!("无法解析字体文件: {}", font_path))?;
+        Ok(Self { font })
+    }
+
+    /// 渲染一张卡片，返回 PNG 数据
+    pub fn render(&self, title: &str, site_name: &str) -> Result<Vec<u8>> {
+        let mut canvas = Canvas::new(WIDTH, HEIGHT, BACKGROUND);
+        // 左侧色条
+        canvas.fill_rect(0, 0, 16, HEIGHT, ACCENT);
+
+        let max_width = WIDTH as f32 - PADDING * 2.0;
+        let line_height = TITLE_SIZE * 1.3;
+        for (i, line) in self.wrap(title, TITLE_SIZE, max_width).iter().enumerate() {
+            let baseline = PADDING + TITLE_SIZE + i as f32 * line_height;
+            self.draw_text(&mut canvas, line, TITLE_SIZE, PADDING, baseline, TITLE_COLOR);
+        }
+        self.draw_text(&mut canvas, site_name, SITE_SIZE, PADDING, HEIGHT as f32 - PADDING, SITE_COLOR);
+
+        canvas.encode_png()
+    }
+
+    /// 按宽度逐字符折行 (CJK 文本没有空格可供断行)，英文尽量在空格处断开
+    fn wrap(&self, text: &str, size: f32, max_width: f32) -> Vec<String> {
+        let font = self.font.as_scaled(PxScale::from(size));
+        let mut lines: Vec<String> = Vec::new();
+        let mut line = String::new();
+        let mut width = 0.0;
+        for c in text.chars() {
+            let advance = font.h_advance(font.glyph_id(c));
+            if width + advance > max_width && !line.is_empty() {
+                // 行内有空格时把最后一个单词移到下一行
+                let carry = match line.rfind(' ') {
+                    Some(i) if c != ' ' && i > 0 => line.split_off(i + 1),
+                    _ => String::new(),
+                };
+                lines.push(line.trim_end().to_string());
+                line = carry;
+                width = line.chars().map(|c| font.h_advance(font.glyph_id(c))).sum();
+                if c == ' ' {
+                    continue;
+                }
+            }
+            line.push(c);
+            width += advance;
+        }
+        if !line.trim().is_empty() {
+            lines.push(line.trim_end().to_string());
+        }
+        if lines.len() > MAX_TITLE_LINES {
+            lines.truncate(MAX_TITLE_LINES);
+            if let Some(last) = lines.last_mut() {
+                last.pop();
+                last.push('…');
+            }
+        }
+        lines
+    }
+
+    fn draw_text(&self, canvas: &mut Canvas, text: &str, size: f32, x: f32, baseline: f32, color: [u8; 3]) {
+        let scale = PxScale::from(size);
+        let font = self.font.as_scaled(scale);
+        let mut caret = x;
+        let mut previous = None;
+        for c in text.chars() {
+            let id = font.glyph_id(c);
+            if let Some(prev) = previous {
+                caret += font.kern(prev, id);
+            }
+            let glyph = id.with_scale_and_position(scale, point(caret, baseline));
+            caret += font.h_advance(id);
+            previous = Some(id);
+            if let Some(outlined) = self.font.outline_glyph(glyph) {
+                let bounds = outlined.px_bounds();
+                outlined.draw(|gx, gy, coverage| {
+                    let px = bounds.min.x as i64 + gx as i64;
+                    let py = bounds.min.y as i64 + gy as i64;
+                    canvas.blend(px, py, color, coverage);
+                });
+            }
+        }
+    }
+}
+
+/// RGB 画布
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32, background: [u8; 3]) -> Self {
+        let pixels = background.repeat((width * height) as usize);
+        Self { width, height, pixels }
+    }
+
+    fn fill_rect(&mut self, x: u32, y: u32, w: u32, h: u32, color: [u8; 3]) {
+        for py in y..(y + h).min(self.height) {
+            for px in x..(x + w).min(self.width) {
+                let i = ((py * self.width + px) * 3) as usize;
+                self.pixels[i..i + 3].copy_from_slice(&color);
+            }
+        }
+    }
+
+    /// 按覆盖率把颜色混合到像素上 (用于字形抗锯齿)
+    fn blend(&mut self, x: i64, y: i64, color: [u8; 3], coverage: f32) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let alpha = coverage.clamp(0.0, 1.0);
+        let i = ((y as u32 * self.width + x as u32) * 3) as usize;
+        for (channel, target) in self.pixels[i..i + 3].iter_mut().zip(color) {
+            *channel = (*channel as f32 * (1.0 - alpha) + target as f32 * alpha).round() as u8;
+        }
+    }
+
+    fn encode_png(&self) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        let mut encoder = png::Encoder::new(&mut data, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        writer.finish()?;
+        Ok(data)
+    }
+}
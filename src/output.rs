@@ -0,0 +1,245 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 输出目录
+pub const OUTPUT_DIR: &str = "public";
+
+/// 输出文件所在目录到站点根目录的相对路径，即模板中的 `rootPath`：
+/// `public/index.html` -> `.`，`public/tag/a.html` -> `..`，`public/2024/01/slug/index.html` -> `../../..`
+pub fn root_path(output_path: &str) -> String {
+    let relative = Path::new(output_path).strip_prefix(OUTPUT_DIR).unwrap_or(Path::new(output_path));
+    let depth = relative.parent().map_or(0, |dir| dir.components().count());
+    if depth == 0 {
+        ".".to_string()
+    } else {
+        vec![".."; depth].join("/")
+    }
+}
+
+/// 计划 (或已经) 生成的文件
+#[derive(Debug, Clone)]
+pub struct OutputFile {
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// HTML 后处理函数：接收完整页面，返回处理后的页面
+pub type PostProcess = Box<dyn Fn(&str) -> String + Send + Sync>;
+
+/// 所有输出文件的写入入口。
+/// 正常模式下写入磁盘；dry-run 模式下只记录将要生成的文件及大小，不触碰文件系统。
+/// 两种模式都会记录文件清单，供后续步骤 (如报告) 使用。
+#[derive(Default)]
+pub struct Output {
+    dry_run: bool,
+    files: Vec<OutputFile>,
+    /// 写出的内容 (启用 `with_contents` 时)
+    contents: Option<HashMap<PathBuf, Vec<u8>>>,
+    post_processors: Vec<PostProcess>,
+}
+
+impl std::fmt::Debug for Output {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Output")
+            .field("dry_run", &self.dry_run)
+            .field("files", &self.files)
+            .field("contents", &self.contents.as_ref().map(|c| c.len()))
+            .field("post_processors", &self.post_processors.len())
+            .finish()
+    }
+}
+
+impl Output {
+    pub fn new(dry_run: bool) -> Self {
+        Self { dry_run, files: Vec::new(), contents: None, post_processors: Vec::new() }
+    }
+
+    /// 在内存中保留写出的内容 (dry-run 时同样保留)，可用 `contents` 读取，便于检查或比较两次构建的输出
+    pub fn with_contents(mut self) -> Self {
+        self.contents = Some(HashMap::new());
+        self
+    }
+
+    /// 注册 HTML 后处理函数 (如注入统计代码、改写 URL)。
+    /// 每个 `.html` 文件在写出前依次经过所有后处理函数，顺序与注册顺序一致；
+    /// 压缩之类需要最后执行的处理应当最后注册。拷贝的静态文件不经过后处理。
+    pub fn with_post_process<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        self.post_processors.push(Box::new(f));
+        self
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// 写入文件，自动创建父目录
+    pub fn write<P: AsRef<Path>, C: AsRef<[u8]>>(&mut self, path: P, contents: C) -> Result<()> {
+        let path = path.as_ref();
+        let processed = self.post_process(path, contents.as_ref());
+        let contents = processed.as_deref().map_or(contents.as_ref(), str::as_bytes);
+        if !self.dry_run {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, contents)?;
+        }
+        if let Some(kept) = self.contents.as_mut() {
+            kept.insert(path.to_path_buf(), contents.to_vec());
+        }
+        self.files.push(OutputFile { path: path.to_path_buf(), size: contents.len() as u64 });
+        Ok(())
+    }
+
+    /// 对 HTML 文件依次应用后处理函数；无需处理时返回 None
+    fn post_process(&self, path: &Path, contents: &[u8]) -> Option<String> {
+        if self.post_processors.is_empty() || path.extension().is_none_or(|ext| ext != "html") {
+            return None;
+        }
+        let html = std::str::from_utf8(contents).ok()?;
+        let mut html = html.to_string();
+        for f in &self.post_processors {
+            html = f(&html);
+        }
+        Some(html)
+    }
+
+    /// 拷贝文件，自动创建父目录
+    pub fn copy<P: AsRef<Path>, Q: AsRef<Path>>(&mut self, src: P, dst: Q) -> Result<()> {
+        let (src, dst) = (src.as_ref(), dst.as_ref());
+        let size = fs::metadata(src)?.len();
+        if !self.dry_run {
+            if let Some(parent) = dst.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(src, dst)?;
+        }
+        if let Some(kept) = self.contents.as_mut() {
+            kept.insert(dst.to_path_buf(), fs::read(src)?);
+        }
+        self.files.push(OutputFile { path: dst.to_path_buf(), size });
+        Ok(())
+    }
+
+    /// 递归拷贝目录
+    pub fn copy_dir(&mut self, src: &Path, dst: &Path) -> Result<()> {
+        for entry in fs::read_dir(src)? {
+            let entry = entry?;
+            let ty = entry.file_type()?;
+            let src_path = entry.path();
+            let dst_path = dst.join(entry.file_name());
+
+            if ty.is_dir() {
+                self.copy_dir(&src_path, &dst_path)?;
+            } else {
+                self.copy(&src_path, &dst_path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 设置已写出的文件，以及它们在 `root` 之下的各级目录 (含 `root`) 的权限，
+    /// 供部署到对权限敏感的服务器或同步到 S3 等存储。dry-run 时不做任何事；仅在 Unix 上生效
+    pub fn set_permissions(&self, root: &Path, file_mode: Option<u32>, dir_mode: Option<u32>) -> Result<()> {
+        if self.dry_run || (file_mode.is_none() && dir_mode.is_none()) {
+            return Ok(());
+        }
+        #[cfg(unix)]
+        {
+            use std::collections::BTreeSet;
+            use std::os::unix::fs::PermissionsExt;
+            let mut dirs = BTreeSet::new();
+            for file in &self.files {
+                if let Some(mode) = file_mode {
+                    fs::set_permissions(&file.path, fs::Permissions::from_mode(mode))?;
+                }
+                dirs.extend(file.path.ancestors().skip(1).filter(|dir| dir.starts_with(root)));
+            }
+            if let Some(mode) = dir_mode {
+                for dir in dirs {
+                    fs::set_permissions(dir, fs::Permissions::from_mode(mode))?;
+                }
+            }
+        }
+        #[cfg(not(unix))]
+        println!(">>> 警告: 当前系统不支持设置文件权限，已忽略 fileMode / dirMode");
+        Ok(())
+    }
+
+    /// 已写入 (或计划写入) 的文件清单
+    pub fn files(&self) -> &[OutputFile] {
+        &self.files
+    }
+
+    /// 文件的内容；未启用 `with_contents` 或没有写出该文件时为 None
+    pub fn contents<P: AsRef<Path>>(&self, path: P) -> Option<&[u8]> {
+        self.contents.as_ref()?.get(path.as_ref()).map(Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn dry_run_lists_files_without_writing() {
+        let dir = test_support::temp_dir("dry-run");
+        let mut output = Output::new(true);
+        output.write(dir.join("public/index.html"), "<html></html>").unwrap();
+        output.copy("Cargo.toml", dir.join("public/Cargo.toml")).unwrap();
+
+        let planned: Vec<PathBuf> = output.files().iter().map(|f| f.path.clone()).collect();
+        assert_eq!(planned, [dir.join("public/index.html"), dir.join("public/Cargo.toml")]);
+        assert_eq!(output.files()[0].size, 13);
+        assert!(!dir.join("public").exists());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn permissions_apply_to_files_and_directories_under_root() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = test_support::temp_dir("permissions");
+        let root = dir.join("public");
+        let mut output = Output::new(false);
+        output.write(root.join("tag/a.html"), "a").unwrap();
+        output.set_permissions(&root, Some(0o640), Some(0o750)).unwrap();
+
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o7777;
+        assert_eq!(mode(&root.join("tag/a.html")), 0o640);
+        assert_eq!(mode(&root.join("tag")), 0o750);
+        assert_eq!(mode(&root), 0o750);
+        assert_ne!(mode(&dir), 0o750);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn post_process_hooks_run_on_rendered_html_pages() {
+        let config = test_support::config(serde_json::json!({}));
+        let post = test_support::post(test_support::meta("a", "2024-01-01"), "<p>marker</p>");
+        let result = test_support::result(&config, vec![post]);
+        let mut output = Output::new(true)
+            .with_contents()
+            .with_post_process(|html| html.replace("marker", "MARKER"))
+            .with_post_process(|html| html.replace("MARKER", "MARKER!"));
+        crate::Site::render_to(&config, &result, &mut output).unwrap();
+
+        let page = String::from_utf8_lossy(output.contents(Path::new("public/a.html")).unwrap()).into_owned();
+        assert!(page.contains("<p>MARKER!</p>"), "{}", page);
+        output.write("public/posts.json", "[\"marker\"]").unwrap();
+        assert_eq!(output.contents(Path::new("public/posts.json")), Some(&b"[\"marker\"]"[..]));
+    }
+
+    #[test]
+    fn root_path_follows_output_depth() {
+        assert_eq!(root_path("public/index.html"), ".");
+        assert_eq!(root_path("public/tag/a.html"), "..");
+        assert_eq!(root_path("public/slug/index.html"), "..");
+        assert_eq!(root_path("public/2024/01/slug/index.html"), "../../..");
+    }
+}
@@ -0,0 +1,115 @@
+/// 包含其他块级元素的容器标签：开闭标签各占一行，内容缩进
+const CONTAINER_TAGS: &[&str] = &[
+    "div", "ul", "ol", "table", "thead", "tbody", "tr", "details", "figure", "blockquote", "section", "article", "nav",
+];
+/// 只含行内内容的块级标签：另起一行，内容与闭合标签保持在同一行
+const LEAF_TAGS: &[&str] = &["p", "h1", "h2", "h3", "h4", "h5", "h6", "li", "summary", "figcaption", "td", "th", "hr"];
+/// 内容必须原样保留的标签
+const RAW_TAGS: &[&str] = &["pre", "style", "script", "textarea"];
+
+/// 为生成的 HTML 片段换行并缩进，便于阅读与比较差异。
+/// 只在块级元素之间插入空白，不改变行内内容；`<pre>` 等标签内的内容原样保留。
+pub fn pretty_html(html: &str) -> String {
+    let mut out = String::with_capacity(html.len() * 2);
+    let mut depth = 0usize;
+    let mut rest = html;
+
+    while let Some(lt) = rest.find('<') {
+        push_text(&mut out, &rest[..lt]);
+        rest = &rest[lt..];
+
+        // 注释原样输出
+        if rest.starts_with("<!--") {
+            let end = rest.find("-->").map_or(rest.len(), |i| i + 3);
+            out.push_str(&rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+
+        let Some(gt) = rest.find('>') else { break };
+        let tag = &rest[..=gt];
+        rest = &rest[gt + 1..];
+        let closing = tag.starts_with("</");
+        let name = tag_name(tag);
+
+        if !closing && RAW_TAGS.contains(&name.as_str()) {
+            newline(&mut out, depth);
+            out.push_str(tag);
+            let close = format!("</{}>", name);
+            let end = rest.find(&close).map_or(rest.len(), |i| i + close.len());
+            out.push_str(&rest[..end]);
+            rest = &rest[end..];
+        } else if CONTAINER_TAGS.contains(&name.as_str()) {
+            if closing {
+                depth = depth.saturating_sub(1);
+            }
+            newline(&mut out, depth);
+            out.push_str(tag);
+            if !closing && !tag.ends_with("/>") {
+                depth += 1;
+            }
+        } else if !closing && LEAF_TAGS.contains(&name.as_str()) {
+            newline(&mut out, depth);
+            out.push_str(tag);
+        } else {
+            out.push_str(tag);
+        }
+    }
+    push_text(&mut out, rest);
+    out.trim_start().to_string()
+}
+
+/// 标签名 (小写，不含 `<`、`/` 与属性)
+fn tag_name(tag: &str) -> String {
+    tag.trim_start_matches('<')
+        .trim_start_matches('/')
+        .split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .next()
+        .unwrap_or("")
+        .to_lowercase()
+}
+
+/// 原有的换行缩进 (只含空白且带换行的文本) 由 `newline` 重新生成，这里丢弃；
+/// 行内元素之间的空格保留
+fn push_text(out: &mut String, text: &str) {
+    if text.trim().is_empty() && text.contains('\n') {
+        return;
+    }
+    out.push_str(text);
+}
+
+fn newline(out: &mut String, depth: usize) {
+    out.push('\n');
+    out.push_str(&"  ".repeat(depth));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 标签与非空白文本组成的序列：块级元素之间插入的换行缩进不影响该序列，可视为同一 DOM
+    fn nodes(html: &str) -> Vec<String> {
+        html.split_inclusive('>')
+            .flat_map(|chunk| match chunk.find('<') {
+                Some(lt) => vec![chunk[..lt].to_string(), chunk[lt..].to_string()],
+                None => vec![chunk.to_string()],
+            })
+            .filter(|node| !node.trim().is_empty() || !node.contains('\n'))
+            .filter(|node| !node.is_empty())
+            .collect()
+    }
+
+    #[test]
+    fn indents_blocks_and_keeps_the_same_nodes() {
+        let compact = "<div class=\"callout\"><p>Hello <strong>world</strong></p><ul><li>One</li><li>Two</li></ul></div>\
+            <pre><code>fn main() {\n    println!(\"hi\");\n}</code></pre>";
+        let pretty = pretty_html(compact);
+        assert_eq!(
+            pretty,
+            "<div class=\"callout\">\n  <p>Hello <strong>world</strong></p>\n  <ul>\n    <li>One</li>\n    <li>Two</li>\n  </ul>\n</div>\n\
+             <pre><code>fn main() {\n    println!(\"hi\");\n}</code></pre>"
+        );
+        assert_eq!(nodes(&pretty), nodes(compact));
+        assert_eq!(pretty_html(&pretty), pretty);
+    }
+}
@@ -1,163 +1,570 @@
 use notionrs_types::prelude::*;
-use anyhow::Result;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt::Write;
 
-pub struct HtmlRenderer;
+/// 渲染选项，由 `Config` 生成
+#[derive(Debug, Clone, Default)]
+pub struct RenderOptions {
+    /// 允许以 iframe 嵌入的域名；为 None 时不限制
+    pub embed_allowed_hosts: Option<Vec<String>>,
+    pub todo_style: TodoStyle,
+    pub empty_paragraph: EmptyParagraph,
+    /// 阅读模式：Embed、PDF 等交互内容一律渲染为静态链接卡片
+    pub reader_mode: bool,
+    /// 超过该行数的代码块折叠显示
+    pub code_collapse_lines: Option<usize>,
+    /// 代码块标题栏中显示复制按钮
+    pub code_copy_button: bool,
+}
+
+/// To-do 的渲染方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TodoStyle {
+    /// 行内的禁用复选框
+    #[default]
+    Checkbox,
+    /// GitHub 风格的 `<ul class="task-list">`
+    Tasklist,
+}
+
+/// 空段落 (作者有意留出的空行) 的渲染方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmptyParagraph {
+    /// 固定高度的 `<div class="spacer"></div>`
+    #[default]
+    Spacer,
+    /// `<p class="empty-paragraph"></p>`，由主题决定高度
+    Paragraph,
+    /// `<br>`
+    Br,
+}
+
+/// 列表项所属的列表容器，连续的同类列表项会被包裹在同一个容器中
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListKind {
+    Bulleted,
+    Numbered,
+    Task,
+}
+
+/// 有序列表各层级的开始标签：与 Notion 一致，编号样式按嵌套层级循环 (1. → a. → i.)。
+/// `type` 只是默认样式，主题可用 CSS 的 `list-style-type` 覆盖
+const NUMBERED_OPEN_TAGS: [&str; 3] = ["<ol type=\"1\">", "<ol type=\"a\">", "<ol type=\"i\">"];
+
+impl ListKind {
+    /// 列表的开始标签；`numbered_level` 为外层有序列表的数量，决定有序列表的编号样式
+    pub fn open_tag(self, numbered_level: usize) -> &'static str {
+        match self {
+            ListKind::Bulleted => "<ul>",
+            ListKind::Numbered => NUMBERED_OPEN_TAGS[numbered_level % NUMBERED_OPEN_TAGS.len()],
+            ListKind::Task => "<ul class=\"task-list\">",
+        }
+    }
+
+    pub fn close_tag(self) -> &'static str {
+        match self {
+            ListKind::Bulleted | ListKind::Task => "</ul>",
+            ListKind::Numbered => "</ol>",
+        }
+    }
+}
+
+/// 页面、Callout 等处图标的解析结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IconHtml {
+    /// Emoji 字符，直接输出
+    Emoji(String),
+    /// 图片地址：外链、上传到 Notion 的文件 (有时效的签名链接) 或自定义 Emoji
+    Image(String),
+}
+
+impl IconHtml {
+    /// Emoji 原样输出，图片输出为带 `class` 的 `<img>`
+    pub fn to_html(&self, class: &str) -> String {
+        match self {
+            IconHtml::Emoji(emoji) => escape_html(emoji),
+            IconHtml::Image(url) => format!(
+                "<img class=\"{}\" src=\"{}\" style=\"width: 1.2em; height: 1.2em; vertical-align: middle;\" />",
+                class,
+                escape_html(url)
+            ),
+        }
+    }
+
+    /// Emoji 字符或图片地址，供模板中的 `iconUrl` 使用
+    pub fn into_source(self) -> String {
+        match self {
+            IconHtml::Emoji(s) | IconHtml::Image(s) => s,
+        }
+    }
+
+    /// `into_source` 的逆操作：`http` 开头的为图片地址，其余为 Emoji
+    pub fn from_source(source: String) -> Self {
+        if source.starts_with("http") { IconHtml::Image(source) } else { IconHtml::Emoji(source) }
+    }
+}
+
+/// 解析 Notion 图标；页面图标、Callout 图标与 Markdown 导出共用，保证各处处理一致
+pub fn render_icon(icon: &Option<Icon>) -> Option<IconHtml> {
+    match icon {
+        Some(Icon::Emoji(emoji)) => Some(IconHtml::Emoji(emoji.emoji.clone())),
+        // 外链与上传到 Notion 的图标都取其 URL
+        Some(Icon::File(file)) => Some(file.to_string()).filter(|url| !url.is_empty()).map(IconHtml::Image),
+        Some(Icon::CustomEmoji(custom)) => Some(IconHtml::Image(custom.custom_emoji.url.clone())),
+        None => None,
+    }
+}
+
+/// 站内页面：站内链接，以及链接卡片中显示的标题与图标
+#[derive(Debug, Clone)]
+pub struct PageLink {
+    pub url: String,
+    pub title: String,
+    pub icon: Option<IconHtml>,
+}
+
+pub struct HtmlRenderer {
+    options: RenderOptions,
+    /// 本次构建中的页面 (及站点数据库本身)：Notion ID (32 位，无连字符) -> 站内页面
+    pages: HashMap<String, PageLink>,
+}
 
 impl HtmlRenderer {
-    pub fn render_block(block: &Block) -> String {
-        match block {
-            Block::Paragraph { paragraph } => {
-                let text = Self::render_rich_text(&paragraph.rich_text);
-                let color_class = Self::get_color_class(&paragraph.color);
-                format!("<p class=\"{}\">{}</p>", color_class, text)
-            }
-            Block::Heading1 { heading_1 } => {
-                let text = Self::render_rich_text(&heading_1.rich_text);
-                let color_class = Self::get_color_class(&heading_1.color);
-                format!("<h1 class=\"{}\">{}</h1>", color_class, text)
-            }
-            Block::Heading2 { heading_2 } => {
-                let text = Self::render_rich_text(&heading_2.rich_text);
-                let color_class = Self::get_color_class(&heading_2.color);
-                format!("<h2 class=\"{}\">{}</h2>", color_class, text)
-            }
-            Block::Heading3 { heading_3 } => {
-                let text = Self::render_rich_text(&heading_3.rich_text);
-                let color_class = Self::get_color_class(&heading_3.color);
-                format!("<h3 class=\"{}\">{}</h3>", color_class, text)
-            }
-            Block::BulletedListItem { bulleted_list_item } => {
-                let text = Self::render_rich_text(&bulleted_list_item.rich_text);
-                let color_class = Self::get_color_class(&bulleted_list_item.color);
-                format!("<li class=\"{}\">{}</li>", color_class, text)
-            }
-            Block::NumberedListItem { numbered_list_item } => {
-                let text = Self::render_rich_text(&numbered_list_item.rich_text);
-                let color_class = Self::get_color_class(&numbered_list_item.color);
-                format!("<li class=\"{}\">{}</li>", color_class, text)
+    pub fn new(options: RenderOptions) -> Self {
+        Self { options, pages: HashMap::new() }
+    }
+
+    /// 设置站内页面表，指向这些页面的 Notion 链接会被改写为站内链接
+    pub fn with_pages(mut self, pages: HashMap<String, PageLink>) -> Self {
+        self.pages = pages;
+        self
+    }
+
+    /// 若链接指向本次构建中的 Notion 页面，返回站内链接，否则原样返回
+    pub fn resolve_link(&self, url: &str) -> String {
+        extract_notion_id(url)
+            .and_then(|id| self.pages.get(&id).map(|page| page.url.clone()))
+            .unwrap_or_else(|| url.to_string())
+    }
+
+    /// 按 Notion ID (可含连字符) 查找站内链接
+    fn page_url_by_id(&self, id: &str) -> Option<String> {
+        self.pages.get(&id.replace('-', "").to_lowercase()).map(|page| page.url.clone())
+    }
+
+    pub fn render_block(&self, block: &Block) -> String {
+        let mut html = String::new();
+        self.render_block_into(&mut html, block);
+        html
+    }
+
+    /// 将 Block 渲染到 `out` 的末尾，可在多个 Block 间复用同一个缓冲区。
+    /// 段落、标题、列表项与引用等高频 Block 直接写入，不产生中间字符串
+    pub fn render_block_into(&self, out: &mut String, block: &Block) {
+        let (tag, rich_text, color) = match block {
+            Block::Paragraph { paragraph } if !paragraph.rich_text.is_empty() => ("p", &paragraph.rich_text, &paragraph.color),
+            Block::Heading1 { heading_1 } => ("h1", &heading_1.rich_text, &heading_1.color),
+            Block::Heading2 { heading_2 } => ("h2", &heading_2.rich_text, &heading_2.color),
+            Block::Heading3 { heading_3 } => ("h3", &heading_3.rich_text, &heading_3.color),
+            Block::BulletedListItem { bulleted_list_item } => ("li", &bulleted_list_item.rich_text, &bulleted_list_item.color),
+            Block::NumberedListItem { numbered_list_item } => ("li", &numbered_list_item.rich_text, &numbered_list_item.color),
+            Block::Quote { quote } => ("blockquote", &quote.rich_text, &quote.color),
+            _ => {
+                self.render_other_block_into(out, block);
+                return;
             }
+        };
+        let _ = write!(out, "<{} class=\"{}\">", tag, Self::get_color_class(color));
+        self.render_rich_text_into(out, rich_text, true);
+        let _ = write!(out, "</{}>", tag);
+    }
+
+    /// 其余 Block 的渲染，同样写入 `out`
+    fn render_other_block_into(&self, out: &mut String, block: &Block) {
+        match block {
+            // 空段落是作者有意留出的空行，输出占位，避免被浏览器折叠
+            Block::Paragraph { paragraph } if paragraph.rich_text.is_empty() => out.push_str(match self.options.empty_paragraph {
+                EmptyParagraph::Spacer => "<div class=\"spacer\"></div>",
+                EmptyParagraph::Paragraph => "<p class=\"empty-paragraph\"></p>",
+                EmptyParagraph::Br => "<br>",
+            }),
             Block::Code { code } => {
-                let text = Self::render_rich_text(&code.rich_text);
-                format!("<pre><code class=\"language-{}\">{}</code></pre>", code.language, text)
-            }
-            Block::Quote { quote } => {
-                let text = Self::render_rich_text(&quote.rich_text);
-                let color_class = Self::get_color_class(&quote.color);
-                format!("<blockquote class=\"{}\">{}</blockquote>", color_class, text)
+                // 代码块保留原始换行，不转换为 <br>；折叠时需要行数，先渲染到单独的字符串
+                let text = self.render_rich_text_with(&code.rich_text, false);
+                // 代码块的 Caption 常被用来标注文件名：像文件名时显示在标题栏中，否则作为说明文字
+                let filename = Some(plain_text(&code.caption)).filter(|c| looks_like_filename(c));
+                let has_caption = filename.is_none() && !code.caption.is_empty();
+                let has_header = filename.is_some() || self.options.code_copy_button;
+                if has_header || has_caption {
+                    out.push_str("<figure class=\"code-block\">");
+                }
+                if has_header {
+                    out.push_str("<div class=\"code-header\">");
+                    if let Some(name) = &filename {
+                        let _ = write!(out, "<span class=\"code-filename\">{}</span>", escape_html(name));
+                    }
+                    let _ = write!(out, "<span class=\"code-language\">{}</span>", escape_html(&code.language.to_string()));
+                    if self.options.code_copy_button {
+                        let _ = write!(
+                            out,
+                            "<button type=\"button\" class=\"code-copy\" data-code=\"{}\">复制</button>",
+                            escape_html(&plain_text(&code.rich_text))
+                        );
+                    }
+                    out.push_str("</div>");
+                }
+                // 过长的代码块折叠起来，完整代码仍在 HTML 中以便搜索引擎收录
+                let lines = text.lines().count();
+                let collapse = self.options.code_collapse_lines.is_some_and(|limit| limit > 0 && lines > limit);
+                if collapse {
+                    let _ = write!(out, "<details class=\"code-collapse\"><summary>显示更多 ({} 行)</summary>", lines);
+                }
+                let _ = write!(out, "<pre><code class=\"language-{}\">{}</code></pre>", code.language, text);
+                if collapse {
+                    out.push_str("</details>");
+                }
+                if has_caption {
+                    out.push_str("<figcaption class=\"code-caption\">");
+                    self.render_rich_text_into(out, &code.caption, true);
+                    out.push_str("</figcaption>");
+                }
+                if has_header || has_caption {
+                    out.push_str("</figure>");
+                }
             }
             Block::Callout { callout } => {
-                let text = Self::render_rich_text(&callout.rich_text);
-                let emoji = match &callout.icon {
-                    Some(icon) => icon.to_string(),
-                    None => "💡".to_string(),
-                };
-                let color_class = Self::get_color_class(&callout.color);
-                format!("<div class=\"callout {}\"><span style=\"margin-right: 10px;\">{}</span>{}</div>", color_class, emoji, text)
-            }
-            Block::Image { image } => {
-                let url = image.to_string();
-                format!("<figure><img src=\"{}\" style=\"max-width: 100%; border-radius: 5px;\" /><figcaption></figcaption></figure>", url)
+                // data-callout-color 保留 Notion 原始颜色名 (如 blue_background)，供主题统一设置边框与背景
+                let _ = write!(
+                    out,
+                    "<div class=\"callout {}\" data-callout-color=\"{}\"><span class=\"callout-icon\" style=\"margin-right: 10px;\">",
+                    Self::get_color_class(&callout.color),
+                    Self::color_name(&callout.color)
+                );
+                // 与页面图标一致：Emoji 直接输出字符，文件 / 自定义 Emoji 输出图片
+                match render_icon(&callout.icon) {
+                    Some(icon) => out.push_str(&icon.to_html("callout-icon")),
+                    None => out.push('💡'),
+                }
+                out.push_str("</span><div class=\"callout-text\">");
+                self.render_rich_text_into(out, &callout.rich_text, true);
+                out.push_str("</div></div>");
             }
+            Block::Image { image } => Self::write_image(out, &image.to_string()),
             Block::Video { video } => {
-                let url = video.to_string();
-                format!("<div class=\"video-block\"><video controls src=\"{}\" style=\"max-width: 100%; border-radius: 5px;\"></video></div>", url)
+                let _ = write!(
+                    out,
+                    "<div class=\"video-block\"><video controls src=\"{}\" style=\"max-width: 100%; border-radius: 5px;\"></video></div>",
+                    video
+                );
             }
             Block::Audio { audio } => {
-                let url = audio.to_string();
-                format!("<div class=\"audio-block\"><audio controls src=\"{}\" style=\"width: 100%; margin: 10px 0;\"></audio></div>", url)
+                // 浏览器不支持该音频格式时 <audio> 什么也不显示，始终附带下载链接
+                let url = escape_html(&audio.to_string());
+                let name = escape_html(&file_title(audio, &audio.to_string()));
+                let _ = write!(
+                    out,
+                    "<figure class=\"audio-block\"><audio controls preload=\"metadata\" src=\"{}\" style=\"width: 100%; margin: 10px 0;\"><a href=\"{}\">{}</a></audio><figcaption><a class=\"audio-download\" href=\"{}\" download>⬇ {}</a></figcaption></figure>",
+                    url, url, name, url, name
+                );
             }
             Block::File { file } => {
                 let url = file.to_string();
-                let name = url.split('/').last().unwrap_or("Download File");
-                format!("<div class=\"file-block\"><a href=\"{}\" target=\"_blank\" class=\"file-link\">📎 {}</a></div>", url, name)
+                let name = url.split('/').next_back().unwrap_or("Download File");
+                Self::write_file_link(out, &url, name);
             }
             Block::Pdf { pdf } => {
                 let url = pdf.to_string();
-                format!("<div class=\"pdf-block\"><embed src=\"{}\" type=\"application/pdf\" width=\"100%\" height=\"500px\" /></div>", url)
+                if self.options.reader_mode {
+                    Self::write_link_card(out, &url, "bookmark");
+                } else {
+                    let _ = write!(
+                        out,
+                        "<div class=\"pdf-block\"><embed src=\"{}\" type=\"application/pdf\" width=\"100%\" height=\"500px\" /></div>",
+                        url
+                    );
+                }
             }
             Block::Embed { embed } => {
-                let url = embed.url.clone();
-                // 简单嵌入 iframe，更复杂的需解析 URL (如 Bilibili, YouTube)
-                format!("<div class=\"embed-block\"><iframe src=\"{}\" style=\"width: 100%; height: 400px; border: none;\"></iframe></div>", url)
-            }
-            Block::Bookmark { bookmark } => {
-                let url = bookmark.url.clone();
-                // 书签样式
-                format!(
-                    "<a href=\"{}\" class=\"bookmark\" target=\"_blank\" style=\"display: block; border: 1px solid #ddd; padding: 12px; border-radius: 4px; margin: 10px 0; text-decoration: none; color: inherit;\">
-                        <div style=\"font-weight: bold;\">{}</div>
-                        <div style=\"font-size: 0.9em; color: #666; overflow: hidden; white-space: nowrap; text-overflow: ellipsis;\">{}</div>
-                    </a>",
-                    url, url, url
-                )
+                let url = &embed.url;
+                // 阅读模式或不在白名单内的域名不渲染 iframe，退化为普通链接卡片
+                if self.options.reader_mode || !self.is_embed_allowed(url) {
+                    Self::write_link_card(out, url, "bookmark");
+                } else {
+                    // 简单嵌入 iframe，更复杂的需解析 URL (如 Bilibili, YouTube)
+                    let _ = write!(
+                        out,
+                        "<div class=\"embed-block\"><iframe src=\"{}\" style=\"width: 100%; height: 400px; border: none;\"></iframe></div>",
+                        url
+                    );
+                }
+            }
+            Block::Bookmark { bookmark } => Self::write_link_card(out, &self.resolve_link(&bookmark.url), "bookmark"),
+            Block::LinkPreview { link_preview } => {
+                Self::write_link_card(out, &self.resolve_link(&link_preview.url), "bookmark link-preview")
             }
             Block::Toggle { toggle } => {
-                let text = Self::render_rich_text(&toggle.rich_text);
                 // 注意：Toggle 的子内容会在 main.rs 的递归中处理，但这里我们无法直接包裹子内容
                 // 因为 main.rs 的逻辑是平铺渲染。
                 // *重要*：目前的 main.rs 逻辑对于 Toggle 这种容器类 Block 支持不够完美（它只是简单的平铺）。
                 // 为了完美支持 Toggle，需要在 main.rs 中特殊处理容器 Block 的闭合标签。
                 // 但作为 renderer 的一部分，我们至少可以渲染 summary。
-                format!("<details><summary>{}</summary></details>", text)
+                out.push_str("<details><summary>");
+                self.render_rich_text_into(out, &toggle.rich_text, true);
+                out.push_str("</summary></details>");
             }
             Block::ToDo { to_do } => {
-                let text = Self::render_rich_text(&to_do.rich_text);
                 let checked = if to_do.checked { "checked" } else { "" };
+                if self.options.todo_style == TodoStyle::Tasklist {
+                    let _ = write!(
+                        out,
+                        "<li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" {} disabled> ",
+                        checked
+                    );
+                    self.render_rich_text_into(out, &to_do.rich_text, true);
+                    out.push_str("</li>");
+                    return;
+                }
                 let style = if to_do.checked { "text-decoration: line-through; opacity: 0.7;" } else { "" };
-                format!(
+                let _ = write!(
+                    out,
                     "<div class=\"todo-item\" style=\"display: flex; align-items: center; margin: 4px 0;\">
                         <input type=\"checkbox\" {} disabled style=\"margin-right: 8px;\">
-                        <span style=\"{}\">{}</span>
-                    </div>",
-                    checked, style, text
-                )
+                        <span style=\"{}\">",
+                    checked, style
+                );
+                self.render_rich_text_into(out, &to_do.rich_text, true);
+                out.push_str("</span>
+                    </div>");
             }
             Block::Equation { equation } => {
-                format!("<div class=\"equation-block\">{}</div>", equation.expression)
+                out.push_str("<div class=\"equation-block\">");
+                out.push_str(&escape_math(&equation.expression));
+                out.push_str("</div>");
+            }
+            // 样式由主题的 .divider 规则控制
+            Block::Divider { .. } => out.push_str("<hr class=\"divider\" />"),
+            _ => {
+                let _ = write!(out, "{}: {} -->", UNSUPPORTED_BLOCK, block_type_name(block));
+            }
+        }
+    }
+
+    /// 渲染表格。`rows` 为全部 table_row 的单元格，按原顺序排列；
+    /// 表头只取自第一行，单元格不足的行以空单元格补齐，保证各行列数一致
+    pub fn render_table(&self, rows: &[Vec<Vec<RichText>>], width: usize, has_column_header: bool, has_row_header: bool) -> String {
+        let columns = rows.iter().map(|r| r.len()).max().unwrap_or(0).max(width);
+        let mut html = String::from("<table class=\"notion-table\">");
+        for (i, row) in rows.iter().enumerate() {
+            let header_row = i == 0 && has_column_header;
+            if header_row {
+                html.push_str("<thead>");
+            } else if i == 0 || (i == 1 && has_column_header) {
+                html.push_str("<tbody>");
+            }
+            html.push_str("<tr>");
+            for col in 0..columns {
+                let text = row.get(col).map(|cell| self.render_rich_text(cell)).unwrap_or_default();
+                let tag = if header_row || (col == 0 && has_row_header) { "th" } else { "td" };
+                html.push_str(&format!("<{tag}>{text}</{tag}>"));
+            }
+            html.push_str("</tr>");
+            if header_row {
+                html.push_str("</thead>");
             }
-            Block::Divider { .. } => "<hr style=\"border: none; border-top: 1px solid #eaeaea; margin: 2em 0;\" />".to_string(),
-            _ => format!("<!-- Unsupported block type -->"),
+        }
+        if rows.len() > usize::from(has_column_header) {
+            html.push_str("</tbody>");
+        }
+        html.push_str("</table>");
+        html
+    }
+
+    /// 带编号的行间公式，`id` 作为锚点供引用跳转
+    pub fn render_numbered_equation(&self, expression: &str, number: usize, id: &str) -> String {
+        format!(
+            "<div class=\"equation-block numbered\" id=\"{}\">{}<span class=\"equation-number\">({})</span></div>",
+            id, escape_math(expression), number
+        )
+    }
+
+    /// 标题 Block 的级别与纯文本；非标题返回 None
+    pub fn heading(&self, block: &Block) -> Option<(u8, String)> {
+        let (level, rich_text) = match block {
+            Block::Heading1 { heading_1 } => (1, &heading_1.rich_text),
+            Block::Heading2 { heading_2 } => (2, &heading_2.rich_text),
+            Block::Heading3 { heading_3 } => (3, &heading_3.rich_text),
+            _ => return None,
+        };
+        Some((level, rich_text.iter().map(|rt| rt.to_string()).collect()))
+    }
+
+    /// 列表项需要包裹的容器类型；非列表项返回 None
+    pub fn list_kind(&self, block: &Block) -> Option<ListKind> {
+        match block {
+            Block::BulletedListItem { .. } => Some(ListKind::Bulleted),
+            Block::NumberedListItem { .. } => Some(ListKind::Numbered),
+            Block::ToDo { .. } if self.options.todo_style == TodoStyle::Tasklist => Some(ListKind::Task),
+            _ => None,
         }
     }
 
-    pub fn render_rich_text(rich_texts: &[RichText]) -> String {
+    pub fn render_rich_text(&self, rich_texts: &[RichText]) -> String {
+        self.render_rich_text_with(rich_texts, true)
+    }
+
+    /// `line_breaks` 为 true 时，文本中的换行 (Shift + Enter) 转换为 `<br>`
+    fn render_rich_text_with(&self, rich_texts: &[RichText], line_breaks: bool) -> String {
         let mut html = String::new();
+        self.render_rich_text_into(&mut html, rich_texts, line_breaks);
+        html
+    }
+
+    /// 将富文本渲染到 `out` 的末尾。标签由外到内依次写出：颜色 `<span>`、链接、
+    /// 下划线、删除线、斜体、粗体 (或行内代码)，再逆序闭合
+    fn render_rich_text_into(&self, out: &mut String, rich_texts: &[RichText], line_breaks: bool) {
         for rt in rich_texts {
             match rt {
                 RichText::Text { text, annotations, .. } => {
-                    let mut content = text.content.clone();
-                    
-                    if annotations.bold {
-                        content = format!("<strong>{}</strong>", content);
+                    let color_class = Self::get_color_class(&annotations.color);
+                    if !color_class.is_empty() {
+                        let _ = write!(out, "<span class=\"{}\">", color_class);
                     }
-                    if annotations.italic {
-                        content = format!("<em>{}</em>", content);
+                    if let Some(link) = &text.link {
+                        let _ = write!(out, "<a href=\"{}\">", self.resolve_link(&link.url));
                     }
-                    if annotations.strikethrough {
-                        content = format!("<del>{}</del>", content);
+                    // 与 Notion 一致：行内代码忽略粗体、斜体、下划线与删除线，只保留颜色
+                    let tags: [&str; 4] = if annotations.code {
+                        ["code", "", "", ""]
+                    } else {
+                        [
+                            if annotations.underline { "u" } else { "" },
+                            if annotations.strikethrough { "del" } else { "" },
+                            if annotations.italic { "em" } else { "" },
+                            if annotations.bold { "strong" } else { "" },
+                        ]
+                    };
+                    for tag in tags.iter().filter(|t| !t.is_empty()) {
+                        let _ = write!(out, "<{}>", tag);
                     }
-                    if annotations.underline {
-                        content = format!("<u>{}</u>", content);
+                    escape_html_into(out, &text.content, line_breaks);
+                    for tag in tags.iter().rev().filter(|t| !t.is_empty()) {
+                        let _ = write!(out, "</{}>", tag);
                     }
-                    if annotations.code {
-                        content = format!("<code>{}</code>", content);
+                    if text.link.is_some() {
+                        out.push_str("</a>");
                     }
-                    
-                    // Handle Color
-                    let color_class = Self::get_color_class(&annotations.color);
                     if !color_class.is_empty() {
-                        content = format!("<span class=\"{}\">{}</span>", color_class, content);
+                        out.push_str("</span>");
                     }
-
-                    html.push_str(&content);
                 }
                 RichText::Equation { equation, .. } => {
-                    html.push_str(&format!("<span class=\"equation-inline\">{}</span>", equation.expression));
+                    let _ = write!(out, "<span class=\"equation-inline\">{}</span>", escape_math(&equation.expression));
+                }
+                RichText::Mention { mention, plain_text, .. } => {
+                    // 提及本次构建中的页面或数据库时链接到站内页面，其余 (含未知数据库) 退化为纯文本
+                    let target = match mention {
+                        Mention::Page { page } => self.page_url_by_id(&page.id.to_string()),
+                        Mention::Database { database } => self.page_url_by_id(&database.id.to_string()),
+                        _ => None,
+                    };
+                    match target {
+                        Some(url) => {
+                            let _ = write!(out, "<a class=\"mention\" href=\"{}\">", url);
+                            escape_html_into(out, plain_text, false);
+                            out.push_str("</a>");
+                        }
+                        None => escape_html_into(out, plain_text, false),
+                    }
                 }
-                _ => {} // Handle mentions if needed
             }
         }
-        html
+    }
+
+    fn write_image(out: &mut String, url: &str) {
+        let _ = write!(out, "<figure><img src=\"{}\"", url);
+        // 指定了显示宽度时按该宽度显示，但不超过容器宽度
+        match image_width(url) {
+            Some(width) => {
+                let _ = write!(out, " width=\"{}\" style=\"width: {}px; max-width: 100%; height: auto; border-radius: 5px;\"", width, width);
+            }
+            None => out.push_str(" style=\"max-width: 100%; border-radius: 5px;\""),
+        }
+        out.push_str(" /><figcaption></figcaption></figure>");
+    }
+
+    fn write_file_link(out: &mut String, url: &str, name: &str) {
+        let _ = write!(out, "<div class=\"file-block\"><a href=\"{}\" target=\"_blank\" class=\"file-link\">📎 {}</a></div>", url, name);
+    }
+
+    /// 按探测到的 Content-Type 渲染地址没有扩展名的文件 Block：图片渲染为 `<img>`，
+    /// 其余渲染为下载链接，文件名补上与类型对应的扩展名
+    pub fn render_file_with_type(&self, url: &str, content_type: &str) -> String {
+        let mut out = String::new();
+        let mime = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+        if mime.starts_with("image/") {
+            Self::write_image(&mut out, url);
+            return out;
+        }
+        let path = url.split(['?', '#']).next().unwrap_or(url);
+        let stem = path.rsplit('/').next().filter(|s| !s.is_empty()).unwrap_or("download");
+        let name = match mime_extension(&mime) {
+            Some(ext) => format!("{}.{}", stem, ext),
+            None => stem.to_string(),
+        };
+        Self::write_file_link(&mut out, url, &escape_html(&name));
+        out
+    }
+
+    /// 书签样式的链接卡片
+    fn write_link_card(out: &mut String, url: &str, class: &str) {
+        let _ = write!(
+            out,
+            "<a href=\"{}\" class=\"{}\" target=\"_blank\" style=\"display: block; border: 1px solid #ddd; padding: 12px; border-radius: 4px; margin: 10px 0; text-decoration: none; color: inherit;\">
+                <div style=\"font-weight: bold;\">{}</div>
+                <div style=\"font-size: 0.9em; color: #666; overflow: hidden; white-space: nowrap; text-overflow: ellipsis;\">{}</div>
+            </a>",
+            url, class, url, url
+        );
+    }
+
+    /// 「链接到页面」卡片：`target_id` 为目标页面 (或数据库) 的 ID。目标在本次构建中时
+    /// 链接到站内页面并显示其标题与图标，否则链接到 Notion 中的原页面
+    pub fn render_link_to_page(&self, target_id: &str) -> String {
+        let id = target_id.replace('-', "").to_lowercase();
+        let (url, title, icon, class) = match self.pages.get(&id) {
+            Some(page) => (page.url.clone(), escape_html(&page.title), page.icon.clone(), "link-to-page"),
+            None => (format!("https://www.notion.so/{}", id), "在 Notion 中打开".to_string(), None, "link-to-page external"),
+        };
+        let icon = icon.map_or_else(|| "📄".to_string(), |icon| icon.to_html("link-to-page-icon-img"));
+        format!(
+            "<a href=\"{}\" class=\"{}\"><span class=\"link-to-page-icon\">{}</span><span class=\"link-to-page-title\">{}</span></a>",
+            escape_html(&url), class, icon, title
+        )
+    }
+
+    /// 检查嵌入地址的域名是否在白名单中 (子域名同样匹配)
+    fn is_embed_allowed(&self, url: &str) -> bool {
+        let Some(allowed) = &self.options.embed_allowed_hosts else {
+            return true;
+        };
+        let Some(host) = url_host(url) else {
+            return false;
+        };
+        allowed.iter().any(|a| {
+            let a = a.trim().to_lowercase();
+            host == a || host.ends_with(&format!(".{}", a))
+        })
+    }
+
+    /// Notion 颜色的原始名称 (如 `default`、`blue_background`)
+    fn color_name(color: &Color) -> String {
+        serde_json::to_value(color)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_else(|| "default".to_string())
     }
 
     fn get_color_class(color: &Color) -> String {
@@ -173,3 +580,524 @@ impl HtmlRenderer {
         }
     }
 }
+
+/// 转义 HTML 特殊字符并写入 `out`，与 `escape_html` 结果一致；`line_breaks` 时换行输出为 `<br>`
+fn escape_html_into(out: &mut String, s: &str, line_breaks: bool) {
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\n' if line_breaks => out.push_str("<br>"),
+            c => out.push(c),
+        }
+    }
+}
+
+/// 不支持的 Block 渲染为以此开头的注释，后接类型名
+pub const UNSUPPORTED_BLOCK: &str = "<!-- Unsupported block type";
+
+/// Block 的类型名 (如 `Template`、`ChildDatabase`)，用于提示信息
+pub fn block_type_name(block: &Block) -> String {
+    let debug = format!("{:?}", block);
+    debug.split([' ', '{', '(']).next().unwrap_or_default().to_string()
+}
+
+/// 图片的显示宽度 (像素)。Notion API 不返回图片在页面中被拖拽调整后的宽度，
+/// 因此以地址末尾的 `#width=480` 片段指定；片段不会发送给服务器，不影响图片加载
+pub fn image_width(url: &str) -> Option<u32> {
+    let (_, fragment) = url.split_once('#')?;
+    fragment
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("width="))
+        .and_then(|w| w.trim_end_matches("px").parse().ok())
+        .filter(|w| *w > 0)
+}
+
+/// 转义 HTML 特殊字符
+pub fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// 地址路径最后一段是否带有扩展名 (如 `a.pdf`)
+pub fn url_has_extension(url: &str) -> bool {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let last = path.rsplit('/').next().unwrap_or("");
+    last.rsplit_once('.').is_some_and(|(stem, ext)| !stem.is_empty() && (1..=5).contains(&ext.len()))
+}
+
+/// 常见 MIME 类型对应的扩展名
+fn mime_extension(mime: &str) -> Option<&'static str> {
+    Some(match mime {
+        "application/pdf" => "pdf",
+        "application/zip" => "zip",
+        "application/json" => "json",
+        "text/plain" => "txt",
+        "text/csv" => "csv",
+        "audio/mpeg" => "mp3",
+        "audio/wav" | "audio/x-wav" => "wav",
+        "video/mp4" => "mp4",
+        "video/webm" => "webm",
+        "application/msword" => "doc",
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => "docx",
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" => "xlsx",
+        "application/vnd.openxmlformats-officedocument.presentationml.presentation" => "pptx",
+        _ => return None,
+    })
+}
+
+/// 富文本的纯文本内容 (忽略格式与链接)。与 `file_title` 一样从序列化结果中读取 `plain_text`
+fn plain_text(rich_texts: &[RichText]) -> String {
+    let value = serde_json::to_value(rich_texts).unwrap_or_default();
+    value
+        .as_array()
+        .map(|parts| parts.iter().filter_map(|p| p["plain_text"].as_str()).collect())
+        .unwrap_or_default()
+}
+
+/// 代码块的 Caption 是否像文件名 (如 `main.rs`、`src/lib.rs`、`Dockerfile`)：
+/// 不含空白，且带有扩展名或是常见的无扩展名文件
+fn looks_like_filename(caption: &str) -> bool {
+    const BARE_NAMES: [&str; 4] = ["Dockerfile", "Makefile", "Gemfile", "Procfile"];
+    let caption = caption.trim();
+    if caption.is_empty() || caption.len() > 100 || caption.contains(char::is_whitespace) {
+        return false;
+    }
+    let name = caption.rsplit('/').next().unwrap_or(caption);
+    BARE_NAMES.contains(&name)
+        || name.rsplit_once('.').is_some_and(|(_, ext)| {
+            (1..=10).contains(&ext.len()) && ext.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+}
+
+/// 文件类 Block (如音频) 的显示名称：依次取说明文字、文件名，都没有时取地址的最后一段。
+/// notionrs 的文件类型按 Notion API 的格式序列化，这里从序列化结果中读取
+fn file_title(file: &impl serde::Serialize, url: &str) -> String {
+    let value = serde_json::to_value(file).unwrap_or_default();
+    let caption: String = value["caption"]
+        .as_array()
+        .map(|parts| parts.iter().filter_map(|p| p["plain_text"].as_str()).collect())
+        .unwrap_or_default();
+    if !caption.trim().is_empty() {
+        return caption.trim().to_string();
+    }
+    if let Some(name) = value["name"].as_str().filter(|n| !n.trim().is_empty()) {
+        return name.trim().to_string();
+    }
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    path.rsplit('/').next().filter(|n| !n.is_empty()).unwrap_or("audio").to_string()
+}
+
+/// 转义公式中的 HTML 特殊字符 (`<`、`&` 等)，TeX 控制序列原样保留。
+/// 行间公式与行内公式共用；KaTeX 读取元素的文本内容，得到的仍是原始公式
+pub fn escape_math(expression: &str) -> String {
+    escape_html(expression)
+}
+
+/// 从 Notion 链接中提取页面 ID (32 位十六进制，无连字符)。
+/// 支持 `https://www.notion.so/Title-<id>`、`https://xxx.notion.site/<id>` 以及工作区内的 `/<id>` 链接
+pub fn extract_notion_id(url: &str) -> Option<String> {
+    let is_notion = url.starts_with('/')
+        || url_host(url).is_some_and(|h| h.ends_with("notion.so") || h.ends_with("notion.site"));
+    if !is_notion {
+        return None;
+    }
+    let path = url.split(['?', '#']).next()?;
+    let last = path.trim_end_matches('/').rsplit('/').next()?;
+    let clean = last.replace('-', "");
+    if clean.len() < 32 {
+        return None;
+    }
+    let id = &clean[clean.len() - 32..];
+    if id.chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(id.to_lowercase())
+    } else {
+        None
+    }
+}
+
+/// 提取 URL 中的域名 (小写，不含端口)
+fn url_host(url: &str) -> Option<String> {
+    let rest = url.split_once("://").map(|(_, r)| r)?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?.split(':').next()?;
+    if host.is_empty() {
+        None
+    } else {
+        Some(host.to_lowercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{block, text};
+    use serde_json::json;
+
+    fn renderer() -> HtmlRenderer {
+        HtmlRenderer::new(RenderOptions::default())
+    }
+
+    fn callout(icon: serde_json::Value) -> Block {
+        block(json!({
+            "type": "callout",
+            "callout": { "rich_text": [text("Note")], "icon": icon, "color": "blue_background" }
+        }))
+    }
+
+    #[test]
+    fn callout_file_icon_renders_as_image() {
+        let html = renderer().render_block(&callout(json!({
+            "type": "external",
+            "external": { "url": "https://example.com/icon.png" }
+        })));
+        assert!(html.contains("<img class=\"callout-icon\" src=\"https://example.com/icon.png\""), "{}", html);
+    }
+
+    #[test]
+    fn callout_emoji_icon_renders_as_text() {
+        let html = renderer().render_block(&callout(json!({ "type": "emoji", "emoji": "🔥" })));
+        assert!(html.contains("<span class=\"callout-icon\" style=\"margin-right: 10px;\">🔥</span>"), "{}", html);
+    }
+
+    #[test]
+    fn code_caption_renders_after_pre() {
+        let html = renderer().render_block(&block(json!({
+            "type": "code",
+            "code": { "rich_text": [text("fn main() {}")], "caption": [text("Entry point")], "language": "rust" }
+        })));
+        let pre = html.find("</pre>").expect(&html);
+        let caption = html.find("<figcaption class=\"code-caption\">Entry point</figcaption>").expect(&html);
+        assert!(caption > pre, "{}", html);
+    }
+
+    fn embed(url: &str) -> Block {
+        block(json!({ "type": "embed", "embed": { "url": url, "caption": [] } }))
+    }
+
+    #[test]
+    fn embed_from_unlisted_host_renders_as_link_card() {
+        let renderer = HtmlRenderer::new(RenderOptions {
+            embed_allowed_hosts: Some(vec!["youtube.com".to_string()]),
+            ..Default::default()
+        });
+        let blocked = renderer.render_block(&embed("https://evil.example/widget"));
+        assert!(!blocked.contains("<iframe"), "{}", blocked);
+        assert!(blocked.contains("href=\"https://evil.example/widget\""), "{}", blocked);
+
+        let allowed = renderer.render_block(&embed("https://www.youtube.com/embed/abc"));
+        assert!(allowed.contains("<iframe src=\"https://www.youtube.com/embed/abc\""), "{}", allowed);
+    }
+
+    #[test]
+    fn newlines_become_br_outside_code() {
+        let paragraph = renderer().render_block(&block(json!({
+            "type": "paragraph",
+            "paragraph": { "rich_text": [text("line 1\nline <2>")], "color": "default" }
+        })));
+        assert!(paragraph.contains("line 1<br>line &lt;2&gt;"), "{}", paragraph);
+
+        let code = renderer().render_block(&block(json!({
+            "type": "code",
+            "code": { "rich_text": [text("a\nb")], "caption": [], "language": "plain text" }
+        })));
+        assert!(code.contains("a\nb") && !code.contains("<br>"), "{}", code);
+    }
+
+    #[test]
+    fn tasklist_style_renders_task_list_items() {
+        let todo = block(json!({
+            "type": "to_do",
+            "to_do": { "rich_text": [text("Ship it")], "checked": true, "color": "default" }
+        }));
+        let tasklist = HtmlRenderer::new(RenderOptions { todo_style: TodoStyle::Tasklist, ..Default::default() });
+        let html = tasklist.render_block(&todo);
+        assert!(html.starts_with("<li class=\"task-list-item\">"), "{}", html);
+        assert!(html.contains("checked disabled") && !html.contains("display: flex"), "{}", html);
+        assert_eq!(tasklist.list_kind(&todo), Some(ListKind::Task));
+
+        let inline = renderer().render_block(&todo);
+        assert!(inline.contains("class=\"todo-item\""), "{}", inline);
+    }
+
+    const PAGE_ID: &str = "0123456789abcdef0123456789abcdef";
+
+    fn page_link(url: &str) -> PageLink {
+        PageLink { url: url.to_string(), title: "Page".to_string(), icon: None }
+    }
+
+    #[test]
+    fn notion_links_to_built_pages_become_site_relative() {
+        let renderer = renderer().with_pages(HashMap::from([(PAGE_ID.to_string(), page_link("./my-post.html"))]));
+        let notion_url = format!("https://www.notion.so/workspace/My-Post-{}?pvs=4", PAGE_ID);
+        let bookmark = renderer.render_block(&block(json!({
+            "type": "bookmark",
+            "bookmark": { "url": notion_url, "caption": [] }
+        })));
+        assert!(bookmark.contains("href=\"./my-post.html\""), "{}", bookmark);
+
+        let mut link = text("see");
+        link["text"]["link"] = json!({ "url": format!("/{}", PAGE_ID) });
+        let paragraph = renderer.render_block(&block(json!({
+            "type": "paragraph",
+            "paragraph": { "rich_text": [link], "color": "default" }
+        })));
+        assert!(paragraph.contains("<a href=\"./my-post.html\">see</a>"), "{}", paragraph);
+
+        let external = "https://www.notion.so/Other-ffffffffffffffffffffffffffffffff";
+        assert_eq!(renderer.resolve_link(external), external);
+    }
+
+    #[test]
+    fn link_preview_renders_as_card() {
+        let html = renderer().render_block(&block(json!({
+            "type": "link_preview",
+            "link_preview": { "url": "https://github.com/rust-lang/rust/pull/1" }
+        })));
+        assert!(html.contains("class=\"bookmark link-preview\""), "{}", html);
+        assert!(html.contains("href=\"https://github.com/rust-lang/rust/pull/1\""), "{}", html);
+    }
+
+    #[test]
+    fn divider_uses_a_class_instead_of_inline_style() {
+        let renderer = HtmlRenderer::new(RenderOptions::default());
+        let html = renderer.render_block(&block(json!({ "type": "divider", "divider": {} })));
+        assert_eq!(html, "<hr class=\"divider\" />");
+    }
+
+    #[test]
+    fn empty_paragraph_is_kept_as_spacing() {
+        let empty = block(json!({ "type": "paragraph", "paragraph": { "rich_text": [], "color": "default" } }));
+        assert_eq!(HtmlRenderer::new(RenderOptions::default()).render_block(&empty), "<div class=\"spacer\"></div>");
+    }
+
+    fn annotated(content: &str, annotations: serde_json::Value) -> RichText {
+        let mut value = text(content);
+        value["annotations"] = annotations;
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn inline_code_ignores_emphasis_but_keeps_color() {
+        let renderer = HtmlRenderer::new(RenderOptions::default());
+        let bold_code = annotated("x", json!({
+            "bold": true, "italic": true, "strikethrough": false, "underline": true, "code": true, "color": "default"
+        }));
+        assert_eq!(renderer.render_rich_text(&[bold_code]), "<code>x</code>");
+
+        let red_code = annotated("y", json!({
+            "bold": false, "italic": false, "strikethrough": false, "underline": false, "code": true, "color": "red"
+        }));
+        let html = renderer.render_rich_text(&[red_code]);
+        assert_eq!(html, format!("<span class=\"{}\"><code>y</code></span>", HtmlRenderer::get_color_class(&Color::Red)));
+    }
+
+    fn database_mention(id: &str, title: &str) -> serde_json::Value {
+        let mut value = text(title);
+        value["type"] = json!("mention");
+        value["mention"] = json!({ "type": "database", "database": { "id": id } });
+        value.as_object_mut().unwrap().remove("text");
+        value
+    }
+
+    #[test]
+    fn database_mentions_link_to_built_sections() {
+        let database_id = "fedcba98-7654-3210-fedc-ba9876543210";
+        let renderer = HtmlRenderer::new(RenderOptions::default())
+            .with_pages(HashMap::from([(database_id.replace('-', ""), page_link("./index.html"))]));
+        let paragraph = |mention: serde_json::Value| {
+            renderer.render_block(&block(json!({
+                "type": "paragraph",
+                "paragraph": { "rich_text": [mention], "color": "default" }
+            })))
+        };
+
+        let known = paragraph(database_mention(database_id, "Blog"));
+        assert!(known.contains("<a class=\"mention\" href=\"./index.html\">Blog</a>"), "{}", known);
+        let unknown = paragraph(database_mention("00000000-0000-0000-0000-000000000000", "Elsewhere"));
+        assert!(unknown.contains("Elsewhere") && !unknown.contains("<a"), "{}", unknown);
+    }
+
+    #[test]
+    fn callout_exposes_color_icon_and_text_containers() {
+        let renderer = HtmlRenderer::new(RenderOptions::default());
+        let html = renderer.render_block(&callout(json!({ "type": "emoji", "emoji": "⚠️" })));
+        let class = HtmlRenderer::get_color_class(&Color::BlueBackground);
+        assert!(html.starts_with(&format!("<div class=\"callout {}\" data-callout-color=\"blue_background\">", class)), "{}", html);
+        assert!(html.contains("<span class=\"callout-icon\" style=\"margin-right: 10px;\">⚠️</span>"), "{}", html);
+        assert!(html.contains("<div class=\"callout-text\">Note</div>"), "{}", html);
+    }
+
+    #[test]
+    fn long_code_blocks_collapse_into_details() {
+        let config = crate::test_support::config(json!({ "codeCollapseLines": 30 }));
+        let renderer = HtmlRenderer::new(config.render_options());
+        let code = |source: String| {
+            renderer.render_block(&block(json!({
+                "type": "code",
+                "code": { "rich_text": [text(&source)], "caption": [], "language": "rust" }
+            })))
+        };
+        let long: Vec<String> = (1..=200).map(|i| format!("let x{} = {};", i, i)).collect();
+        let html = code(long.join("\n"));
+        assert!(html.contains("<details class=\"code-collapse\"><summary>显示更多 (200 行)</summary><pre>"), "{}", html);
+        assert!(html.contains("let x200 = 200;"), "完整代码应保留在 HTML 中");
+
+        let short = code("fn main() {}".to_string());
+        assert!(!short.contains("<details"), "{}", short);
+    }
+
+    #[test]
+    fn every_icon_variant_goes_through_render_icon() {
+        let icon = |value: serde_json::Value| render_icon(&Some(serde_json::from_value::<Icon>(value).unwrap()));
+        assert_eq!(icon(json!({ "type": "emoji", "emoji": "📝" })), Some(IconHtml::Emoji("📝".to_string())));
+        assert_eq!(
+            icon(json!({ "type": "external", "external": { "url": "https://example.com/a.png" } })),
+            Some(IconHtml::Image("https://example.com/a.png".to_string()))
+        );
+        assert_eq!(
+            icon(json!({ "type": "file", "file": { "url": "https://files.example.com/b.png", "expiry_time": "2024-01-01T01:00:00.000Z" } })),
+            Some(IconHtml::Image("https://files.example.com/b.png".to_string()))
+        );
+        assert_eq!(
+            icon(json!({ "type": "custom_emoji", "custom_emoji": { "id": "c1", "name": "party", "url": "https://example.com/party.png" } })),
+            Some(IconHtml::Image("https://example.com/party.png".to_string()))
+        );
+        assert_eq!(render_icon(&None), None);
+
+        assert_eq!(IconHtml::Emoji("📝".to_string()).to_html("page-icon"), "📝");
+        let img = IconHtml::Image("https://example.com/a.png?x=1&y=2".to_string()).to_html("page-icon");
+        assert!(img.starts_with("<img class=\"page-icon\" src=\"https://example.com/a.png?x=1&amp;y=2\""), "{}", img);
+    }
+
+    #[test]
+    fn equations_escape_html_but_keep_tex() {
+        let renderer = HtmlRenderer::new(RenderOptions::default());
+        let expression = r"a < b \land c \& d";
+        let html = renderer.render_block(&block(json!({ "type": "equation", "equation": { "expression": expression } })));
+        assert!(html.contains(r"a &lt; b \land c \&amp; d"), "{}", html);
+        assert!(!html.contains("a < b"), "{}", html);
+
+        let mut inline = text(expression);
+        inline["type"] = json!("equation");
+        inline["equation"] = json!({ "expression": expression });
+        inline.as_object_mut().unwrap().remove("text");
+        let rich_text: Vec<RichText> = serde_json::from_value(json!([inline])).unwrap();
+        let html = renderer.render_rich_text(&rich_text);
+        assert_eq!(html, r#"<span class="equation-inline">a &lt; b \land c \&amp; d</span>"#);
+    }
+
+    #[test]
+    fn image_width_is_capped_responsively() {
+        let renderer = HtmlRenderer::new(RenderOptions::default());
+        let image = |url: &str| block(json!({
+            "type": "image",
+            "image": { "type": "external", "external": { "url": url }, "caption": [] }
+        }));
+        let html = renderer.render_block(&image("https://example.com/a.png#width=480"));
+        assert!(html.contains("width=\"480\" style=\"width: 480px; max-width: 100%; height: auto;"), "{}", html);
+
+        let html = renderer.render_block(&image("https://example.com/a.png"));
+        assert!(html.contains("style=\"max-width: 100%;"), "{}", html);
+        assert!(!html.contains("width=\""), "{}", html);
+    }
+
+    /// 覆盖各类 Block 的测试数据，与基准测试共用
+    const FIXTURE_BLOCKS: &str = include_str!("../benches/fixtures/blocks.json");
+
+    fn fixture_options() -> RenderOptions {
+        RenderOptions {
+            embed_allowed_hosts: Some(vec!["youtube.com".to_string()]),
+            todo_style: TodoStyle::Tasklist,
+            empty_paragraph: EmptyParagraph::Br,
+            reader_mode: true,
+            code_collapse_lines: Some(3),
+            code_copy_button: true,
+        }
+    }
+
+    #[test]
+    fn render_block_into_matches_the_string_path() {
+        let blocks: Vec<Block> = serde_json::from_str(FIXTURE_BLOCKS).unwrap();
+        // 期望的 HTML 由改为写入缓冲区之前 (各 Block 返回 String) 的实现生成，每个 Block 一行
+        let cases = [
+            (RenderOptions::default(), include_str!("../benches/fixtures/blocks.html")),
+            (fixture_options(), include_str!("../benches/fixtures/blocks-options.html")),
+        ];
+        for (options, expected) in cases {
+            let renderer = HtmlRenderer::new(options);
+            // 所有 Block 写入同一个缓冲区，结果应与逐个渲染再拼接一致
+            let mut out = String::new();
+            for block in &blocks {
+                renderer.render_block_into(&mut out, block);
+                out.push('\n');
+            }
+            assert_eq!(out, expected);
+            let joined: String = blocks.iter().map(|b| renderer.render_block(b) + "\n").collect();
+            assert_eq!(joined, expected);
+        }
+    }
+
+    #[test]
+    fn empty_paragraphs_render_the_configured_spacer() {
+        let empty = block(json!({ "type": "paragraph", "paragraph": { "rich_text": [], "color": "default" } }));
+        let cases = [
+            (EmptyParagraph::Spacer, "<div class=\"spacer\"></div>"),
+            (EmptyParagraph::Paragraph, "<p class=\"empty-paragraph\"></p>"),
+            (EmptyParagraph::Br, "<br>"),
+        ];
+        for (empty_paragraph, expected) in cases {
+            let renderer = HtmlRenderer::new(RenderOptions { empty_paragraph, ..RenderOptions::default() });
+            assert_eq!(renderer.render_block(&empty), expected);
+        }
+    }
+
+    #[test]
+    fn audio_renders_player_and_download_link_with_track_name() {
+        let renderer = HtmlRenderer::new(RenderOptions::default());
+        let html = renderer.render_block(&block(json!({
+            "type": "audio",
+            "audio": {
+                "type": "external",
+                "external": { "url": "https://example.com/media/track.ogg" },
+                "caption": [text("Episode 1 & 2")]
+            }
+        })));
+        assert!(html.contains("<audio controls preload=\"metadata\" src=\"https://example.com/media/track.ogg\""), "{}", html);
+        assert!(
+            html.contains("<a class=\"audio-download\" href=\"https://example.com/media/track.ogg\" download>⬇ Episode 1 &amp; 2</a>"),
+            "{}",
+            html
+        );
+
+        let untitled = renderer.render_block(&block(json!({
+            "type": "audio",
+            "audio": { "type": "external", "external": { "url": "https://example.com/media/track.ogg?v=1" }, "caption": [] }
+        })));
+        assert!(untitled.contains("download>⬇ track.ogg</a>"), "{}", untitled);
+    }
+
+    #[test]
+    fn code_filename_caption_renders_header_and_copy_button() {
+        let code = block(json!({
+            "type": "code",
+            "code": { "rich_text": [text("if a < b && c > \"d\" {}")], "caption": [text("main.rs")], "language": "rust" }
+        }));
+        let renderer = HtmlRenderer::new(RenderOptions { code_copy_button: true, ..Default::default() });
+        let html = renderer.render_block(&code);
+        assert!(html.contains("<span class=\"code-filename\">main.rs</span><span class=\"code-language\">rust</span>"), "{}", html);
+        assert!(
+            html.contains("<button type=\"button\" class=\"code-copy\" data-code=\"if a &lt; b &amp;&amp; c &gt; &quot;d&quot; {}\">"),
+            "{}",
+            html
+        );
+        assert!(!html.contains("code-caption"), "{}", html);
+
+        let html = HtmlRenderer::new(RenderOptions::default()).render_block(&code);
+        assert!(html.contains("code-filename") && !html.contains("code-copy"), "{}", html);
+    }
+}
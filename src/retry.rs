@@ -0,0 +1,128 @@
+use anyhow::Result;
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+
+/// 最大尝试次数 (含第一次)
+const MAX_ATTEMPTS: u32 = 4;
+/// 首次重试前的等待时间，之后每次翻倍
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// 带指数退避的重试。
+/// 网络抖动、限流、5xx 等暂时性错误会重试；
+/// 鉴权失败、数据库未共享、找不到对象等确定性错误立即返回，并附带可读的排查提示。
+pub async fn with_retry<T, E, F, Fut>(label: &str, mut f: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = std::result::Result<T, E>>,
+    E: Display,
+{
+    let mut delay = INITIAL_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        match f().await {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                let message = e.to_string();
+                if let Some(hint) = describe_notion_error(&message) {
+                    return Err(anyhow::anyhow!("{}失败: {}\n提示: {}", label, message, hint));
+                }
+                if attempt >= MAX_ATTEMPTS {
+                    return Err(anyhow::anyhow!("{}失败 (已尝试 {} 次): {}", label, attempt, message));
+                }
+                println!(">>> {}失败，{}ms 后重试 ({}/{}): {}", label, delay.as_millis(), attempt, MAX_ATTEMPTS, message);
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// 根据 Notion 返回的错误码给出针对性的提示；暂时性错误返回 None
+pub fn describe_notion_error(message: &str) -> Option<&'static str> {
+    if message.contains("unauthorized") {
+        Some("Notion Token 无效，请检查 notionToken 或环境变量 NOTION_TOKEN")
+    } else if message.contains("restricted_resource") {
+        Some("Integration 无权访问该数据库，请在 Notion 中将数据库 Share 给你的 Integration")
+    } else if message.contains("object_not_found") {
+        Some("找不到数据库，请检查 URL 是否正确；若 URL 无误，通常是数据库尚未 Share 给 Integration")
+    } else if message.contains("validation_error") {
+        Some("请求参数无效，请检查数据库属性名称与类型是否与模板一致")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notion::NotionApi;
+    use crate::test_support::MockServer;
+    use notionrs_types::prelude::Filter;
+    use std::cell::Cell;
+
+    #[tokio::test]
+    async fn permanent_errors_fail_immediately_with_hint() {
+        let calls = Cell::new(0);
+        let result: Result<()> = with_retry("查询", || {
+            calls.set(calls.get() + 1);
+            async { Err("API error: unauthorized") }
+        })
+        .await;
+        let message = result.unwrap_err().to_string();
+        assert_eq!(calls.get(), 1);
+        assert!(message.contains("Notion Token 无效"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn transient_errors_are_retried() {
+        let calls = Cell::new(0);
+        let result = with_retry("查询", || {
+            calls.set(calls.get() + 1);
+            let attempt = calls.get();
+            async move { if attempt < 2 { Err("502 Bad Gateway") } else { Ok(attempt) } }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 2);
+    }
+
+    /// 让模拟的 Notion 服务返回错误响应，经重试包装后取回错误信息
+    async fn query_error(status: &'static str, code: &str, message: &str) -> (String, usize) {
+        let body = serde_json::json!({ "object": "error", "code": code, "message": message }).to_string();
+        let server = MockServer::error(status, "application/json", body.into_bytes()).await;
+        let api = NotionApi::with_base(&server.url, "secret_test");
+        let filter = Filter::timestamp_is_not_empty();
+        let calls = Cell::new(0);
+        let result = with_retry("查询 Notion 数据库", || {
+            calls.set(calls.get() + 1);
+            api.query_data_source::<serde_json::Value>("db", &filter, &[], None)
+        })
+        .await;
+        (result.unwrap_err().to_string(), calls.get())
+    }
+
+    #[tokio::test]
+    async fn unauthorized_response_points_at_the_token() {
+        let (message, calls) = query_error("401 Unauthorized", "unauthorized", "API token is invalid.").await;
+        assert_eq!(calls, 1);
+        assert!(message.contains("HTTP 401"), "{}", message);
+        assert!(message.contains("Notion Token 无效"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn not_found_response_suggests_checking_url_and_sharing() {
+        let (message, calls) = query_error("404 Not Found", "object_not_found", "Could not find data_source with ID: db.").await;
+        assert_eq!(calls, 1);
+        assert!(message.contains("找不到数据库"), "{}", message);
+        assert!(message.contains("尚未 Share 给 Integration"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn restricted_response_asks_to_share_the_database() {
+        let (message, calls) = query_error("403 Forbidden", "restricted_resource", "Insufficient permissions.").await;
+        assert_eq!(calls, 1);
+        assert!(message.contains("无权访问该数据库"), "{}", message);
+        assert!(!message.contains("找不到数据库"), "{}", message);
+    }
+}
@@ -0,0 +1,139 @@
+use crate::notion::NotionApi;
+use crate::retry;
+use anyhow::Result;
+use notionrs_types::prelude::Icon;
+use std::collections::BTreeMap;
+
+/// `MyProperties` 依赖的属性：(名称, Notion 类型, 是否必需)
+const EXPECTED_PROPERTIES: &[(&str, &str, bool)] = &[
+    ("title", "title", true),
+    ("tags", "multi_select", true),
+    ("template", "select", true),
+    ("publish", "checkbox", true),
+    ("inMenu", "checkbox", true),
+    ("inList", "checkbox", true),
+    ("date", "date", true),
+    ("series", "select", false),
+    ("head", "rich_text", false),
+    ("coverFocus", "rich_text", false),
+    ("publishWindow", "date", false),
+    ("language", "select", false),
+    ("translationKey", "rich_text", false),
+    ("hideTitle", "checkbox", false),
+    ("hideMeta", "checkbox", false),
+    ("inFeed", "checkbox", false),
+    ("order", "number", false),
+];
+
+/// 获取数据库的属性定义 (属性名 -> 类型)，在查询之前校验属性名与类型，
+/// 让属性不匹配时给出明确的提示，而不是反序列化失败。校验通过时返回数据库的图标
+pub async fn validate(client: &NotionApi, data_source_id: &str) -> Result<Option<Icon>> {
+    let data_source = retry::with_retry("获取数据库结构", || client.retrieve_data_source(data_source_id)).await?;
+    ensure_properties(&data_source["properties"])?;
+    Ok(serde_json::from_value(data_source["icon"].clone()).unwrap_or_default())
+}
+
+/// 校验 Notion 返回的原始属性定义 (属性名 -> 含 `type` 的对象)
+fn ensure_properties(properties: &serde_json::Value) -> Result<()> {
+    let schema: BTreeMap<String, String> = properties
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(name, property)| {
+            let kind = property.get("type").and_then(|t| t.as_str()).unwrap_or("unknown");
+            (name.clone(), kind.to_string())
+        })
+        .collect();
+    ensure_matches(&schema)
+}
+
+/// 属性不一致时返回错误，列出每个问题与数据库中现有的属性
+fn ensure_matches(schema: &BTreeMap<String, String>) -> Result<()> {
+    let problems = check(schema);
+    if problems.is_empty() {
+        return Ok(());
+    }
+    let available: Vec<String> = schema.iter().map(|(name, kind)| format!("{} ({})", name, kind)).collect();
+    anyhow::bail!(
+        "数据库属性与模板不一致:\n  - {}\n数据库中现有的属性: {}",
+        problems.join("\n  - "),
+        available.join(", ")
+    )
+}
+
+/// 逐个检查期望的属性，返回问题描述 (为空表示通过)
+fn check(schema: &BTreeMap<String, String>) -> Vec<String> {
+    let mut problems = Vec::new();
+    for &(name, kind, required) in EXPECTED_PROPERTIES {
+        match schema.get(name) {
+            Some(actual) if actual == kind => {}
+            Some(actual) => problems.push(format!("属性 {} 的类型应为 {}，实际为 {}", name, kind, actual)),
+            None if required => {
+                let mut problem = format!("缺少 {} 类型的属性 {}", kind, name);
+                if let Some(candidate) = suggest(schema, name, kind) {
+                    problem.push_str(&format!("，是否应将 {} 重命名为 {}？", candidate, name));
+                }
+                problems.push(problem);
+            }
+            None => {}
+        }
+    }
+    problems
+}
+
+/// 推荐重命名的候选：忽略大小写与分隔符后同名，且类型相同的属性
+fn suggest<'a>(schema: &'a BTreeMap<String, String>, name: &str, kind: &str) -> Option<&'a str> {
+    let normalize = |s: &str| s.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase();
+    let target = normalize(name);
+    schema
+        .iter()
+        .find(|(candidate, actual)| actual.as_str() == kind && normalize(candidate) == target)
+        .map(|(candidate, _)| candidate.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::Site;
+    use crate::test_support::{self, MockServer};
+    use serde_json::json;
+
+    fn schema(properties: &[(&str, &str)]) -> BTreeMap<String, String> {
+        properties.iter().map(|(name, kind)| (name.to_string(), kind.to_string())).collect()
+    }
+
+    #[test]
+    fn missing_property_lists_the_available_ones() {
+        let mut properties: Vec<(&str, &str)> = EXPECTED_PROPERTIES.iter()
+            .filter(|(_, _, required)| *required)
+            .map(|&(name, kind, _)| (name, kind))
+            .collect();
+        assert!(ensure_matches(&schema(&properties)).is_ok());
+
+        properties.retain(|(name, _)| *name != "inMenu");
+        properties.push(("In Menu", "checkbox"));
+        let error = ensure_matches(&schema(&properties)).unwrap_err().to_string();
+        assert!(error.contains("缺少 checkbox 类型的属性 inMenu"), "{}", error);
+        assert!(error.contains("是否应将 In Menu 重命名为 inMenu"), "{}", error);
+        assert!(error.contains("数据库中现有的属性: In Menu (checkbox), date (date)"), "{}", error);
+    }
+
+    #[test]
+    fn wrong_property_type_is_reported() {
+        let problems = check(&schema(&[("publish", "select")]));
+        assert!(problems.contains(&"属性 publish 的类型应为 checkbox，实际为 select".to_string()), "{:?}", problems);
+    }
+
+    #[tokio::test]
+    async fn build_stops_before_querying_when_a_property_is_missing() {
+        let data_source = json!({
+            "object": "data_source",
+            "properties": { "title": { "type": "title" }, "In Menu": { "type": "checkbox" } },
+        });
+        let server = MockServer::with_routes(vec![("/data_sources/", "application/json", data_source.to_string().into_bytes())]).await;
+
+        let error = Site::build_from(&server.url, &test_support::config(json!({}))).await.unwrap_err().to_string();
+        assert!(error.contains("是否应将 In Menu 重命名为 inMenu"), "{}", error);
+        assert!(server.requests().iter().all(|r| !r.contains("/query")), "{:?}", server.requests());
+    }
+}
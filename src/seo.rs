@@ -0,0 +1,238 @@
+use crate::config::{Config, HumansConfig, SecurityConfig};
+use crate::model::PostMetadata;
+use crate::renderer::escape_html;
+use crate::site::{today_utc, BuildResult};
+
+/// 生成 robots.txt；配置了 `siteUrl` 时附带 sitemap 的绝对地址
+pub fn robots_txt(config: &Config) -> String {
+    let mut s = String::from("User-agent: *\n");
+    for path in &config.robots.disallow {
+        s.push_str(&format!("Disallow: {}\n", path));
+    }
+    for path in &config.robots.allow {
+        s.push_str(&format!("Allow: {}\n", path));
+    }
+    if config.robots.disallow.is_empty() && config.robots.allow.is_empty() {
+        s.push_str("Allow: /\n");
+    }
+    if let Some(site_url) = config.site_url() {
+        s.push_str(&format!("\nSitemap: {}/sitemap.xml\n", site_url));
+    }
+    s
+}
+
+/// 生成 sitemap.xml：首页、文章页与标签页
+pub fn sitemap_xml(site_url: &str, result: &BuildResult) -> String {
+    let mut urls = vec![format!("{}/", site_url)];
+    for post in &result.posts {
+        urls.push(format!("{}/{}", site_url, post.meta.url));
+    }
+    for tag in &result.tags {
+        urls.push(format!("{}/{}", site_url, tag.url));
+    }
+
+    let mut s = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    s.push_str("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+    for url in urls {
+        s.push_str(&format!("  <url><loc>{}</loc></url>\n", escape_html(&url)));
+    }
+    s.push_str("</urlset>\n");
+    s
+}
+
+/// 文章的 BreadcrumbList 结构化数据 (JSON-LD)：首页 → 第一个标签的标签页 (有标签时) → 文章。
+/// 结果可直接放进 `<script type="application/ld+json">`
+pub fn breadcrumb_json_ld(site_url: &str, site_title: &str, post: &PostMetadata) -> String {
+    let mut trail = vec![(site_title.to_string(), format!("{}/", site_url))];
+    if let Some(tag) = post.tags.first() {
+        trail.push((tag.name.clone(), format!("{}/{}", site_url, tag.url)));
+    }
+    trail.push((post.title.clone(), format!("{}/{}", site_url, post.url)));
+
+    let items: Vec<serde_json::Value> = trail
+        .into_iter()
+        .enumerate()
+        .map(|(i, (name, item))| {
+            serde_json::json!({
+                "@type": "ListItem",
+                "position": i + 1,
+                "name": name,
+                "item": item,
+            })
+        })
+        .collect();
+    let json_ld = serde_json::json!({
+        "@context": "https://schema.org",
+        "@type": "BreadcrumbList",
+        "itemListElement": items,
+    });
+    // 标题中的 `</script>` 会提前结束脚本，转义 `</`
+    json_ld.to_string().replace("</", "<\\/")
+}
+
+/// 生成 humans.txt：TEAM / THANKS / SITE 三节，未配置的节省略；SITE 节附带最后更新日期
+pub fn humans_txt(humans: &HumansConfig) -> String {
+    let mut s = String::new();
+    let mut section = |name: &str, lines: &[String]| {
+        if !lines.is_empty() {
+            s.push_str(&format!("/* {} */\n", name));
+            for line in lines {
+                s.push_str(&format!("    {}\n", line));
+            }
+            s.push('\n');
+        }
+    };
+    section("TEAM", &humans.team);
+    section("THANKS", &humans.thanks);
+    let mut site = humans.site.clone();
+    site.push(format!("Last update: {}", today_utc().replace('-', "/")));
+    section("SITE", &site);
+    s
+}
+
+/// 生成 security.txt；没有任何联系方式时返回 None (RFC 9116 要求至少一个 `Contact`)
+pub fn security_txt(config: &Config, security: &SecurityConfig) -> Option<String> {
+    if security.contact.is_empty() {
+        return None;
+    }
+    let mut s = String::new();
+    for contact in &security.contact {
+        let contact = contact.trim();
+        if contact.contains('@') && !contact.contains(':') {
+            s.push_str(&format!("Contact: mailto:{}\n", contact));
+        } else {
+            s.push_str(&format!("Contact: {}\n", contact));
+        }
+    }
+    let expires = match &security.expires {
+        Some(date) if date.len() == 10 => format!("{}T00:00:00.000Z", date),
+        Some(time) => time.clone(),
+        None => {
+            // 一年后的同一天 (2 月 29 日顺延为 3 月 1 日)
+            let today = today_utc();
+            let year: u32 = today[..4].parse().unwrap_or(1970) + 1;
+            let rest = if &today[5..] == "02-29" { "03-01" } else { &today[5..] };
+            format!("{}-{}T00:00:00.000Z", year, rest)
+        }
+    };
+    s.push_str(&format!("Expires: {}\n", expires));
+    if let Some(encryption) = &security.encryption {
+        s.push_str(&format!("Encryption: {}\n", encryption));
+    }
+    if let Some(policy) = &security.policy {
+        s.push_str(&format!("Policy: {}\n", policy));
+    }
+    if let Some(languages) = &security.preferred_languages {
+        s.push_str(&format!("Preferred-Languages: {}\n", languages));
+    }
+    if let Some(site_url) = config.site_url() {
+        s.push_str(&format!("Canonical: {}/.well-known/security.txt\n", site_url));
+    }
+    Some(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use serde_json::json;
+
+    #[test]
+    fn robots_txt_references_the_sitemap_and_disallowed_paths() {
+        let config = test_support::config(json!({
+            "siteUrl": "https://blog.example.com/",
+            "robots": { "disallow": ["/drafts/"] }
+        }));
+        let robots = robots_txt(&config);
+        assert!(robots.contains("Disallow: /drafts/\n"), "{}", robots);
+        assert!(!robots.contains("Allow: /\n"), "{}", robots);
+        assert!(robots.ends_with("Sitemap: https://blog.example.com/sitemap.xml\n"), "{}", robots);
+
+        let default = robots_txt(&test_support::config(json!({})));
+        assert_eq!(default, "User-agent: *\nAllow: /\n");
+    }
+
+    #[test]
+    fn render_writes_robots_and_sitemap_only_with_a_site_url() {
+        let render = |config: &Config| {
+            let result = test_support::result(config, vec![test_support::post(test_support::meta("a", "2024-01-01"), "")]);
+            let mut output = crate::Output::new(true).with_contents();
+            crate::Site::render_to(config, &result, &mut output).unwrap();
+            output
+        };
+        let file = |output: &crate::Output, path: &str| {
+            output.contents(std::path::Path::new(path)).map(|c| String::from_utf8_lossy(c).into_owned())
+        };
+
+        let output = render(&test_support::config(json!({ "siteUrl": "https://blog.example.com" })));
+        let sitemap = file(&output, "public/sitemap.xml").unwrap();
+        assert!(sitemap.contains("<loc>https://blog.example.com/a.html</loc>"), "{}", sitemap);
+        assert!(file(&output, "public/robots.txt").unwrap().contains("Sitemap: https://blog.example.com/sitemap.xml"));
+
+        let output = render(&test_support::config(json!({ "robots": { "enabled": false } })));
+        assert!(file(&output, "public/sitemap.xml").is_none());
+        assert!(file(&output, "public/robots.txt").is_none());
+    }
+
+    #[test]
+    fn render_writes_security_and_humans_txt_from_config() {
+        let config = test_support::config(json!({
+            "siteUrl": "https://blog.example.com",
+            "security": { "contact": ["security@example.com"], "expires": "2030-01-01" },
+            "humans": { "team": ["Author: Jane"] },
+        }));
+        let result = test_support::result(&config, Vec::new());
+        let mut output = crate::Output::new(true).with_contents();
+        crate::Site::render_to(&config, &result, &mut output).unwrap();
+        let txt = String::from_utf8_lossy(output.contents("public/.well-known/security.txt").expect("应生成 security.txt")).into_owned();
+        assert_eq!(
+            txt,
+            "Contact: mailto:security@example.com\nExpires: 2030-01-01T00:00:00.000Z\nCanonical: https://blog.example.com/.well-known/security.txt\n"
+        );
+        let humans = String::from_utf8_lossy(output.contents("public/humans.txt").expect("应生成 humans.txt")).into_owned();
+        assert!(humans.starts_with("/* TEAM */\n    Author: Jane\n\n/* SITE */\n    Last update: "), "{}", humans);
+
+        let config = test_support::config(json!({ "security": { "contact": [] } }));
+        let mut output = crate::Output::new(true).with_contents();
+        crate::Site::render_to(&config, &result, &mut output).unwrap();
+        assert!(output.contents("public/.well-known/security.txt").is_none());
+        assert!(output.contents("public/humans.txt").is_none());
+    }
+
+    #[test]
+    fn breadcrumb_list_follows_site_section_post() {
+        let mut post = test_support::meta("ownership", "2024-01-01");
+        post.title = "Ownership </script>".to_string();
+        post.tags = vec![test_support::tag("Rust")];
+        let json_ld = breadcrumb_json_ld("https://blog.example.com", "Test Blog", &post);
+        assert!(!json_ld.contains("</script>"), "{}", json_ld);
+        let value: serde_json::Value = serde_json::from_str(&json_ld).unwrap();
+        assert_eq!(value["@type"], "BreadcrumbList");
+        let items: Vec<(u64, &str, &str)> = value["itemListElement"].as_array().unwrap().iter()
+            .map(|item| (item["position"].as_u64().unwrap(), item["name"].as_str().unwrap(), item["item"].as_str().unwrap()))
+            .collect();
+        assert_eq!(items, [
+            (1, "Test Blog", "https://blog.example.com/"),
+            (2, "Rust", "https://blog.example.com/tag/rust.html"),
+            (3, "Ownership </script>", "https://blog.example.com/ownership.html"),
+        ]);
+
+        post.tags.clear();
+        let value: serde_json::Value = serde_json::from_str(&breadcrumb_json_ld("https://blog.example.com", "Test Blog", &post)).unwrap();
+        let positions: Vec<u64> = value["itemListElement"].as_array().unwrap().iter().map(|i| i["position"].as_u64().unwrap()).collect();
+        assert_eq!(positions, [1, 2]);
+    }
+
+    #[tokio::test]
+    async fn breadcrumbs_reach_post_pages_only_when_enabled() {
+        let server = test_support::notion_site(vec![test_support::page("p1", "ownership", &["Rust"], json!({}))], Vec::new()).await;
+        for (breadcrumbs, expected) in [(true, true), (false, false)] {
+            let config = test_support::config(json!({ "siteUrl": "https://blog.example.com", "breadcrumbs": breadcrumbs }));
+            let result = crate::Site::build_from(&server.url, &config).await.unwrap();
+            let mut output = crate::Output::new(true).with_contents();
+            crate::Site::render_to(&config, &result, &mut output).unwrap();
+            let page = String::from_utf8_lossy(output.contents("public/ownership.html").unwrap()).into_owned();
+            assert_eq!(page.contains("\"@type\":\"BreadcrumbList\""), expected, "{}", page);
+        }
+    }
+}
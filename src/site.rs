@@ -0,0 +1,3260 @@
+use crate::api;
+use crate::cdn;
+use crate::compress;
+use crate::config::{Config, DateSource, QueryConfig, SortDirection, TemplateBlocks};
+use crate::error::BuildError;
+use crate::init;
+use crate::journal::Journal;
+use crate::feed;
+use crate::cache::Cache;
+use crate::favicon::{self, FaviconBundle};
+use crate::fonts::{self, FontBundle};
+use crate::images::{self, ImageFile, ImageFormat};
+use crate::markdown::MarkdownRenderer;
+use crate::og;
+use crate::output::{self, Output};
+use crate::pretty;
+use crate::model::*;
+use crate::notion::{NOTION_API, NotionApi};
+use crate::renderer::{block_type_name, escape_html, render_icon, url_has_extension, HtmlRenderer, IconHtml, ListKind, PageLink, UNSUPPORTED_BLOCK};
+use crate::retry;
+use crate::schema;
+use crate::seo;
+use crate::text;
+use anyhow::{Context, Result};
+use notionrs_types::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// `Site::build` 的产物：渲染模板所需的全部结构化数据
+#[derive(Debug)]
+pub struct BuildResult {
+    pub site_meta: SiteMeta,
+    /// 已发布的文章 (含正文)
+    pub posts: Vec<PostMetadataWithContent>,
+    /// 出现在列表中的文章的标签统计，按数量降序
+    pub tags: Vec<TagStat>,
+    /// 本地化的字体 (启用 `selfHostFonts` 时)
+    pub fonts: Option<FontBundle>,
+    /// 转换格式后的封面及其原图 (`imageFormat` 不为 `original` 时)
+    pub images: Vec<ImageFile>,
+    /// 由站点图标生成的 favicon 文件 (启用 `favicon` 时)
+    pub favicon: Option<FaviconBundle>,
+}
+
+impl BuildResult {
+    /// 出现在首页列表中的文章：设置了 `order` 的按其升序排在前面，其余按日期降序
+    pub fn listed_posts(&self) -> Vec<&PostMetadata> {
+        let mut posts: Vec<&PostMetadata> = self.posts
+            .iter()
+            .filter(|p| p.meta.in_list)
+            .map(|p| &p.meta)
+            .collect();
+        posts.sort_by(|a, b| compare_index_order(a, b));
+        posts
+    }
+}
+
+pub struct Site;
+
+impl Site {
+    /// 查询数据库、抓取每篇文章的正文并统计标签，不写任何文件
+    pub async fn build(config: &Config) -> Result<BuildResult, BuildError> {
+        let journal = open_journal(config, false).map_err(BuildError::fetch)?;
+        Self::build_with(NOTION_API, config, None, journal).await.map_err(BuildError::fetch)
+    }
+
+    /// 同 `build`，但跳过上次中断的构建中已抓取完成的文章，使用构建日志中记录的结果
+    pub async fn build_resume(config: &Config) -> Result<BuildResult, BuildError> {
+        let journal = open_journal(config, true).map_err(BuildError::fetch)?;
+        Self::build_with(NOTION_API, config, None, journal).await.map_err(BuildError::fetch)
+    }
+
+    /// 同 `build`，但只抓取 slug 或页面 ID 与 `only` 相同的一篇文章的正文，用于快速预览单篇文章。
+    /// 其余文章只有元数据，不在结果的 `posts` 中
+    pub async fn build_only(config: &Config, only: &str) -> Result<BuildResult, BuildError> {
+        let result = Self::build_with(NOTION_API, config, Some(only), None).await.map_err(BuildError::fetch)?;
+        if result.posts.is_empty() {
+            return Err(BuildError::Config(anyhow::anyhow!("未找到 slug 或 ID 为 {} 的已发布文章", only)));
+        }
+        Ok(result)
+    }
+
+    /// 与 `build` 相同，但向 `api` 请求 Notion 数据 (测试时为本地模拟服务)，不记录构建日志
+    #[cfg(test)]
+    pub(crate) async fn build_from(api: &str, config: &Config) -> Result<BuildResult> {
+        Self::build_with(api, config, None, None).await
+    }
+
+    /// 只检查配置与 Notion 的连接：数据库地址能否解析、Token 是否有效、数据库是否可访问、
+    /// 属性是否与模板一致，并试查询文章列表。不抓取任何正文，不写任何文件
+    pub async fn validate(config: &Config) -> Result<(), BuildError> {
+        let data_source_id = config.get_notion_id().map_err(BuildError::Config)?;
+        println!(">>> 数据库 ID: {}", data_source_id);
+        Self::check_connection(NOTION_API, config, &data_source_id).await.map_err(BuildError::fetch)
+    }
+
+    async fn check_connection(api: &str, config: &Config, data_source_id: &str) -> Result<()> {
+        let client = NotionApi::with_base(api, &config.notion_token);
+        // 获取数据库结构：Token 无效或数据库未共享时在此给出提示
+        schema::validate(&client, data_source_id).await?;
+        println!(">>> 数据库可以访问，属性与模板一致");
+
+        let filter = query_filter(&config.query);
+        let sorts: Vec<Sort> = query_sort(&config.query).into_iter().collect();
+        let response = retry::with_retry("查询 Notion 数据库", || {
+            client.query_data_source::<MyProperties>(data_source_id, &filter, &sorts, None)
+        })
+        .await?;
+        match response.results.first() {
+            Some(page) => println!(">>> 查询成功，示例文章: {}", page.properties.title),
+            None => println!(">>> 查询成功，但数据库中还没有文章"),
+        }
+        Ok(())
+    }
+
+    /// `journal` 为构建日志：其中已有的文章直接使用记录的结果，新抓取的文章逐篇记入，构建成功后删除
+    async fn build_with(api: &str, config: &Config, only: Option<&str>, mut journal: Option<Journal<PageContent>>) -> Result<BuildResult> {
+        let client = NotionApi::with_base(api, &config.notion_token);
+        let data_source_id = config.get_notion_id()?;
+
+        // 先校验数据库属性，属性名或类型不符时给出明确提示
+        let site_icon = render_icon(&schema::validate(&client, &data_source_id).await?);
+
+        // 获取所有文章元数据
+        println!(">>> 正在获取文章列表...");
+        // 第一次查询决定了构建能否继续，因此对暂时性错误进行重试
+        // 每次最多返回 100 条，按 next_cursor 翻页直到取完
+        let filter = query_filter(&config.query);
+        let sorts: Vec<Sort> = query_sort(&config.query).into_iter().collect();
+        let results = collect_pages(None, |cursor| {
+            let (client, data_source_id, filter, sorts) = (&client, &data_source_id, &filter, &sorts);
+            async move {
+                let response = retry::with_retry("查询 Notion 数据库", || {
+                    client.query_data_source::<MyProperties>(data_source_id, filter, sorts, cursor.as_deref())
+                })
+                .await?;
+                Ok((response.results, response.next_cursor))
+            }
+        })
+        .await?;
+
+        let today = today_utc();
+        // 标签颜色以标签名为键只确定一次 (配置覆盖优先，否则取首次出现时的颜色)，全站共用
+        let mut tag_colors: HashMap<String, String> = HashMap::new();
+        let mut all_posts = Vec::new();
+        for page in results {
+            let p = page.properties;
+            let title = p.title.to_string();
+            let safe_title = safe_title(&title, &page.id.to_string());
+            let url = config.post_url(&safe_title);
+
+            let date_str = post_date(
+                config.date_source,
+                p.date.date.as_ref().and_then(|d| d.start.as_ref()).map(|dt| dt.to_string()),
+                &page.created_time.to_string(),
+                &page.last_edited_time.to_string(),
+            );
+
+            // 提取页面图标 (Emoji 或 URL)
+            let icon_url = render_icon(&page.icon).map(IconHtml::into_source);
+
+            // 提取封面图片 URL
+            let cover = page.cover.as_ref().map(|c| c.to_string());
+
+            let cover_position = match p.cover_focus.as_ref().map(|f| f.to_string()) {
+                Some(focus) if !focus.trim().is_empty() => cover_position(&focus).unwrap_or_else(|| {
+                    println!(">>> 警告: {} 的 coverFocus 无效 ({})，使用 center", title, focus);
+                    "center".to_string()
+                }),
+                _ => "center".to_string(),
+            };
+
+            let series = p.series.as_ref()
+                .and_then(|s| s.select.as_ref())
+                .map(|opt| opt.name.clone());
+
+            let extra_head = p.head.as_ref()
+                .map(|h| h.to_string())
+                .filter(|h| !h.trim().is_empty());
+
+            // 发布时间窗口：窗口外的文章视同未发布
+            let in_window = match p.publish_window.as_ref().and_then(|w| w.date.as_ref()) {
+                Some(window) => {
+                    let start = window.start.as_ref().map(|d| d.to_string());
+                    let end = window.end.as_ref().map(|d| d.to_string());
+                    in_publish_window(start.as_deref(), end.as_deref(), &today)
+                }
+                None => true,
+            };
+
+            let language = p.language.as_ref()
+                .and_then(|l| l.select.as_ref())
+                .map(|opt| opt.name.clone());
+            let translation_key = p.translation_key.as_ref()
+                .map(|k| k.to_string().trim().to_string())
+                .filter(|k| !k.is_empty());
+
+            let mut tags: Vec<Tag> = p.tags.multi_select.iter()
+                .map(|opt| site_tag(config, &mut tag_colors, &opt.name, &opt.color))
+                .collect();
+            tags.sort_by(|a, b| a.name.cmp(&b.name));
+            let (tags, hidden_tags) = split_hidden_tags(config, tags);
+
+            let properties_table = config.properties_table.iter()
+                .filter_map(|name| p.extra.get(name).and_then(|prop| PropertyRow::from_property(name, prop)))
+                .collect();
+
+            let og_image = og_image_url(config, cover.is_some(), &safe_title);
+
+            all_posts.push((page.id.to_string(), PostMetadata {
+                title,
+                slug: safe_title,
+                url,
+                date: date_str,
+                tags,
+                hidden_tags,
+                preview: "".to_string(), // 稍后填充
+                excerpt_html: String::new(),
+                manual_excerpt: false,
+                word_count: 0,
+                char_count: 0,
+                publish: p.publish.checkbox && in_window,
+                in_menu: p.in_menu.checkbox,
+                in_list: p.in_list.checkbox,
+                hide_title: p.hide_title.as_ref().is_some_and(|c| c.checkbox),
+                hide_meta: p.hide_meta.as_ref().is_some_and(|c| c.checkbox),
+                in_feed: p.in_feed.as_ref().is_some_and(|c| c.checkbox),
+                order: p.order.as_ref().and_then(|o| o.number),
+                icon_url,
+                cover,
+                cover_position,
+                cover_picture: None,
+                og_image,
+                series,
+                extra_head,
+                todo_progress: None,
+                language,
+                translation_key,
+                comment_count: None,
+                properties_table,
+            }));
+        }
+        // 查询结果按 Notion 中的顺序返回；日期取自页面时间戳时按该日期从新到旧排列 (稳定排序)
+        if config.date_source != DateSource::Date {
+            all_posts.sort_by(|(_, a), (_, b)| b.date.cmp(&a.date));
+        }
+
+        // 字体样式表及字体文件下载到本地，不再依赖第三方字体服务
+        let fonts = if config.self_host_fonts.is_empty() {
+            None
+        } else {
+            let cache = Cache::open(&config.cache_dir, config.cache_max_age(), config.cache_max_size)?;
+            Some(fonts::self_host(&config.self_host_fonts, &cache).await?)
+        };
+
+        // 封面下载并转换格式，原图作为 <picture> 的回退；转换失败时保留 Notion 中的地址
+        let mut images = Vec::new();
+        if config.image_format != ImageFormat::Original {
+            let cache = Cache::open(&config.cache_dir, config.cache_max_age(), config.cache_max_size)?;
+            for (_, meta) in all_posts.iter_mut().filter(|(_, m)| m.publish) {
+                let Some(cover) = &meta.cover else { continue };
+                match images::localize(cover, &cache, config.image_format, config.image_quality).await {
+                    Ok(Some(converted)) => {
+                        meta.cover_picture = Some(converted.picture);
+                        images.extend(converted.files);
+                    }
+                    Ok(None) => {}
+                    Err(e) => println!(">>> 警告: 封面转换失败，使用原地址 {}: {:#}", cover, e),
+                }
+            }
+        }
+
+        // 数据库图标生成 favicon
+        let favicon = match (&site_icon, config.favicon) {
+            (Some(icon), true) => {
+                let cache = Cache::open(&config.cache_dir, config.cache_max_age(), config.cache_max_size)?;
+                Some(favicon::generate(icon, &cache).await?)
+            }
+            (None, true) => {
+                println!(">>> 数据库没有设置图标，跳过生成 favicon");
+                None
+            }
+            _ => None,
+        };
+
+        // 构建时间与构建 ID 在一次构建中只确定一次，所有页面一致
+        let site_meta = SiteMeta {
+            icon_url: site_icon.map(IconHtml::into_source),
+            favicons: favicon.as_ref().map(|f| f.links.clone()).unwrap_or_default(),
+            ..site_meta(config, all_posts.iter().map(|(_, m)| m.clone()).collect(), fonts.is_some(), unix_now())
+        };
+
+        // 指向本次构建中页面的 Notion 链接改写为站内链接 (相对于文章页面所在目录)
+        let post_root = config.post_root_path();
+        let mut pages: HashMap<String, PageLink> = all_posts.iter()
+            .filter(|(_, m)| m.publish)
+            .map(|(id, m)| (id.replace('-', "").to_lowercase(), PageLink {
+                url: format!("{}/{}", post_root, m.url),
+                title: m.title.clone(),
+                icon: m.icon_url.clone().map(IconHtml::from_source),
+            }))
+            .collect();
+        // 提及站点数据库本身时链接到首页
+        pages.insert(data_source_id.replace('-', "").to_lowercase(), PageLink {
+            url: format!("{}/{}", post_root, config.home_url()),
+            title: config.site_title(),
+            icon: None,
+        });
+        let renderer = HtmlRenderer::new(config.render_options()).with_pages(pages);
+        let fetcher = Fetcher::new(config, &client, &renderer);
+
+        // 遍历抓取每篇文章的正文
+        let mut posts = Vec::new();
+        // 没有读取评论的权限时只警告一次，之后不再请求；其他错误只跳过当前文章
+        let mut fetch_comments = config.fetch_comments;
+        for (page_id, mut meta) in all_posts {
+            if !meta.publish {
+                continue;
+            }
+            if only.is_some_and(|only| !is_same_post(only, &page_id, &meta.slug)) {
+                continue;
+            }
+
+            let page = match journal.as_mut().and_then(|j| j.take(&page_id)) {
+                Some(page) => {
+                    println!(">>> 已完成，跳过: {}", meta.title);
+                    page
+                }
+                None => {
+                    println!(">>> 正在处理: {}", meta.title);
+                    let page = fetcher.fetch_page(&page_id).await
+                        .with_context(|| format!("处理文章「{}」失败", meta.title))?;
+                    if let Some(journal) = journal.as_mut() {
+                        journal.record(&page_id, &page)?;
+                    }
+                    page
+                }
+            };
+            if fetch_comments {
+                match fetch_comment_count(&client, &page_id).await {
+                    Ok(count) => meta.comment_count = Some(count),
+                    Err(e) if is_comment_permission_error(&e) => {
+                        println!(">>> 警告: 没有读取评论的权限，已停止获取评论 (请在 Integration 设置中开启 Read comments): {}", e);
+                        fetch_comments = false;
+                    }
+                    Err(e) => println!(">>> 警告: 获取「{}」的评论失败: {}", meta.title, e),
+                }
+            }
+            (meta.word_count, meta.char_count) = text::count_words(&page.plain_text);
+            meta.todo_progress = Some(page.todos).filter(|t| t.total > 0);
+
+            // 搜索引擎使用的标题与摘要，与页面显示的标题、卡片预览长度不同
+            let meta_title = text::truncate_words(&meta.title, META_TITLE_CHARS);
+            let summary = page.excerpt.as_ref().map_or(page.plain_text.as_str(), |e| e.plain_text.as_str());
+            let meta_description = Some(text::truncate_words(summary, META_DESCRIPTION_CHARS)).filter(|d| !d.is_empty());
+
+            // 手动摘要：使用分隔标记之前的全部内容；否则按长度截断
+            if let Some(excerpt) = page.excerpt {
+                meta.preview = excerpt.plain_text.trim().to_string();
+                meta.excerpt_html = excerpt.html;
+                meta.manual_excerpt = true;
+            } else {
+                meta.preview = match config.preview_width {
+                    // 按显示宽度截断，中英文预览的视觉长度一致
+                    Some(width) => match text::truncate_width(&page.plain_text, width) {
+                        Some(head) => format!("{}...", head),
+                        None => page.plain_text,
+                    },
+                    // 一次遍历找到第 150 个字符的位置，避免先计数再截取
+                    None => match page.plain_text.char_indices().nth(150) {
+                        Some((idx, _)) => format!("{}...", &page.plain_text[..idx]),
+                        None => page.plain_text,
+                    },
+                };
+                meta.excerpt_html = format!("<p>{}</p>", escape_html(&meta.preview));
+            }
+
+            let description = Some(meta.preview.clone());
+            // 正文 HTML 直接移入结果，渲染时各上下文只引用不复制
+            let content = if config.pretty_html { pretty::pretty_html(&page.html) } else { page.html };
+            posts.push(PostMetadataWithContent {
+                meta,
+                content,
+                markdown: page.markdown,
+                description,
+                meta_title,
+                meta_description,
+                series: None,
+                headings: page.headings,
+                translations: Vec::new(),
+                blocks: config.include_raw_blocks.then_some(page.blocks),
+            });
+        }
+        if let Some(only) = only.filter(|_| posts.is_empty()) {
+            anyhow::bail!("未找到 slug 或 ID 为 {} 的已发布文章", only);
+        }
+
+        let unsupported = fetcher.unsupported.take();
+        if !unsupported.is_empty() {
+            let summary: Vec<String> = unsupported.iter().map(|(kind, n)| format!("{} ×{}", kind, n)).collect();
+            println!(">>> 警告: 以下不支持的 Block 已省略 (使用 --strict 时构建失败): {}", summary.join(", "));
+        }
+
+        if let Some(journal) = journal {
+            journal.finish()?;
+        }
+
+        // 系列：同系列文章按日期排序并互相链接
+        let series_infos: Vec<Option<SeriesInfo>> = {
+            let series_map = group_by_series(posts.iter().map(|p| &p.meta));
+            posts.iter().map(|p| series_info(&p.meta, &series_map)).collect()
+        };
+        for (post, series) in posts.iter_mut().zip(series_infos) {
+            post.series = series;
+        }
+
+        // 多语言：同一 translationKey 的文章互相链接
+        let translations = translations_of(&posts);
+        for (post, translations) in posts.iter_mut().zip(translations) {
+            post.translations = translations;
+        }
+
+        let mut result = BuildResult {
+            site_meta,
+            posts,
+            tags: Vec::new(),
+            fonts,
+            images,
+            favicon,
+        };
+        let tags = tag_stats(config, &group_by_tag(result.listed_posts()));
+        result.tags = tags;
+        Ok(result)
+    }
+
+    /// 只渲染一个 Block 树的正文 HTML，不查询数据库、不套用模板，用于调试渲染器。
+    /// `source` 为本地 JSON 文件时从中读取 Block (格式见 README「渲染单个页面」)，否则视为 Notion 页面 ID
+    pub async fn render_blocks(config: &Config, source: &str) -> Result<String, BuildError> {
+        Self::render_blocks_from(NOTION_API, config, source).await.map_err(BuildError::fetch)
+    }
+
+    /// 同 `render_blocks`，页面 ID 向 `api` 所指的地址请求
+    pub(crate) async fn render_blocks_from(api: &str, config: &Config, source: &str) -> Result<String> {
+        let client = NotionApi::with_base(api, &config.notion_token);
+        let renderer = HtmlRenderer::new(config.render_options());
+        let mut fetcher = Fetcher::new(config, &client, &renderer);
+        let path = Path::new(source);
+        let root = if path.is_file() {
+            let json = std::fs::read_to_string(path)?;
+            let fixture: HashMap<String, serde_json::Value> = serde_json::from_str(&json)
+                .map_err(|e| anyhow::anyhow!("无法解析 {}: {}", source, e))?;
+            fetcher.fixture = Some(fixture);
+            "root".to_string()
+        } else {
+            source.replace('-', "")
+        };
+        Ok(fetcher.fetch_page(&root).await?.html)
+    }
+
+    /// 用 `templates/` 下的 Tera 模板渲染 `BuildResult`，并写出到 `public/`
+    pub fn render(config: &Config, result: &BuildResult) -> Result<(), BuildError> {
+        Self::render_to(config, result, &mut Output::new(false))
+    }
+
+    /// 只渲染 `result.posts` 中的文章页，不生成首页、标签页等汇总页面 (配合 `build_only` 使用)
+    pub fn render_posts(config: &Config, result: &BuildResult, output: &mut Output) -> Result<(), BuildError> {
+        let tera = load_templates().map_err(BuildError::Template)?;
+        render_post_pages(&tera, config, result, output).map_err(BuildError::render)
+    }
+
+    /// 以阅读模式渲染文章页到 `public/reader/`：使用内置模板，样式内联，不引用外部脚本与样式表。
+    /// 需要以 `reader_mode` 配置构建，使嵌入内容渲染为静态卡片。输出不是 AMP 规范页面 (不加载 AMP 运行时)
+    pub fn render_reader(result: &BuildResult, output: &mut Output) -> Result<(), BuildError> {
+        render_reader_pages(result, output).map_err(BuildError::render)
+    }
+
+    /// 同 `render`，但所有文件经由 `output` 写出 (可用于 dry-run 或收集文件清单)
+    pub fn render_to(config: &Config, result: &BuildResult, output: &mut Output) -> Result<(), BuildError> {
+        render_site(config, result, output).map_err(BuildError::render)
+    }
+}
+
+fn render_reader_pages(result: &BuildResult, output: &mut Output) -> Result<()> {
+    let mut tera = tera::Tera::default();
+    tera.add_raw_template("reader.html", init::READER_TEMPLATE)?;
+    for post in &result.posts {
+        let mut context = tera::Context::new();
+        context.insert("siteMeta", &result.site_meta);
+        context.insert("post", post);
+        context.insert("inlineCss", init::READER_CSS);
+        let path = format!("public/{}/{}.html", READER_DIR, post.meta.slug);
+        context.insert("rootPath", &output::root_path(&path));
+        let html = tera.render("reader.html", &context)?;
+        output.write(path, html)?;
+    }
+    Ok(())
+}
+
+/// `Site::render_to` 的实现
+fn render_site(config: &Config, result: &BuildResult, output: &mut Output) -> Result<()> {
+    let tera = load_templates()?;
+
+    // 1. 渲染文章页
+    render_post_pages(&tera, config, result, output)?;
+
+    // 2. 首页、主题页、系列页、sitemap 与 Feed 互不依赖，并行渲染后按原顺序写出
+    let posts_meta_for_index = result.listed_posts();
+    let series_template = if tera.get_template_names().any(|t| t == "series.html") {
+        "series.html"
+    } else {
+        "index.html"
+    };
+
+    if config.topics_page && !tera.get_template_names().any(|t| t == "topics.html") {
+        anyhow::bail!("已启用 topicsPage，但缺少模板 topics.html (查找路径: {}/topics.html)", TEMPLATE_DIR);
+    }
+
+    let mut jobs: Vec<RenderJob> = Vec::new();
+    println!(">>> 正在生成首页...");
+    jobs.push(Box::new(|| {
+        let mut context = tera::Context::new();
+        context.insert("siteMeta", &result.site_meta);
+        context.insert("pages", &posts_meta_for_index); // Changed from "posts" to "pages" to match articleList.html
+        context.insert("rootPath", &output::root_path("public/index.html"));
+        Ok(("public/index.html".into(), tera.render("index.html", &context)?))
+    }));
+    if config.topics_page {
+        println!(">>> 正在生成主题页...");
+        jobs.push(Box::new(|| {
+            let topics = topic_groups(result, &posts_meta_for_index);
+            let mut context = tera::Context::new();
+            context.insert("siteMeta", &result.site_meta);
+            context.insert("topics", &topics);
+            context.insert("rootPath", &output::root_path("public/topics.html"));
+            Ok(("public/topics.html".into(), tera.render("topics.html", &context)?))
+        }));
+    }
+    if config.series_pages {
+        println!(">>> 正在生成系列页...");
+        for (series_name, series_posts) in group_by_series(result.posts.iter().map(|p| &p.meta)) {
+            let tera = &tera;
+            jobs.push(Box::new(move || {
+                let filename = config.listing_output_path(&format!("series/{}", slugify(&series_name)));
+                let series_site_meta = SiteMeta {
+                    title: format!("Series: {}", series_name),
+                    icon_url: None,
+                    pages: series_posts.iter().map(|p| (*p).clone()).collect(),
+                    ..result.site_meta.clone()
+                };
+
+                let mut context = tera::Context::new();
+                context.insert("siteMeta", &series_site_meta);
+                context.insert("seriesName", &series_name);
+                context.insert("pages", &series_posts);
+                context.insert("rootPath", &output::root_path(&filename));
+                Ok((filename.into(), tera.render(series_template, &context)?))
+            }));
+        }
+    }
+    if let Some(site_url) = config.site_url() {
+        jobs.push(Box::new(move || Ok(("public/sitemap.xml".into(), seo::sitemap_xml(site_url, result)))));
+        jobs.push(Box::new(move || {
+            Ok((Path::new("public").join(feed::RSS_PATH), feed::rss_xml(config, site_url, result)))
+        }));
+        if config.index_feed {
+            jobs.push(Box::new(move || {
+                Ok((Path::new("public").join(feed::INDEX_PATH), feed::rss_xml(config, site_url, result)))
+            }));
+        }
+    }
+    let rendered = render_parallel(config.render_workers(), &jobs, |job| job())?;
+    for (path, contents) in rendered {
+        output.write(path, contents)?;
+    }
+
+    // 3. 生成标签页 (各标签页之间并行渲染)
+    println!(">>> 正在生成标签页...");
+    render_tag_pages(&tera, config, result, &result.tags, &posts_meta_for_index, "tag", output)?;
+
+    // 3.5 按语言生成首页与标签页：标签链接指向该语言的标签页 (`<lang>/tag/`)，文章数只统计该语言的文章
+    if config.i18n {
+        let languages = languages_of(&posts_meta_for_index);
+        for language in &languages {
+            println!(">>> 正在生成语言子站: {}", language);
+            let pages: Vec<&PostMetadata> = posts_meta_for_index.iter()
+                .copied()
+                .filter(|p| p.language.as_deref() == Some(language.as_str()))
+                .collect();
+            let tags = tag_stats(config, &group_by_tag(pages.iter().copied()));
+
+            let mut context = tera::Context::new();
+            context.insert("siteMeta", &result.site_meta);
+            context.insert("pages", &pages);
+            context.insert("language", language);
+            context.insert("languages", &languages);
+            let path = format!("public/{}/index.html", language);
+            context.insert("rootPath", &output::root_path(&path));
+            context.insert("tagRoot", ".");
+            let html = tera.render("index.html", &context)?;
+            output.write(path, html)?;
+
+            let tag_dir = format!("{}/tag", language);
+            render_tag_pages(&tera, config, result, &tags, &pages, &tag_dir, output)?;
+        }
+    }
+
+    // 6. 拷贝静态资源
+    if Path::new("templates/main.css").exists() {
+        output.copy("templates/main.css", "public/main.css")?;
+    }
+
+    // 自动拷贝 templates/assets 到 public/assets
+    let assets_src = Path::new("templates/assets");
+    if assets_src.exists() {
+        println!(">>> 正在拷贝静态资源...");
+        let assets_dst = Path::new("public/assets");
+        output.copy_dir(assets_src, assets_dst)?;
+    }
+
+    // 额外的静态文件 (CNAME、ads.txt 等)
+    for (src, dst) in &config.extra_static {
+        let src_path = Path::new(src);
+        let dst_path = Path::new("public").join(dst.trim_start_matches('/'));
+        if src_path.is_dir() {
+            output.copy_dir(src_path, &dst_path)?;
+        } else if src_path.is_file() {
+            output.copy(src_path, &dst_path)?;
+        } else {
+            println!(">>> 警告: extraStatic 中的 {} 不存在，已跳过", src);
+        }
+    }
+
+    // robots.txt、humans.txt 与 security.txt (sitemap.xml 与 Feed 已与首页一同渲染)
+    if config.robots.enabled {
+        output.write("public/robots.txt", seo::robots_txt(config))?;
+    }
+    // 供无头前端使用的 JSON 接口
+    if config.json_api {
+        output.write(Path::new("public").join(api::API_DIR).join("posts.json"), api::posts_json(result)?)?;
+        for post in &result.posts {
+            output.write(Path::new("public").join(api::post_json_path(&post.meta)), api::post_json(post)?)?;
+        }
+    }
+    if let Some(humans) = &config.humans {
+        output.write("public/humans.txt", seo::humans_txt(humans))?;
+    }
+    if let Some(security) = &config.security {
+        match seo::security_txt(config, security) {
+            Some(txt) => output.write("public/.well-known/security.txt", txt)?,
+            None => println!(">>> 警告: security 中没有 contact，跳过 security.txt"),
+        }
+    }
+
+    // 本地化的字体
+    if let Some(fonts) = &result.fonts {
+        let dir = Path::new("public").join(fonts::FONTS_DIR);
+        for file in &fonts.files {
+            output.write(dir.join(&file.name), &file.data)?;
+        }
+        output.write(Path::new("public").join(fonts::FONTS_CSS), &fonts.css)?;
+    }
+
+    // 转换格式后的封面
+    let dir = Path::new("public").join(images::IMAGES_DIR);
+    for file in &result.images {
+        output.write(dir.join(&file.name), &file.data)?;
+    }
+
+    // 由站点图标生成的 favicon
+    if let Some(favicon) = &result.favicon {
+        for file in &favicon.files {
+            output.write(Path::new("public").join(&file.name), &file.data)?;
+        }
+    }
+
+    // 文章分享卡片
+    if config.generate_og_images {
+        write_og_images(config, result, output)?;
+    }
+
+    // 7. 部署平台规则文件
+    if let Some(format) = config.cdn_rules {
+        cdn::write_rules(config, format, output)?;
+    }
+
+    // 8. 预压缩：根据上面生成的文件清单写出 .gz / .br 副本，必须在所有文件生成之后
+    if !config.precompress.is_empty() {
+        let count = compress::precompress(output, &config.precompress, config.precompress_min_size)?;
+        println!(">>> 已生成 {} 个预压缩文件", count);
+    }
+
+    // 9. 输出文件与目录的权限 (包括预压缩副本)
+    let (file_mode, dir_mode) = config.output_modes()?;
+    output.set_permissions(Path::new(output::OUTPUT_DIR), file_mode, dir_mode)?;
+
+    Ok(())
+}
+
+/// 模板目录
+const TEMPLATE_DIR: &str = "templates";
+/// 渲染必需的模板；主题缺少时使用内置默认模板
+const REQUIRED_TEMPLATES: [&str; 2] = ["index.html", "post.html"];
+/// 阅读模式页面的输出目录 (相对于 `public/`)
+const READER_DIR: &str = "reader";
+
+fn load_templates() -> Result<tera::Tera> {
+    load_templates_from(TEMPLATE_DIR)
+}
+
+/// 加载 `dir` 下的全部模板，缺少的必需模板以内置默认模板补上
+fn load_templates_from(dir: &str) -> Result<tera::Tera> {
+    let mut tera = tera::Tera::new(&format!("{}/**/*", dir))?;
+    tera.full_reload()?;
+    for name in REQUIRED_TEMPLATES {
+        if tera.get_template_names().any(|t| t == name) {
+            continue;
+        }
+        let builtin = init::builtin_template(name)
+            .with_context(|| format!("缺少模板 {} (查找路径: {}/{})", name, dir, name))?;
+        println!(">>> 警告: 主题中没有 {}/{}，使用内置默认模板", dir, name);
+        tera.add_raw_template(name, builtin)?;
+    }
+    Ok(tera)
+}
+
+/// 为 `pages` 中出现的每个标签生成标签页，`dir` 为标签页所在目录 (相对于 `public/`，如 `tag`、`en/tag`)，
+/// `tags` 为该目录下的标签统计 (`allTags`)。语言子站的标签页中，标签链接以 `tagRoot` (该语言目录的相对路径) 为起点。
+/// 设置了 `postsPerPage` 时分页：第一页为 `<dir>/<slug>.html`，其后为 `<dir>/<slug>/2.html` 等
+fn render_tag_pages(
+    tera: &tera::Tera,
+    config: &Config,
+    result: &BuildResult,
+    tags: &[TagStat],
+    pages: &[&PostMetadata],
+    dir: &str,
+    output: &mut Output,
+) -> Result<()> {
+    // 优先使用 tag.html，如果没有则回退到 index.html
+    let tag_template = if tera.get_template_names().any(|t| t == "tag.html") {
+        "tag.html"
+    } else {
+        "index.html"
+    };
+    // 每个标签按 postsPerPage 拆成若干页：(标签名, 页码, 总页数, 该页的文章)
+    let mut tag_pages = Vec::new();
+    for (tag_name, tag_posts) in group_by_tag(pages.iter().copied()) {
+        let per_page = config.posts_per_page.filter(|n| *n > 0).unwrap_or(tag_posts.len().max(1));
+        let chunks: Vec<Vec<&PostMetadata>> = tag_posts.chunks(per_page).map(|c| c.to_vec()).collect();
+        let total = chunks.len();
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            tag_pages.push((tag_name.clone(), i + 1, total, chunk));
+        }
+    }
+    let rendered = render_parallel(config.render_workers(), &tag_pages, |(tag_name, page, total, tag_posts)| {
+        let tag_site_meta = SiteMeta {
+            title: format!("Tag: {}", tag_name),
+            icon_url: None,
+            pages: tag_posts.iter().map(|p| (*p).clone()).collect(),
+            ..result.site_meta.clone()
+        };
+        let pagination = Pagination {
+            current: *page,
+            total: *total,
+            prev_url: (*page > 1).then(|| config.listing_url(&tag_page_name(dir, tag_name, page - 1))),
+            next_url: (page < total).then(|| config.listing_url(&tag_page_name(dir, tag_name, page + 1))),
+        };
+
+        let mut context = tera::Context::new();
+        context.insert("siteMeta", &tag_site_meta);
+        context.insert("tagName", tag_name); // 传入 tagName 供模板使用
+        if let Some(tag) = tags.iter().find(|t| &t.name == tag_name) {
+            context.insert("tag", tag); // 含描述与封面，供主题渲染标签头部
+        }
+        context.insert("pages", tag_posts);
+        context.insert("pagination", &pagination);
+        context.insert("allTags", tags); // 传入所有标签列表
+        context.insert("rootPath", &output::root_path(&tag_page_path(config, dir, tag_name, *page)));
+        if dir != "tag" {
+            // 语言目录中的标签页与站点根目录下的同名标签页深度相同
+            context.insert("tagRoot", &output::root_path(&tag_page_path(config, "tag", tag_name, *page)));
+        }
+        Ok(tera.render(tag_template, &context)?)
+    })?;
+    for ((tag_name, page, _, _), html) in tag_pages.iter().zip(rendered) {
+        output.write(tag_page_path(config, dir, tag_name, *page), html)?;
+    }
+    Ok(())
+}
+
+/// 标签页第 `page` 页的输出文件路径，按 `trailingSlash` 决定形式
+fn tag_page_path(config: &Config, dir: &str, tag_name: &str, page: usize) -> String {
+    config.listing_output_path(&tag_page_name(dir, tag_name, page))
+}
+
+/// 标签页第 `page` 页的路径名 (不含扩展名)：第一页为 `<dir>/<slug>`，其后为 `<dir>/<slug>/<page>`
+fn tag_page_name(dir: &str, tag_name: &str, page: usize) -> String {
+    if page == 1 {
+        format!("{}/{}", dir, slugify(tag_name))
+    } else {
+        format!("{}/{}/{}", dir, slugify(tag_name), page)
+    }
+}
+
+/// 文章中出现的语言 (按名称排序)
+fn languages_of(pages: &[&PostMetadata]) -> Vec<String> {
+    let mut languages: Vec<String> = pages.iter().filter_map(|p| p.language.clone()).collect();
+    languages.sort();
+    languages.dedup();
+    languages
+}
+
+/// 同一 `translationKey` 的文章互为译文：为每篇文章列出其他语言版本
+fn translations_of(posts: &[PostMetadataWithContent]) -> Vec<Vec<Translation>> {
+    posts.iter()
+        .map(|post| {
+            let Some(key) = &post.meta.translation_key else { return Vec::new() };
+            posts.iter()
+                .filter(|other| other.meta.translation_key.as_ref() == Some(key) && other.meta.slug != post.meta.slug)
+                .filter_map(|other| {
+                    other.meta.language.clone().map(|language| Translation {
+                        language,
+                        title: other.meta.title.clone(),
+                        url: other.meta.url.clone(),
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// 没有封面的文章使用生成的分享卡片 `og/<slug>.png`；分享平台要求绝对地址，未设置 siteUrl 时退回相对地址
+fn og_image_url(config: &Config, has_cover: bool, slug: &str) -> Option<String> {
+    (config.generate_og_images && !has_cover).then(|| {
+        let path = format!("{}/{}.png", og::OG_DIR, slug);
+        match config.site_url() {
+            Some(site_url) => format!("{}/{}", site_url, path),
+            None => format!("{}/{}", config.post_root_path(), path),
+        }
+    })
+}
+
+/// 为设置了 `og_image` 的文章绘制分享卡片，写入 `public/og/<slug>.png`
+fn write_og_images(config: &Config, result: &BuildResult, output: &mut Output) -> Result<()> {
+    let font = config.og_image_font.as_deref()
+        .ok_or_else(|| anyhow::anyhow!("启用 generateOgImages 时需要在 ogImageFont 中指定字体文件"))?;
+    let cards = og::CardRenderer::load(font)?;
+    let dir = Path::new("public").join(og::OG_DIR);
+    for post in result.posts.iter().filter(|p| p.meta.og_image.is_some()) {
+        let png = cards.render(&post.meta.title, &result.site_meta.title)?;
+        output.write(dir.join(format!("{}.png", post.meta.slug)), png)?;
+    }
+    Ok(())
+}
+
+/// 渲染文章页 (各页面互不依赖，并行渲染后按原顺序写出)
+fn render_post_pages(tera: &tera::Tera, config: &Config, result: &BuildResult, output: &mut Output) -> Result<()> {
+    let rendered = render_parallel(config.render_workers(), &result.posts, |post| {
+        let context = PageContext {
+            site_meta: &result.site_meta,
+            post,
+            headings: &post.headings,
+            properties_table: &post.meta.properties_table,
+            breadcrumb_json_ld: config.site_url()
+                .filter(|_| config.breadcrumbs)
+                .map(|site_url| seo::breadcrumb_json_ld(site_url, &result.site_meta.title, &post.meta)),
+            canonical_url: config.site_url().map(|site_url| format!("{}/{}", site_url, post.meta.url)),
+            root_path: output::root_path(&config.post_output_path(&post.meta.slug)),
+        };
+        Ok(tera.render("post.html", &tera::Context::from_serialize(&context)?)?)
+    })?;
+    for (post, html) in result.posts.iter().zip(rendered) {
+        output.write(config.post_output_path(&post.meta.slug), html)?;
+    }
+    Ok(())
+}
+
+/// 各栏的 flex-basis 百分比：有比例时按比例分配，否则 (或比例不完整时) 平均分配
+fn column_basis(ratios: &[Option<f64>]) -> Vec<f64> {
+    let n = ratios.len() as f64;
+    let known: Option<Vec<f64>> = ratios.iter().map(|r| r.filter(|r| *r > 0.0)).collect();
+    match known {
+        Some(ratios) if !ratios.is_empty() => {
+            let total: f64 = ratios.iter().sum();
+            ratios.iter().map(|r| r / total * 100.0).collect()
+        }
+        _ => vec![100.0 / n; ratios.len()],
+    }
+}
+
+/// 取出公式中的 `\label{name}`，返回去掉标签后的公式与标签名
+fn take_equation_label(expression: &str) -> (String, Option<String>) {
+    let Some(start) = expression.find("\\label{") else {
+        return (expression.to_string(), None);
+    };
+    let rest = &expression[start + "\\label{".len()..];
+    let Some(end) = rest.find('}') else {
+        return (expression.to_string(), None);
+    };
+    let label = rest[..end].trim().to_string();
+    let stripped = format!("{}{}", &expression[..start], &rest[end + 1..]);
+    (stripped.trim().to_string(), Some(label).filter(|l| !l.is_empty()))
+}
+
+/// 将正文中的 `\eqref{name}` 替换为指向对应公式的编号链接；未知标签保持原样
+fn resolve_equation_refs(html: &str, labels: &HashMap<String, usize>) -> String {
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find("\\eqref{") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + "\\eqref{".len()..];
+        match after.find('}').and_then(|end| labels.get(after[..end].trim()).map(|n| (end, n))) {
+            Some((end, number)) => {
+                output.push_str(&format!("<a class=\"equation-ref\" href=\"#eq-{0}\">({0})</a>", number));
+                rest = &after[end + 1..];
+            }
+            None => {
+                output.push_str("\\eqref{");
+                rest = after;
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// `only` 是否指向该文章：与 slug 相同，或与页面 ID 相同 (忽略连字符与大小写)
+fn is_same_post(only: &str, page_id: &str, slug: &str) -> bool {
+    let normalize = |id: &str| id.replace('-', "").to_lowercase();
+    only == slug || normalize(only) == normalize(page_id)
+}
+
+/// 一个待渲染的输出文件：返回输出路径与内容
+type RenderJob<'a> = Box<dyn Fn() -> Result<(PathBuf, String)> + Sync + 'a>;
+
+/// 用 `workers` 个线程并行渲染互不依赖的页面，结果按输入顺序返回，保证输出与串行渲染完全一致。
+/// Tera 渲染只需要 `&Tera`，各线程共享同一个实例。`workers` 为 1 时在当前线程串行渲染
+fn render_parallel<T, R, F>(workers: usize, items: &[T], render: F) -> Result<Vec<R>>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> Result<R> + Sync,
+{
+    if workers <= 1 {
+        return items.iter().map(render).collect();
+    }
+    let chunk_size = items.len().div_ceil(workers).max(1);
+    let render = &render;
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(render).collect::<Result<Vec<_>>>()))
+            .collect();
+        let mut rendered = Vec::with_capacity(items.len());
+        for handle in handles {
+            rendered.extend(handle.join().map_err(|_| anyhow::anyhow!("渲染线程异常退出"))??);
+        }
+        Ok(rendered)
+    })
+}
+
+/// 按 `dateSource` 选出文章日期：`date` 属性 (未填写时为空)、创建时间或最后编辑时间
+fn post_date(source: DateSource, property: Option<String>, created_time: &str, last_edited_time: &str) -> String {
+    match source {
+        DateSource::Date => property.unwrap_or_default(),
+        // 页面时间戳只取日期部分，与 date 属性的格式一致
+        DateSource::Created => date_part(created_time),
+        DateSource::Edited => date_part(last_edited_time),
+    }
+}
+
+/// 由配置构造站点信息；标签页、系列页等在此基础上替换标题与文章列表。
+/// `now` 为构建开始的 Unix 时间戳，决定构建时间与构建 ID (时间戳的短哈希，供模板给资源地址附加 `?v=`)
+fn site_meta(config: &Config, pages: Vec<PostMetadata>, self_hosted_fonts: bool, now: u64) -> SiteMeta {
+    SiteMeta {
+        build_time: format_utc(now, &config.build_time_format),
+        build_id: text::short_hash(&now.to_string()),
+        title: config.site_title(),
+        icon_url: None,
+        pages,
+        font_css: self_hosted_fonts.then(|| fonts::FONTS_CSS.to_string()),
+        theme_color: config.theme_color.clone(),
+        color_scheme: config.color_scheme,
+        home_url: config.home_url().to_string(),
+        feeds: feed::feed_links(config),
+        favicons: Vec::new(),
+    }
+}
+
+/// 由 Notion 多选项构造标签。颜色以标签名为键只确定一次并记入 `colors`，
+/// 之后同名标签都使用该颜色，即使 Notion 在不同文章中返回的颜色不一致
+fn site_tag(config: &Config, colors: &mut HashMap<String, String>, name: &str, notion_color: &impl std::fmt::Debug) -> Tag {
+    Tag {
+        name: name.to_string(),
+        color: colors.entry(name.to_string())
+            .or_insert_with(|| config.tag_color(name, notion_color))
+            .clone(),
+        slug: slugify(name),
+        url: config.listing_url(&format!("tag/{}", slugify(name))),
+    }
+}
+
+/// 时间戳的日期部分 (`YYYY-MM-DD`)
+fn date_part(timestamp: &str) -> String {
+    timestamp.get(..10).unwrap_or(timestamp).to_string()
+}
+
+/// `<title>` 与 `<meta name="description">` 的建议长度 (字符数)
+const META_TITLE_CHARS: usize = 60;
+const META_DESCRIPTION_CHARS: usize = 155;
+
+/// 当前日期 (UTC)，格式为 `YYYY-MM-DD`
+pub(crate) fn today_utc() -> String {
+    format_utc(unix_now(), "%Y-%m-%d")
+}
+
+/// 当前的 Unix 时间戳 (秒)
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// 按格式输出 UTC 时间，支持 `%Y` `%m` `%d` `%H` `%M` `%S` 与 `%%`，其余字符原样输出
+fn format_utc(secs: u64, format: &str) -> String {
+    // 由 1970-01-01 起的天数换算公历日期 (Howard Hinnant 的 civil_from_days 算法)
+    let z = (secs / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    let seconds_of_day = secs % 86_400;
+
+    let mut out = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('Y') => out.push_str(&format!("{:04}", year)),
+            Some('m') => out.push_str(&format!("{:02}", month)),
+            Some('d') => out.push_str(&format!("{:02}", day)),
+            Some('H') => out.push_str(&format!("{:02}", seconds_of_day / 3_600)),
+            Some('M') => out.push_str(&format!("{:02}", seconds_of_day / 60 % 60)),
+            Some('S') => out.push_str(&format!("{:02}", seconds_of_day % 60)),
+            Some('%') => out.push('%'),
+            Some(other) => {
+                out.push('%');
+                out.push(other);
+            }
+            None => out.push('%'),
+        }
+    }
+    out
+}
+
+/// `today` 是否在发布窗口内。按天比较，起止日期均包含在内 (结束日期当天全天可见)
+fn in_publish_window(start: Option<&str>, end: Option<&str>, today: &str) -> bool {
+    let day = |d: &str| d.get(..10).unwrap_or(d).to_string();
+    start.is_none_or(|s| day(s).as_str() <= today) && end.is_none_or(|e| day(e).as_str() >= today)
+}
+
+/// 校验并规范化 `coverFocus`：`top` / `center` / `bottom` / `left` / `right` 的一到两个组合，
+/// 或 `x% y%` (0–100)。无效时返回 None
+fn cover_position(focus: &str) -> Option<String> {
+    let parts: Vec<String> = focus.split_whitespace().map(|p| p.to_lowercase()).collect();
+    if parts.is_empty() || parts.len() > 2 {
+        return None;
+    }
+    let keyword = |p: &str| matches!(p, "top" | "center" | "bottom" | "left" | "right");
+    let percent = |p: &str| {
+        p.strip_suffix('%')
+            .and_then(|n| n.parse::<f64>().ok())
+            .is_some_and(|n| (0.0..=100.0).contains(&n))
+    };
+    let valid = parts.iter().all(|p| keyword(p)) || parts.iter().all(|p| percent(p));
+    valid.then(|| parts.join(" "))
+}
+
+/// 将标签分为显示的标签与 `hiddenTags` 中的隐藏标签；隐藏标签保留在元数据中，但不出现在标签云、标签页与文章的标签列表中
+fn split_hidden_tags(config: &Config, tags: Vec<Tag>) -> (Vec<Tag>, Vec<Tag>) {
+    tags.into_iter().partition(|t| !config.is_hidden_tag(&t.name))
+}
+
+/// 按标签分组文章 (按标签名排序，保证每次构建输出一致)
+pub(crate) fn group_by_tag<'a>(posts: impl IntoIterator<Item = &'a PostMetadata>) -> BTreeMap<String, Vec<&'a PostMetadata>> {
+    let mut tags_map: BTreeMap<String, Vec<&PostMetadata>> = BTreeMap::new();
+    for post in posts {
+        for tag in &post.tags {
+            tags_map.entry(tag.name.clone())
+                .or_default()
+                .push(post);
+        }
+    }
+    tags_map
+}
+
+/// 主题页的分组：每个标签一组 (顺序与标签统计一致)，组内为带有该标签的列表文章
+fn topic_groups<'a>(result: &'a BuildResult, listed: &[&'a PostMetadata]) -> Vec<TopicGroup<'a>> {
+    let mut tags_map = group_by_tag(listed.iter().copied());
+    result.tags.iter()
+        .map(|tag| TopicGroup {
+            tag,
+            pages: tags_map.remove(&tag.name).unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// 数据库查询的过滤条件：配置了 `query.filterCheckbox` 时只取回勾选了该属性的页面，
+/// 否则使用总是成立的条件取回全部页面
+fn query_filter(query: &QueryConfig) -> Filter {
+    match &query.filter_checkbox {
+        Some(property) => Filter::checkbox_is_checked(property),
+        None => Filter::timestamp_is_not_empty(),
+    }
+}
+
+/// 数据库查询的排序条件 (`query.sortProperty`)
+fn query_sort(query: &QueryConfig) -> Option<Sort> {
+    let property = query.sort_property.as_deref()?;
+    Some(match query.sort_direction {
+        SortDirection::Ascending => Sort::asc(property),
+        SortDirection::Descending => Sort::desc(property),
+    })
+}
+
+/// 首页顺序：有 `order` 的在前并按其升序，`order` 相同或都没有时按日期降序
+fn compare_index_order(a: &PostMetadata, b: &PostMetadata) -> std::cmp::Ordering {
+    let by_order = match (a.order, b.order) {
+        (Some(x), Some(y)) => x.total_cmp(&y),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    };
+    by_order.then_with(|| b.date.cmp(&a.date))
+}
+
+/// 按系列分组文章，组内按日期升序排列
+fn group_by_series<'a>(posts: impl IntoIterator<Item = &'a PostMetadata>) -> BTreeMap<String, Vec<&'a PostMetadata>> {
+    let mut series_map: BTreeMap<String, Vec<&PostMetadata>> = BTreeMap::new();
+    for post in posts {
+        if let Some(name) = &post.series {
+            series_map.entry(name.clone())
+                .or_default()
+                .push(post);
+        }
+    }
+    for posts in series_map.values_mut() {
+        posts.sort_by(|a, b| a.date.cmp(&b.date));
+    }
+    series_map
+}
+
+/// 文章在所属系列中的位置及同系列文章
+fn series_info(meta: &PostMetadata, series_map: &BTreeMap<String, Vec<&PostMetadata>>) -> Option<SeriesInfo> {
+    let name = meta.series.as_ref()?;
+    let siblings = series_map.get(name)?;
+    let position = siblings.iter().position(|p| p.slug == meta.slug).unwrap_or(0) + 1;
+    Some(SeriesInfo {
+        name: name.clone(),
+        slug: slugify(name),
+        position,
+        total: siblings.len(),
+        posts: siblings.iter().map(|p| SeriesEntry {
+            title: p.title.clone(),
+            url: p.url.clone(),
+            current: p.slug == meta.slug,
+        }).collect(),
+    })
+}
+
+/// 计算标签统计信息
+pub(crate) fn tag_stats(config: &Config, tags_map: &BTreeMap<String, Vec<&PostMetadata>>) -> Vec<TagStat> {
+    let mut all_tags: Vec<TagStat> = Vec::new();
+    for (tag_name, posts) in tags_map {
+        // 标签颜色在构建时已统一，任取一篇文章中的即可
+        let color = posts.first()
+            .and_then(|p| p.tags.iter().find(|t| t.name == *tag_name))
+            .map(|t| t.color.clone())
+            .unwrap_or_else(|| "default".to_string());
+        let meta = config.tag_meta.get(tag_name).cloned().unwrap_or_default();
+
+        all_tags.push(TagStat {
+            name: tag_name.clone(),
+            slug: slugify(tag_name),
+            url: config.listing_url(&format!("tag/{}", slugify(tag_name))),
+            count: posts.len(),
+            color,
+            description: meta.description,
+            cover: meta.cover,
+        });
+    }
+    // 按数量降序排序，数量相同时按名称排序
+    all_tags.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    all_tags
+}
+
+/// 抓取页面内容时共享的上下文
+struct Fetcher<'a> {
+    client: &'a NotionApi,
+    renderer: &'a HtmlRenderer,
+    /// 子 Block 的最大递归深度
+    max_depth: usize,
+    /// 每篇文章最多渲染的 Block 数；`block_count` 为当前文章已处理的数量，超出后不再抓取
+    max_blocks: Option<usize>,
+    block_count: Cell<usize>,
+    /// 手动摘要分隔标记：`divider` 表示第一个分割线，其他值表示内容与之相同的段落或 Callout
+    excerpt_delimiter: Option<&'a str>,
+    /// 有子内容的列表项渲染为可折叠的 <details>
+    toggle_list_items: bool,
+    /// 当前页面已分配的标题锚点 (slug -> 出现次数)，每篇文章开始时清空
+    anchors: RefCell<HashMap<String, usize>>,
+    /// 分栏的布局样式 (间距与堆叠断点)，每篇文章在第一个分栏前输出一次
+    column_style: String,
+    column_style_written: Cell<bool>,
+    /// 收集原始 Block 数据，以 `post.blocks` 提供给模板
+    include_raw_blocks: bool,
+    /// 是否为行间公式编号；编号与 `\label{...}` 标签按文章重新计数
+    number_equations: bool,
+    equation_labels: RefCell<HashMap<String, usize>>,
+    equation_count: Cell<usize>,
+    /// 当前所在的有序列表嵌套层数，决定嵌套有序列表的编号样式
+    numbered_level: Cell<usize>,
+    /// 在标题旁输出 `#` 锚点链接
+    heading_anchors: bool,
+    /// 把引用中的出处段落渲染为 `<cite>`
+    quote_citations: bool,
+    /// 正在渲染其子 Block 的引用 ID，其直接子段落可能是出处
+    quote_parent: RefCell<Option<String>>,
+    /// 展开模板按钮中的模板内容，否则省略模板按钮
+    inline_templates: bool,
+    /// 严格模式：遇到不支持的 Block 时构建失败
+    strict: bool,
+    /// 非严格模式下省略的不支持 Block (类型名 -> 次数)
+    unsupported: RefCell<BTreeMap<String, usize>>,
+    /// 本地的 Block 数据 (Block ID -> 子 Block 列表)；设置后不再请求 Notion
+    fixture: Option<HashMap<String, serde_json::Value>>,
+    /// 探测地址没有扩展名的文件的类型 (`sniffFileTypes`)
+    sniffer: Option<reqwest::Client>,
+}
+
+impl<'a> Fetcher<'a> {
+    fn new(config: &'a Config, client: &'a NotionApi, renderer: &'a HtmlRenderer) -> Self {
+        Fetcher {
+            client,
+            renderer,
+            max_depth: config.max_block_depth,
+            max_blocks: config.max_blocks_per_page,
+            block_count: Cell::new(0),
+            excerpt_delimiter: config.excerpt_delimiter.as_deref(),
+            toggle_list_items: config.toggle_list_items,
+            anchors: RefCell::new(HashMap::new()),
+            column_style: format!(
+                "<style>.column-list{{display:flex;gap:{};}}.column{{min-width:0;}}@media (max-width:{}px){{.column-list{{flex-direction:column;}}}}</style>",
+                config.column_gap, config.column_breakpoint
+            ),
+            column_style_written: Cell::new(false),
+            include_raw_blocks: config.include_raw_blocks,
+            number_equations: config.number_equations,
+            equation_labels: RefCell::new(HashMap::new()),
+            equation_count: Cell::new(0),
+            numbered_level: Cell::new(0),
+            heading_anchors: config.heading_anchors,
+            quote_citations: config.quote_citations,
+            quote_parent: RefCell::new(None),
+            inline_templates: config.template_blocks == TemplateBlocks::Inline,
+            strict: config.strict,
+            unsupported: RefCell::new(BTreeMap::new()),
+            fixture: None,
+            sniffer: config.sniff_file_types.then(reqwest::Client::new),
+        }
+    }
+
+    /// 请求文件地址的响应头，取得 Content-Type。先发 HEAD 请求；只为 GET 签名的地址 (如 S3) 会拒绝 HEAD，
+    /// 此时改为只请求第一个字节的 GET。任何失败都返回 None，按原方式渲染
+    async fn sniff_content_type(&self, url: &str) -> Option<String> {
+        let http = self.sniffer.as_ref()?;
+        let response = match http.head(url).send().await {
+            Ok(response) if response.status().is_success() => response,
+            _ => http.get(url).header(reqwest::header::RANGE, "bytes=0-0").send().await.ok()?,
+        };
+        if !response.status().is_success() {
+            return None;
+        }
+        response.headers()
+            .get(reqwest::header::CONTENT_TYPE)?
+            .to_str()
+            .ok()
+            .map(|t| t.to_string())
+    }
+
+    /// 抓取并渲染一篇文章的正文；锚点、公式编号等按文章计数的状态先清空
+    async fn fetch_page(&self, page_id: &str) -> Result<PageContent> {
+        self.anchors.borrow_mut().clear();
+        self.column_style_written.set(false);
+        self.equation_labels.borrow_mut().clear();
+        self.equation_count.set(0);
+        self.numbered_level.set(0);
+        self.block_count.set(0);
+        let mut page = get_page_html(self, page_id, 0).await?;
+        if self.number_equations {
+            page.html = resolve_equation_refs(&page.html, &self.equation_labels.borrow());
+        }
+        if let Some(limit) = self.max_blocks.filter(|_| self.blocks_exhausted()) {
+            println!(">>> 警告: 页面 {} 的 Block 超过 {} 个，已截断", page_id, limit);
+            page.html.push_str("<p class=\"truncated-notice\">（内容过长，其余部分已省略）</p>");
+        }
+        Ok(page)
+    }
+
+    /// 当前文章处理的 Block 数是否已超过 `maxBlocksPerPage`
+    fn blocks_exhausted(&self) -> bool {
+        self.max_blocks.is_some_and(|limit| self.block_count.get() > limit)
+    }
+
+    /// 记录处理了一个 Block，超过 `maxBlocksPerPage` 时返回 false
+    fn take_block(&self) -> bool {
+        self.block_count.set(self.block_count.get() + 1);
+        !self.blocks_exhausted()
+    }
+
+    /// 取回子 Block：有本地数据时从中读取 (没有记录的 Block 视为没有子 Block)，否则请求 Notion。
+    /// 设置了 `maxBlocksPerPage` 时，取够剩余的额度 (多取一个以判断是否超出) 后不再翻页
+    async fn block_children(&self, block_id: &str) -> Result<Vec<BlockResponse>> {
+        match &self.fixture {
+            Some(fixture) => match fixture.get(block_id) {
+                Some(blocks) => serde_json::from_value(blocks.clone())
+                    .map_err(|e| anyhow::anyhow!("Block {} 的本地数据格式错误: {}", block_id, e)),
+                None => Ok(Vec::new()),
+            },
+            None => {
+                let budget = self.max_blocks.map(|limit| (limit + 1).saturating_sub(self.block_count.get()));
+                fetch_block_children(self.client, block_id, budget).await
+            }
+        }
+    }
+
+    /// notionrs 不认识「链接到页面」Block，反序列化为 `Unsupported`。取回该 Block 的原始数据
+    /// (有本地数据时从中查找)，是「链接到页面」时返回目标页面或数据库的 ID
+    async fn link_to_page_target(&self, block_id: &str) -> Option<String> {
+        let raw = match &self.fixture {
+            Some(fixture) => fixture.values()
+                .filter_map(|blocks| blocks.as_array())
+                .flatten()
+                .find(|block| block["id"].as_str() == Some(block_id))
+                .cloned()?,
+            None => self.client.retrieve_block(block_id).await.ok()?,
+        };
+        link_to_page_id(&raw)
+    }
+
+    /// 为标题分配页面内唯一的锚点：重复的标题依次追加 `-1`、`-2`，
+    /// 跳过已被其他标题占用的 id (如标题本身就是 "Intro-1")
+    fn heading_anchor(&self, text: &str) -> String {
+        let slug = escape_html(&slugify(text));
+        let slug = if slug.is_empty() { "section".to_string() } else { slug };
+        let mut anchors = self.anchors.borrow_mut();
+        let mut count = anchors.get(&slug).copied().unwrap_or(0);
+        let id = loop {
+            let candidate = if count == 0 { slug.clone() } else { format!("{}-{}", slug, count) };
+            count += 1;
+            if !anchors.contains_key(&candidate) {
+                break candidate;
+            }
+        };
+        anchors.insert(slug, count);
+        anchors.entry(id.clone()).or_insert(1);
+        id
+    }
+
+    fn is_excerpt_marker(&self, block: &Block) -> bool {
+        match (self.excerpt_delimiter, block) {
+            // `divider` 只匹配分割线，不匹配内容恰好为 "divider" 的段落
+            (Some("divider"), _) => matches!(block, Block::Divider { .. }),
+            (Some(marker), Block::Paragraph { .. } | Block::Callout { .. }) => block.to_string().trim() == marker,
+            _ => false,
+        }
+    }
+}
+
+/// 一个页面 (或一组子 Block) 的抓取结果
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PageContent {
+    html: String,
+    /// 纯文本，用于预览与字数统计
+    plain_text: String,
+    /// Markdown 正文，用于导出
+    markdown: String,
+    /// 手动摘要 (仅顶层页面，遇到分隔标记时记录)
+    excerpt: Option<Excerpt>,
+    /// 标题 (含子 Block 中的标题)，按出现顺序
+    headings: Vec<Heading>,
+    /// To-do 完成情况 (含子 Block 中的 To-do)
+    todos: TodoProgress,
+    /// 原始 Block 数据 (仅在开启 `includeRawBlocks` 时收集)，子 Block 位于 `children`
+    blocks: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Excerpt {
+    html: String,
+    plain_text: String,
+}
+
+impl PageContent {
+    /// 合并子 Block 的标题与 To-do 统计
+    fn merge_stats(&mut self, children: &mut PageContent) {
+        self.headings.append(&mut children.headings);
+        self.todos.completed += children.todos.completed;
+        self.todos.total += children.todos.total;
+        self.attach_raw_children(std::mem::take(&mut children.blocks));
+    }
+
+    /// 把子 Block 的原始数据挂到最近一个 Block 的 `children` 下
+    fn attach_raw_children(&mut self, children: Vec<serde_json::Value>) {
+        if children.is_empty() {
+            return;
+        }
+        if let Some(serde_json::Value::Object(parent)) = self.blocks.last_mut() {
+            let list = parent.entry("children").or_insert_with(|| serde_json::Value::Array(Vec::new()));
+            if let serde_json::Value::Array(list) = list {
+                list.extend(children);
+            }
+        }
+    }
+
+    /// 追加一段 Markdown，Block 之间空一行，连续的列表项之间不空行
+    fn push_markdown(&mut self, md: &str, tight: bool) {
+        if !self.markdown.is_empty() {
+            self.markdown.push_str(if tight { "\n" } else { "\n\n" });
+        }
+        self.markdown.push_str(md);
+    }
+}
+
+async fn get_page_html(fetcher: &Fetcher<'_>, page_id: &str, depth: usize) -> Result<PageContent> {
+    // 防止过深或循环引用 (如同步块) 的结构无限递归
+    if depth > fetcher.max_depth {
+        println!(">>> 警告: Block {} 的嵌套深度超过 {}，已截断", page_id, fetcher.max_depth);
+        return Ok(PageContent {
+            html: "<p class=\"truncated-notice\">（内容嵌套层级过深，已省略）</p>".to_string(),
+            ..Default::default()
+        });
+    }
+
+    let renderer = fetcher.renderer;
+    let mut content = PageContent::default();
+    // 超过 Block 数上限后，其余的子 Block 不再请求
+    if fetcher.blocks_exhausted() {
+        return Ok(content);
+    }
+    let blocks = fetcher.block_children(page_id).await?;
+
+    let mut current_list: Option<ListKind> = None;
+    for block_res in blocks {
+        if !fetcher.take_block() {
+            break;
+        }
+        if fetcher.include_raw_blocks {
+            content.blocks.push(raw_block(&block_res)?);
+        }
+
+        // 模板按钮在静态站点上没有意义：默认省略；`inline` 时结束当前列表并展开其模板内容
+        if let Block::Template { .. } = &block_res.block {
+            if fetcher.inline_templates && block_res.has_children {
+                if let Some(kind) = current_list.take() {
+                    content.html.push_str(kind.close_tag());
+                    content.html.push('\n');
+                }
+                let mut children = Box::pin(get_page_html(fetcher, &block_res.id, depth + 1)).await?;
+                content.html.push_str(&children.html);
+                content.plain_text.push_str(&children.plain_text);
+                content.merge_stats(&mut children);
+                content.push_markdown(&children.markdown, false);
+            }
+            continue;
+        }
+
+        let mut block_html = renderer.render_block(&block_res.block);
+        // 「链接到页面」：notionrs 无法解析，按原始数据渲染为页面卡片
+        if let Block::Unsupported = block_res.block
+            && let Some(target) = fetcher.link_to_page_target(&block_res.id).await
+        {
+            block_html = renderer.render_link_to_page(&target);
+        }
+        // 不支持的 Block：严格模式下构建失败，否则保留注释并计数 (表格与分栏在下方单独处理)
+        if block_html.starts_with(UNSUPPORTED_BLOCK) && !matches!(block_res.block, Block::Table { .. } | Block::ColumnList { .. }) {
+            let kind = block_type_name(&block_res.block);
+            if fetcher.strict {
+                anyhow::bail!("Block {} 的类型 {} 不受支持 (--strict)", block_res.id, kind);
+            }
+            *fetcher.unsupported.borrow_mut().entry(kind).or_insert(0) += 1;
+        }
+
+        // 地址没有扩展名的文件 (常见于 Notion 上传的文件)：按响应头中的类型决定渲染为图片还是下载链接
+        if let Block::File { file } = &block_res.block {
+            let url = file.to_string();
+            if !url_has_extension(&url)
+                && let Some(content_type) = fetcher.sniff_content_type(&url).await
+            {
+                block_html = renderer.render_file_with_type(&url, &content_type);
+            }
+        }
+
+        // 引用的出处：直接子段落以破折号开头且较短时渲染为 <cite>
+        if let Block::Paragraph { paragraph } = &block_res.block
+            && fetcher.quote_parent.borrow().as_deref() == Some(page_id)
+            && is_attribution(&block_res.block.to_string())
+        {
+            block_html = format!("<cite>{}</cite>", renderer.render_rich_text(&paragraph.rich_text));
+        }
+
+        if let Block::ToDo { to_do } = &block_res.block {
+            content.todos.total += 1;
+            content.todos.completed += usize::from(to_do.checked);
+        }
+
+        // 行间公式编号：`\label{name}` 从公式中移除，记录下来供 `\eqref{name}` 引用
+        if let (true, Block::Equation { equation }) = (fetcher.number_equations, &block_res.block) {
+            let number = fetcher.equation_count.get() + 1;
+            fetcher.equation_count.set(number);
+            let (expression, label) = take_equation_label(&equation.expression);
+            if let Some(label) = label {
+                fetcher.equation_labels.borrow_mut().insert(label, number);
+            }
+            block_html = renderer.render_numbered_equation(&expression, number, &format!("eq-{}", number));
+        }
+
+        // 标题：分配锚点并记录，供目录使用
+        let mut toggle_heading = None;
+        if let Some((level, text)) = renderer.heading(&block_res.block) {
+            let id = fetcher.heading_anchor(&text);
+            // 可折叠标题的 <details> 使用基于锚点的 id (锚点已按页面去重)，供脚本记住展开状态
+            if block_res.has_children && is_toggleable_heading(&block_res.block) {
+                toggle_heading = Some(format!("toggle-{}", id));
+            }
+            block_html = block_html.replacen(&format!("<h{} ", level), &format!("<h{} id=\"{}\" ", level, id), 1);
+            // 锚点链接与目录使用同一个 id
+            if fetcher.heading_anchors {
+                let close = format!("</h{}>", level);
+                let anchor = format!("<a class=\"heading-anchor\" href=\"#{}\" aria-label=\"链接到本节\">#</a>{}", id, close);
+                block_html = block_html.replacen(&close, &anchor, 1);
+            }
+            content.headings.push(Heading { level, text, id });
+        }
+
+        // 连续的列表项包裹在同一个 <ul>/<ol> 中
+        let list_kind = renderer.list_kind(&block_res.block);
+        let continues_list = list_kind.is_some() && list_kind == current_list;
+        if list_kind != current_list {
+            if let Some(kind) = current_list {
+                content.html.push_str(kind.close_tag());
+                content.html.push('\n');
+            }
+            if let Some(kind) = list_kind {
+                content.html.push_str(kind.open_tag(fetcher.numbered_level.get()));
+            }
+            current_list = list_kind;
+        }
+
+        // 摘要分隔标记：记录此前的内容；文本标记本身不输出
+        if depth == 0 && content.excerpt.is_none() && fetcher.is_excerpt_marker(&block_res.block) {
+            content.excerpt = Some(Excerpt {
+                html: content.html.clone(),
+                plain_text: content.plain_text.clone(),
+            });
+            if !matches!(block_res.block, Block::Divider { .. }) {
+                content.push_markdown("<!--more-->", false);
+                continue;
+            }
+        }
+
+        if !matches!(block_res.block, Block::Table { .. } | Block::ColumnList { .. }) {
+            content.push_markdown(&MarkdownRenderer::render_block(&block_res.block), continues_list);
+        }
+
+        // 特殊处理 Toggle：我们需要把子内容放进 details 标签内部
+        if let Block::Toggle { .. } = &block_res.block {
+             // 移除末尾的 </details>
+             let open_tag = block_html.strip_suffix("</details>").unwrap_or(&block_html);
+             content.html.push_str(open_tag);
+
+             if block_res.has_children {
+                 let mut children = Box::pin(get_page_html(fetcher, &block_res.id, depth + 1)).await?;
+                 content.html.push_str("<div class=\"details-content\" style=\"padding-left: 1.2em;\">");
+                 content.html.push_str(&children.html);
+                 content.html.push_str("</div>");
+                 content.plain_text.push_str(&children.plain_text);
+                 content.merge_stats(&mut children);
+                 content.push_markdown(&children.markdown, false);
+             }
+             content.html.push_str("</details>");
+        } else if let Block::Table { table } = &block_res.block {
+            // 表格：行是表格的子 Block，需要全部取回后一起渲染
+            let row_blocks = fetcher.block_children(&block_res.id).await?;
+            if fetcher.include_raw_blocks {
+                let raw_rows = row_blocks.iter().map(raw_block).collect::<Result<Vec<_>>>()?;
+                content.attach_raw_children(raw_rows);
+            }
+            let rows: Vec<Vec<Vec<RichText>>> = row_blocks
+                .into_iter()
+                .filter_map(|row| match row.block {
+                    Block::TableRow { table_row } => Some(table_row.cells),
+                    _ => None,
+                })
+                .collect();
+            let width = table.table_width as usize;
+            content.html.push_str(&renderer.render_table(&rows, width, table.has_column_header, table.has_row_header));
+            content.html.push('\n');
+            for cell in rows.iter().flatten() {
+                content.plain_text.push_str(&cell.iter().map(|rt| rt.to_string()).collect::<String>());
+                content.plain_text.push(' ');
+            }
+            content.push_markdown(&MarkdownRenderer::render_table(&rows, width, table.has_column_header), false);
+            continue;
+        } else if let Block::ColumnList { .. } = &block_res.block {
+            // 分栏：子 Block 为各栏，栏宽按 Notion 中保存的比例分配
+            let columns = fetcher.block_children(&block_res.id).await?;
+            let ratios: Vec<Option<f64>> = columns.iter()
+                .map(|c| match &c.block {
+                    Block::Column { column } => Some(column.width_ratio),
+                    _ => None,
+                })
+                .collect();
+
+            if !fetcher.column_style_written.replace(true) {
+                content.html.push_str(&fetcher.column_style);
+            }
+            content.html.push_str("<div class=\"column-list\">");
+            for (column, basis) in columns.iter().zip(column_basis(&ratios)) {
+                content.html.push_str(&format!("<div class=\"column\" style=\"flex: 1 1 {:.4}%;\">", basis));
+                if column.has_children {
+                    let mut children = Box::pin(get_page_html(fetcher, &column.id, depth + 1)).await?;
+                    content.html.push_str(&children.html);
+                    content.plain_text.push_str(&children.plain_text);
+                    content.merge_stats(&mut children);
+                    content.push_markdown(&children.markdown, false);
+                }
+                content.html.push_str("</div>");
+            }
+            content.html.push_str("</div>\n");
+            continue;
+        } else if list_kind.is_some() {
+            // 列表项：子内容放进 <li> 内部，保证嵌套列表的语义
+            let open_tag = block_html.strip_suffix("</li>").unwrap_or(&block_html);
+            let collapsible = block_res.has_children && fetcher.toggle_list_items;
+            if collapsible {
+                // 可折叠列表项：<li><details><summary>文本</summary>子内容</details></li>
+                let split = open_tag.find('>').map_or(0, |i| i + 1);
+                let (li_tag, text) = open_tag.split_at(split);
+                content.html.push_str(li_tag);
+                content.html.push_str("<details><summary>");
+                content.html.push_str(text);
+                content.html.push_str("</summary>");
+            } else {
+                content.html.push_str(open_tag);
+            }
+
+            content.plain_text.push_str(&block_res.block.to_string());
+            content.plain_text.push(' ');
+
+            if block_res.has_children {
+                // 有序列表项的子列表嵌套一层，编号样式随之切换
+                let numbered = list_kind == Some(ListKind::Numbered);
+                if numbered {
+                    fetcher.numbered_level.set(fetcher.numbered_level.get() + 1);
+                }
+                let children = Box::pin(get_page_html(fetcher, &block_res.id, depth + 1)).await;
+                if numbered {
+                    fetcher.numbered_level.set(fetcher.numbered_level.get() - 1);
+                }
+                let mut children = children?;
+                content.html.push_str(&children.html);
+                content.plain_text.push_str(&children.plain_text);
+                content.merge_stats(&mut children);
+                content.push_markdown(&MarkdownRenderer::indent(&children.markdown, "    "), true);
+            }
+            if collapsible {
+                content.html.push_str("</details>");
+            }
+            content.html.push_str("</li>\n");
+        } else if let (true, Block::Quote { .. }) = (block_res.has_children, &block_res.block) {
+            // 有子内容的引用：子内容 (代码块、公式等) 放进 <blockquote> 内部；开启 quoteCitations 时出处段落渲染为 <cite>
+            let open_tag = block_html.strip_suffix("</blockquote>").unwrap_or(&block_html);
+            content.html.push_str(open_tag);
+            content.plain_text.push_str(&block_res.block.to_string());
+            content.plain_text.push(' ');
+
+            let parent = fetcher.quote_citations.then(|| block_res.id.to_string());
+            let previous = fetcher.quote_parent.replace(parent);
+            let children = Box::pin(get_page_html(fetcher, &block_res.id, depth + 1)).await;
+            fetcher.quote_parent.replace(previous);
+            let mut children = children?;
+            content.html.push_str(&children.html);
+            content.html.push_str("</blockquote>\n");
+            content.plain_text.push_str(&children.plain_text);
+            content.merge_stats(&mut children);
+            content.push_markdown(&children.markdown, false);
+        } else if let (true, Block::Callout { .. }) = (block_res.has_children, &block_res.block) {
+            // 有子内容的 Callout：子内容放进 .callout-text 内部，与 Notion 一样显示在框内
+            let open_tag = block_html.strip_suffix("</div></div>").unwrap_or(&block_html);
+            content.html.push_str(&open_tag.replacen("class=\"callout ", "class=\"callout has-children ", 1));
+            content.plain_text.push_str(&block_res.block.to_string());
+            content.plain_text.push(' ');
+
+            let mut children = Box::pin(get_page_html(fetcher, &block_res.id, depth + 1)).await?;
+            content.html.push_str("<div class=\"callout-children\">");
+            content.html.push_str(&children.html);
+            content.html.push_str("</div></div></div>\n");
+            content.plain_text.push_str(&children.plain_text);
+            content.merge_stats(&mut children);
+            content.push_markdown(&children.markdown, false);
+        } else if let Some(details_id) = toggle_heading {
+            // 可折叠标题：标题放进 <summary>，子内容默认收起
+            content.html.push_str(&format!("<details class=\"toggle-heading\" id=\"{}\"><summary>", details_id));
+            content.html.push_str(&block_html);
+            content.html.push_str("</summary>");
+            content.plain_text.push_str(&block_res.block.to_string());
+            content.plain_text.push(' ');
+
+            let mut children = Box::pin(get_page_html(fetcher, &block_res.id, depth + 1)).await?;
+            content.html.push_str("<div class=\"details-content\">");
+            content.html.push_str(&children.html);
+            content.html.push_str("</div></details>\n");
+            content.plain_text.push_str(&children.plain_text);
+            content.merge_stats(&mut children);
+            content.push_markdown(&children.markdown, false);
+        } else {
+            // 普通 Block
+            content.html.push_str(&block_html);
+            content.html.push('\n');
+
+            // 提取纯文本，用于预览与字数统计
+            content.plain_text.push_str(&block_res.block.to_string());
+            content.plain_text.push(' ');
+
+            if block_res.has_children {
+                let mut children = Box::pin(get_page_html(fetcher, &block_res.id, depth + 1)).await?;
+                content.html.push_str("<div style=\"margin-left: 20px;\">");
+                content.html.push_str(&children.html);
+                content.html.push_str("</div>");
+                content.plain_text.push_str(&children.plain_text);
+                content.merge_stats(&mut children);
+                content.push_markdown(&children.markdown, false);
+            }
+        }
+    }
+    if let Some(kind) = current_list {
+        content.html.push_str(kind.close_tag());
+        content.html.push('\n');
+    }
+    Ok(content)
+}
+
+/// 引用中的出处行：以破折号开头 (`—`、`―`、`--`)，且不超过 100 个字符
+fn is_attribution(text: &str) -> bool {
+    let text = text.trim();
+    let dashed = text.starts_with(['—', '―']) || text.starts_with("--");
+    dashed && text.chars().count() <= 100
+}
+
+/// 标题是否在 Notion 中设为可折叠 (Toggle heading)
+fn is_toggleable_heading(block: &Block) -> bool {
+    match block {
+        Block::Heading1 { heading_1 } => heading_1.is_toggleable,
+        Block::Heading2 { heading_2 } => heading_2.is_toggleable,
+        Block::Heading3 { heading_3 } => heading_3.is_toggleable,
+        _ => false,
+    }
+}
+
+/// Block 的原始数据：Notion 返回的 Block 结构，附加其 `id`
+fn raw_block(block_res: &BlockResponse) -> Result<serde_json::Value> {
+    let mut value = serde_json::to_value(&block_res.block)?;
+    if let serde_json::Value::Object(map) = &mut value {
+        map.insert("id".to_string(), serde_json::Value::String(block_res.id.to_string()));
+    }
+    Ok(value)
+}
+
+/// 页面上未解决的评论数 (Notion 的评论接口只返回未解决的评论)，按 `next_cursor` 翻页计数
+async fn fetch_comment_count(client: &NotionApi, page_id: &str) -> Result<usize> {
+    let mut count = 0;
+    let mut cursor: Option<String> = None;
+    loop {
+        let response = retry::with_retry("获取评论", || client.list_comments(page_id, cursor.as_deref())).await?;
+        count += response["results"].as_array().map_or(0, Vec::len);
+        match response["next_cursor"].as_str() {
+            Some(next) if response["has_more"].as_bool() != Some(false) => cursor = Some(next.to_string()),
+            _ => break,
+        }
+    }
+    Ok(count)
+}
+
+/// 评论接口的错误是否由权限不足引起 (Integration 没有读取评论的权限)；此时之后的文章也无需再请求
+fn is_comment_permission_error(error: &anyhow::Error) -> bool {
+    let message = error.to_string();
+    ["HTTP 401", "HTTP 403", "restricted_resource"].iter().any(|s| message.contains(s))
+}
+
+/// 原始 Block 数据为「链接到页面」时，取出目标页面 (或数据库) 的 ID
+fn link_to_page_id(raw: &serde_json::Value) -> Option<String> {
+    if raw["type"].as_str() != Some("link_to_page") {
+        return None;
+    }
+    let link = &raw["link_to_page"];
+    ["page_id", "database_id", "data_source_id"]
+        .iter()
+        .find_map(|key| link[key].as_str())
+        .map(str::to_string)
+}
+
+/// 取回一个 Block 的全部子 Block (最多约 `budget` 个)。
+/// 接口每次最多返回 100 个，按 `next_cursor` 翻页直到取完，避免长页面或大表格被截断
+async fn fetch_block_children(client: &NotionApi, block_id: &str, budget: Option<usize>) -> Result<Vec<BlockResponse>> {
+    collect_pages(budget, |cursor| async move {
+        let response = client.get_block_children(block_id, cursor.as_deref()).await?;
+        Ok((response.results, response.next_cursor))
+    })
+    .await
+}
+
+/// 打开构建日志；dry-run 不写入任何文件，也不记录构建日志
+fn open_journal(config: &Config, resume: bool) -> Result<Option<Journal<PageContent>>> {
+    if config.dry_run {
+        return Ok(None);
+    }
+    Journal::open(resume).map(Some)
+}
+
+/// 按 `next_cursor` 依次请求每一页并拼接结果；`fetch_page` 收到上一页返回的游标 (第一页为 None)。
+/// 已取得 `limit` 个结果时不再请求下一页
+async fn collect_pages<T, F, Fut>(limit: Option<usize>, mut fetch_page: F) -> Result<Vec<T>>
+where
+    F: FnMut(Option<String>) -> Fut,
+    Fut: std::future::Future<Output = Result<(Vec<T>, Option<String>)>>,
+{
+    let mut items = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let (page, next) = fetch_page(cursor.take()).await?;
+        items.extend(page);
+        match next {
+            Some(next) if limit.is_none_or(|limit| items.len() < limit) => cursor = Some(next),
+            _ => break,
+        }
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, MockServer};
+    use serde_json::json;
+
+    #[test]
+    fn tag_stats_carry_description_and_cover_from_tag_meta() {
+        let config = test_support::config(json!({
+            "tagMeta": { "Rust": { "description": "Systems programming", "cover": "rust.png", "color": "red" } }
+        }));
+        let mut a = test_support::meta("a", "2024-01-01");
+        a.tags = vec![test_support::tag("Rust"), test_support::tag("Go")];
+        let stats = tag_stats(&config, &group_by_tag(&[a]));
+        let rust = stats.iter().find(|t| t.name == "Rust").unwrap();
+        assert_eq!(rust.description.as_deref(), Some("Systems programming"));
+        assert_eq!(rust.cover.as_deref(), Some("rust.png"));
+        let go = stats.iter().find(|t| t.name == "Go").unwrap();
+        assert_eq!(go.description, None);
+        assert_eq!(go.cover, None);
+        assert_eq!(config.tag_color("Rust", &"Blue"), "red");
+    }
+
+    /// 由本地模拟的 Notion 服务提供数据库与正文，检查库接口 `Site::build` 的统计结果
+    #[tokio::test]
+    async fn build_counts_posts_and_tags_for_library_use() {
+        let checkbox = |checked: bool| json!({ "type": "checkbox", "checkbox": checked });
+        let pages = vec![
+            test_support::page("page-a", "A", &["Rust", "Web"], json!({})),
+            test_support::page("page-b", "B", &["Rust"], json!({})),
+            test_support::page("page-c", "C", &["Draft"], json!({ "inList": checkbox(false) })),
+            test_support::page("page-d", "D", &["Rust"], json!({ "publish": checkbox(false) })),
+        ];
+        let server = test_support::notion_site(pages, vec![test_support::paragraph("p1", "Body text")]).await;
+        let config = test_support::config(json!({}));
+
+        let result = Site::build_from(&server.url, &config).await.unwrap();
+
+        let slugs: Vec<&str> = result.posts.iter().map(|p| p.meta.slug.as_str()).collect();
+        assert_eq!(slugs, ["A", "B", "C"]);
+        assert_eq!(result.listed_posts().len(), 2);
+        assert_eq!(result.site_meta.pages.len(), 4);
+        assert!(result.posts[0].content.contains("Body text"), "{}", result.posts[0].content);
+        assert!(result.posts[0].meta.preview.contains("Body text"), "{}", result.posts[0].meta.preview);
+        let counts: Vec<(&str, usize)> = result.tags.iter().map(|t| (t.name.as_str(), t.count)).collect();
+        assert_eq!(counts, [("Rust", 2), ("Web", 1)]);
+
+        // 未发布的文章不抓取正文
+        let requests = server.requests();
+        assert!(requests[0].starts_with("GET /data_sources/0123456789abcdef0123456789abcdef "), "{}", requests[0]);
+        assert!(requests[1].starts_with("POST /data_sources/0123456789abcdef0123456789abcdef/query"), "{}", requests[1]);
+        assert!(requests.iter().all(|r| !r.contains("/blocks/page-d/")), "{:?}", requests);
+        assert!(requests.iter().all(|r| r.contains("authorization: Bearer secret_test")), "{:?}", requests);
+    }
+
+    #[tokio::test]
+    async fn series_posts_are_ordered_by_date_and_linked() {
+        let dated = |date: &str, series: Option<&str>| {
+            json!({
+                "date": { "type": "date", "date": { "start": date, "end": null, "time_zone": null } },
+                "series": { "type": "select", "select": series.map(|name| json!({ "name": name, "color": "default" })) },
+            })
+        };
+        let pages = vec![
+            test_support::page("page-2", "Part 2", &[], dated("2024-02-01", Some("Intro"))),
+            test_support::page("page-1", "Part 1", &[], dated("2024-01-01", Some("Intro"))),
+            test_support::page("page-3", "Other", &[], dated("2024-03-01", None)),
+        ];
+        let server = test_support::notion_site(pages, vec![]).await;
+
+        let result = Site::build_from(&server.url, &test_support::config(json!({}))).await.unwrap();
+
+        let info = result.posts[0].series.as_ref().expect("Part 2 应属于系列");
+        assert_eq!((info.position, info.total), (2, 2));
+        assert_eq!(info.slug, "intro");
+        let urls: Vec<&str> = info.posts.iter().map(|p| p.url.as_str()).collect();
+        assert_eq!(urls, ["Part_1.html", "Part_2.html"]);
+        assert!(info.posts[1].current);
+        assert!(result.posts[2].series.is_none());
+    }
+
+    /// 由本地模拟的 Notion 服务提供 Block 数据，抓取页面 `root`
+    async fn fetch_fixture(config: &Config, fixture: serde_json::Value) -> Result<PageContent> {
+        let server = MockServer::with_blocks(&fixture).await;
+        fetch_root(config, &server).await
+    }
+
+    /// 从 `server` 抓取页面 `root`
+    async fn fetch_root(config: &Config, server: &MockServer) -> Result<PageContent> {
+        let client = NotionApi::with_base(&server.url, &config.notion_token);
+        let renderer = HtmlRenderer::new(config.render_options());
+        Fetcher::new(config, &client, &renderer).fetch_page("root").await
+    }
+
+    fn toggle(id: &str, label: &str) -> serde_json::Value {
+        test_support::block_response(id, true, json!({
+            "type": "toggle",
+            "toggle": { "rich_text": [test_support::text(label)], "color": "default" }
+        }))
+    }
+
+    #[tokio::test]
+    async fn children_deeper_than_max_block_depth_are_truncated() {
+        let fixture = json!({
+            "root": [toggle("t1", "Level 1")],
+            "t1": [toggle("t2", "Level 2")],
+            "t2": [test_support::paragraph("p3", "Level 3")],
+        });
+        let shallow = fetch_fixture(&test_support::config(json!({ "maxBlockDepth": 1 })), fixture.clone()).await.unwrap();
+        assert!(shallow.html.contains("Level 2"), "{}", shallow.html);
+        assert!(!shallow.html.contains("Level 3"), "{}", shallow.html);
+        assert!(shallow.html.contains("truncated-notice"), "{}", shallow.html);
+
+        let deep = fetch_fixture(&test_support::config(json!({ "maxBlockDepth": 2 })), fixture).await.unwrap();
+        assert!(deep.html.contains("Level 3") && !deep.html.contains("truncated-notice"), "{}", deep.html);
+    }
+
+    #[tokio::test]
+    async fn head_property_reaches_the_post_context() {
+        let head = json!({ "head": { "type": "rich_text", "rich_text": [test_support::text("<style>.x{color:red}</style>")] } });
+        let pages = vec![
+            test_support::page("page-a", "Widget", &[], head),
+            test_support::page("page-b", "Plain", &[], json!({})),
+        ];
+        let server = test_support::notion_site(pages, vec![]).await;
+
+        let result = Site::build_from(&server.url, &test_support::config(json!({}))).await.unwrap();
+
+        assert_eq!(result.posts[1].meta.extra_head, None);
+        let context = tera::Context::from_serialize(json!({ "post": &result.posts[0] })).unwrap();
+        let html = tera::Tera::one_off("{{ post.extraHead | safe }}", &context, false).unwrap();
+        assert_eq!(html, "<style>.x{color:red}</style>");
+    }
+
+    #[tokio::test]
+    async fn tag_order_is_reproducible() {
+        let pages = vec![
+            test_support::page("page-a", "A", &["Zig", "Rust", "Go"], json!({})),
+            test_support::page("page-b", "B", &["Go", "Zig", "Rust"], json!({})),
+            test_support::page("page-c", "C", &["Rust", "Go", "Zig"], json!({})),
+        ];
+        let server = test_support::notion_site(pages, vec![]).await;
+        let config = test_support::config(json!({}));
+
+        let build = || async {
+            let result = Site::build_from(&server.url, &config).await.unwrap();
+            let stats: Vec<String> = result.tags.iter().map(|t| format!("{}:{}", t.name, t.count)).collect();
+            let post_tags: Vec<Vec<String>> = result.posts.iter()
+                .map(|p| p.meta.tags.iter().map(|t| t.name.clone()).collect())
+                .collect();
+            (stats, post_tags)
+        };
+        let first = build().await;
+        assert_eq!(first.0, ["Go:3", "Rust:3", "Zig:3"]);
+        assert!(first.1.iter().all(|tags| tags == &["Go", "Rust", "Zig"]), "{:?}", first.1);
+        for _ in 0..3 {
+            assert_eq!(build().await, first);
+        }
+    }
+
+    #[tokio::test]
+    async fn divider_marks_the_manual_excerpt() {
+        let config = test_support::config(json!({ "excerptDelimiter": "divider" }));
+        let fixture = json!({
+            "root": [
+                test_support::paragraph("p1", "Summary"),
+                test_support::paragraph("p2", "divider"),
+                test_support::block_response("d1", false, json!({ "type": "divider", "divider": {} })),
+                test_support::paragraph("p3", "Rest of the post"),
+            ],
+        });
+        let page = fetch_fixture(&config, fixture).await.unwrap();
+        let excerpt = page.excerpt.expect("应找到分隔标记");
+        // 内容为 "divider" 的段落不是分隔标记，留在摘要中
+        assert!(excerpt.html.contains("Summary") && excerpt.html.contains("divider"), "{}", excerpt.html);
+        assert!(!excerpt.html.contains("Rest of the post"), "{}", excerpt.html);
+        assert_eq!(excerpt.plain_text.split_whitespace().collect::<Vec<_>>(), ["Summary", "divider"]);
+        assert!(page.html.contains("Rest of the post"));
+    }
+
+    #[test]
+    fn topics_have_one_group_per_tag() {
+        let config = test_support::config(json!({ "topicsPage": true }));
+        let mut a = test_support::meta("a", "2024-01-01");
+        a.tags = vec![test_support::tag("Rust"), test_support::tag("Web")];
+        let mut b = test_support::meta("b", "2024-02-01");
+        b.tags = vec![test_support::tag("Rust")];
+        let result = test_support::result(&config, vec![test_support::post(a, ""), test_support::post(b, "")]);
+
+        let topics = topic_groups(&result, &result.listed_posts());
+        let groups: Vec<(&str, Vec<&str>)> = topics.iter()
+            .map(|g| (g.tag.name.as_str(), g.pages.iter().map(|p| p.slug.as_str()).collect()))
+            .collect();
+        assert_eq!(groups, [("Rust", vec!["b", "a"]), ("Web", vec!["a"])]);
+    }
+
+    #[tokio::test]
+    async fn large_page_is_rendered_once_and_listings_borrow_it() {
+        let config = test_support::config(json!({}));
+        let blocks: Vec<serde_json::Value> = (0..2000)
+            .map(|i| test_support::paragraph(&format!("p{}", i), &format!("block-{}-end", i)))
+            .collect();
+        let page = fetch_fixture(&config, json!({ "root": blocks })).await.unwrap();
+        assert_eq!(page.html.matches("-end</p>").count(), 2000);
+
+        let mut meta = test_support::meta("big", "2024-01-01");
+        meta.tags = vec![test_support::tag("Rust")];
+        let result = test_support::result(&config, vec![test_support::post(meta, &page.html)]);
+        // 列表、标签分组与主题页都引用结果中的同一份元数据，不复制
+        let listed = result.listed_posts();
+        assert!(std::ptr::eq(listed[0], &result.posts[0].meta));
+        let tags_map = group_by_tag(listed.iter().copied());
+        assert!(std::ptr::eq(tags_map["Rust"][0], &result.posts[0].meta));
+        let topics = topic_groups(&result, &listed);
+        assert!(std::ptr::eq(topics[0].pages[0], &result.posts[0].meta));
+    }
+
+    #[tokio::test]
+    async fn toggleable_list_item_nests_details_inside_li() {
+        let config = test_support::config(json!({ "toggleListItems": true }));
+        let fixture = json!({
+            "root": [test_support::block_response("li1", true, json!({
+                "type": "bulleted_list_item",
+                "bulleted_list_item": { "rich_text": [test_support::text("Item")], "color": "default" }
+            }))],
+            "li1": [test_support::paragraph("p1", "Hidden detail")],
+        });
+        let html = fetch_fixture(&config, fixture).await.unwrap().html;
+        let li = html.find("<li").expect(&html);
+        let details = html.find("<details").expect(&html);
+        let detail = html.find("Hidden detail").expect(&html);
+        let details_end = html.find("</details>").expect(&html);
+        let li_end = html.find("</li>").expect(&html);
+        assert!(html.starts_with("<ul>"), "{}", html);
+        assert!(li < details && details < detail && detail < details_end && details_end < li_end, "{}", html);
+    }
+
+    fn table_row(id: &str, cells: &[&str]) -> serde_json::Value {
+        let cells: Vec<serde_json::Value> = cells.iter().map(|c| json!([test_support::text(c)])).collect();
+        test_support::block_response(id, false, json!({ "type": "table_row", "table_row": { "cells": cells } }))
+    }
+
+    #[tokio::test]
+    async fn table_rows_split_across_pages_all_render_with_consistent_columns() {
+        let table = test_support::block_response("tbl", true, json!({
+            "type": "table",
+            "table": { "table_width": 2, "has_column_header": true, "has_row_header": false }
+        }));
+        // 第一页返回表头与一行，第二页返回最后一行 (单元格不足)
+        let first_page = json!({
+            "object": "list",
+            "results": [table_row("r1", &["Name", "Score"]), table_row("r2", &["Alice", "90"])],
+            "next_cursor": "cursor-1",
+            "has_more": true,
+        });
+        let server = MockServer::with_routes(vec![
+            ("/blocks/root/children", "application/json", test_support::list(vec![table])),
+            ("/blocks/tbl/children?start_cursor=cursor-1", "application/json", test_support::list(vec![table_row("r3", &["Bob"])])),
+            ("/blocks/tbl/children", "application/json", first_page.to_string().into_bytes()),
+        ])
+        .await;
+
+        let html = fetch_root(&test_support::config(json!({})), &server).await.unwrap().html;
+        assert_eq!(html.matches("<tr>").count(), 3, "{}", html);
+        assert_eq!(html.matches("<th>").count(), 2, "{}", html);
+        assert_eq!(html.matches("<td>").count(), 4, "{}", html);
+        assert!(html.contains("Bob"), "{}", html);
+        assert_eq!(server.requests().iter().filter(|r| r.contains("/blocks/tbl/children")).count(), 2);
+    }
+
+    #[test]
+    fn cover_focus_is_validated_and_exposed_as_cover_position() {
+        assert_eq!(cover_position("Top").as_deref(), Some("top"));
+        assert_eq!(cover_position("left  bottom").as_deref(), Some("left bottom"));
+        assert_eq!(cover_position("30% 70%").as_deref(), Some("30% 70%"));
+        assert_eq!(cover_position("150% 0%"), None);
+        assert_eq!(cover_position("top 20%"), None);
+        assert_eq!(cover_position("middle"), None);
+
+    }
+
+    #[tokio::test]
+    async fn cover_focus_property_reaches_the_post_metadata() {
+        let focus = |value: &str| json!({ "coverFocus": { "type": "rich_text", "rich_text": [test_support::text(value)] } });
+        let pages = vec![
+            test_support::page("page-a", "A", &[], focus("Top")),
+            test_support::page("page-b", "B", &[], focus("middle")),
+            test_support::page("page-c", "C", &[], json!({})),
+        ];
+        let server = test_support::notion_site(pages, vec![]).await;
+
+        let result = Site::build_from(&server.url, &test_support::config(json!({}))).await.unwrap();
+        let positions: Vec<&str> = result.posts.iter().map(|p| p.meta.cover_position.as_str()).collect();
+        assert_eq!(positions, ["top", "center", "center"]);
+    }
+
+    /// 按写出顺序列出 `output` 中的文件路径与内容
+    fn written(output: &Output) -> Vec<(String, String)> {
+        output.files().iter()
+            .map(|f| (
+                f.path.display().to_string(),
+                String::from_utf8_lossy(output.contents(&f.path).unwrap_or_default()).into_owned(),
+            ))
+            .collect()
+    }
+
+    #[test]
+    fn parallel_rendering_matches_serial_rendering() {
+        let posts: Vec<PostMetadataWithContent> = (1..=12)
+            .map(|i| {
+                let mut meta = test_support::meta(&format!("post-{}", i), &format!("2024-01-{:02}", i));
+                meta.tags = vec![test_support::tag(["Rust", "Go", "Zig"][i % 3])];
+                meta.series = Some(["Intro", "Deep Dive"][i % 2].to_string());
+                test_support::post(meta, &format!("<p>Body {}</p>", i))
+            })
+            .collect();
+        let render = |threads: usize| {
+            let config = test_support::config(json!({
+                "seriesPages": true,
+                "topicsPage": true,
+                "renderThreads": threads,
+            }));
+            let result = test_support::result(&config, posts.clone());
+            let mut output = Output::new(true).with_contents();
+            Site::render_to(&config, &result, &mut output).unwrap();
+            written(&output)
+        };
+
+        let serial = render(1);
+        let paths: Vec<&str> = serial.iter().map(|(p, _)| p.as_str()).collect();
+        for expected in ["public/index.html", "public/topics.html", "public/series/deep-dive.html", "public/tag/rust.html", "public/post-12.html"] {
+            assert!(paths.contains(&expected), "缺少 {}: {:?}", expected, paths);
+        }
+        assert_eq!(serial, render(4));
+    }
+
+    #[tokio::test]
+    async fn uploaded_file_icons_keep_their_url() {
+        let url = "https://prod-files-secure.s3.us-west-2.amazonaws.com/icon.png?X-Amz-Signature=abc";
+        let mut page = test_support::page("page-a", "A", &[], json!({}));
+        page["icon"] = json!({ "type": "file", "file": { "url": url, "expiry_time": "2024-01-01T01:00:00.000Z" } });
+        let server = test_support::notion_site(vec![page], vec![]).await;
+
+        let result = Site::build_from(&server.url, &test_support::config(json!({}))).await.unwrap();
+        assert_eq!(result.posts[0].meta.icon_url.as_deref(), Some(url));
+    }
+
+    #[tokio::test]
+    async fn headings_are_exposed_with_their_anchor_ids() {
+        let config = test_support::config(json!({}));
+        let heading = |id: &str, kind: &str, content: &str| {
+            test_support::block_response(id, false, json!({
+                "type": kind,
+                kind: { "rich_text": [test_support::text(content)], "color": "default", "is_toggleable": false }
+            }))
+        };
+        let fixture = json!({
+            "root": [
+                heading("h1", "heading_2", "Getting Started"),
+                test_support::paragraph("p1", "Intro"),
+                heading("h2", "heading_3", "Install"),
+            ],
+        });
+        let page = fetch_fixture(&config, fixture).await.unwrap();
+        let headings: Vec<(u8, &str)> = page.headings.iter().map(|h| (h.level, h.id.as_str())).collect();
+        assert_eq!(headings, [(2, "getting-started"), (3, "install")]);
+        assert!(page.html.contains("id=\"getting-started\""), "{}", page.html);
+    }
+
+    #[test]
+    fn posts_outside_the_publish_window_are_excluded() {
+        let today = "2024-06-15";
+        assert!(!in_publish_window(Some("2024-05-01"), Some("2024-06-14"), today), "窗口已结束");
+        assert!(in_publish_window(Some("2024-05-01"), Some("2024-06-15T00:00:00.000Z"), today), "结束日期当天仍可见");
+        assert!(!in_publish_window(Some("2024-06-16"), None, today), "窗口尚未开始");
+        assert!(in_publish_window(Some("2024-06-15"), None, today));
+    }
+
+    #[tokio::test]
+    async fn only_renders_the_selected_post() {
+        let pages = vec![
+            test_support::page("0123abcd-0000-0000-0000-000000000000", "my-post", &[], json!({})),
+            test_support::page("page-b", "other-post", &[], json!({})),
+        ];
+        let server = test_support::notion_site(pages, vec![test_support::paragraph("p1", "Hi")]).await;
+        let config = test_support::config(json!({}));
+
+        let result = Site::build_with(&server.url, &config, Some("my-post"), None).await.unwrap();
+        let slugs: Vec<&str> = result.posts.iter().map(|p| p.meta.slug.as_str()).collect();
+        assert_eq!(slugs, ["my-post"]);
+        let block_requests = server.requests().iter().filter(|r| r.contains("/blocks/")).count();
+        assert_eq!(block_requests, 1, "只应抓取所选文章的正文");
+        // 页面 ID 不区分大小写与连字符
+        let by_id = Site::build_with(&server.url, &config, Some("0123ABCD000000000000000000000000"), None).await.unwrap();
+        assert_eq!(by_id.posts[0].meta.slug, "my-post");
+        assert!(Site::build_with(&server.url, &config, Some("missing"), None).await.is_err());
+
+        let mut output = Output::new(true).with_contents();
+        Site::render_posts(&config, &result, &mut output).unwrap();
+        let paths: Vec<String> = written(&output).into_iter().map(|(p, _)| p).collect();
+        assert_eq!(paths, [config.post_output_path("my-post")]);
+    }
+
+    #[tokio::test]
+    async fn column_widths_follow_notion_ratios() {
+        assert_eq!(column_basis(&[None, None]), [50.0, 50.0]);
+
+        let config = test_support::config(json!({}));
+        let column = |id: &str, ratio: f64| {
+            test_support::block_response(id, true, json!({ "type": "column", "column": { "width_ratio": ratio } }))
+        };
+        let fixture = json!({
+            "root": [test_support::block_response("cl", true, json!({ "type": "column_list", "column_list": {} }))],
+            "cl": [column("c1", 2.0 / 3.0), column("c2", 1.0 / 3.0)],
+            "c1": [test_support::paragraph("p1", "Wide")],
+            "c2": [test_support::paragraph("p2", "Narrow")],
+        });
+        let page = fetch_fixture(&config, fixture).await.unwrap();
+        assert!(page.html.contains("flex: 1 1 66.6667%"), "{}", page.html);
+        assert!(page.html.contains("flex: 1 1 33.3333%"), "{}", page.html);
+    }
+
+    #[tokio::test]
+    async fn display_equations_are_numbered_with_anchors() {
+        let equation = |id: &str, expression: &str| {
+            test_support::block_response(id, false, json!({ "type": "equation", "equation": { "expression": expression } }))
+        };
+        let fixture = json!({
+            "root": [
+                equation("e1", "E = mc^2 \\label{energy}"),
+                equation("e2", "a^2 + b^2 = c^2"),
+                test_support::paragraph("p1", "See \\eqref{energy}."),
+            ],
+        });
+        let page = fetch_fixture(&test_support::config(json!({ "numberEquations": true })), fixture.clone()).await.unwrap();
+        assert!(page.html.contains("id=\"eq-1\"") && page.html.contains("(1)"), "{}", page.html);
+        assert!(page.html.contains("id=\"eq-2\"") && page.html.contains("(2)"), "{}", page.html);
+        assert!(page.html.contains("href=\"#eq-1\""), "{}", page.html);
+        assert!(!page.html.contains("\\label"), "{}", page.html);
+
+        let plain = fetch_fixture(&test_support::config(json!({})), fixture).await.unwrap();
+        assert!(!plain.html.contains("eq-1"), "{}", plain.html);
+    }
+
+    #[test]
+    fn missing_post_template_falls_back_to_the_builtin() {
+        let dir = test_support::temp_dir("templates");
+        std::fs::write(dir.join("index.html"), "<h1>{{ siteMeta.title }}</h1>").unwrap();
+        let tera = load_templates_from(&dir.display().to_string()).unwrap();
+        assert!(tera.get_template_names().any(|t| t == "post.html"));
+        assert_eq!(tera.render("index.html", &tera::Context::from_serialize(json!({ "siteMeta": { "title": "Mine" } })).unwrap()).unwrap(), "<h1>Mine</h1>");
+
+        let config = test_support::config(json!({}));
+        let result = test_support::result(&config, vec![test_support::post(test_support::meta("a", "2024-01-01"), "<p>Body A</p>")]);
+        let mut output = Output::new(true).with_contents();
+        render_post_pages(&tera, &config, &result, &mut output).unwrap();
+        let (_, html) = &written(&output)[0];
+        assert!(html.contains("<p>Body A</p>"), "{}", html);
+    }
+
+    #[test]
+    fn extra_static_copies_cname_into_the_output_root() {
+        let dir = test_support::temp_dir("extra-static");
+        std::fs::write(dir.join("CNAME"), "blog.example.com\n").unwrap();
+        let cname = dir.join("CNAME").display().to_string();
+        let missing = dir.join("missing.txt").display().to_string();
+        let config = test_support::config(json!({ "extraStatic": { cname: "CNAME", missing: "missing.txt" } }));
+        let result = test_support::result(&config, Vec::new());
+        let mut output = Output::new(true).with_contents();
+        Site::render_to(&config, &result, &mut output).unwrap();
+        assert_eq!(output.contents(Path::new("public/CNAME")), Some(&b"blog.example.com\n"[..]));
+        assert!(output.contents(Path::new("public/missing.txt")).is_none());
+    }
+
+    #[tokio::test]
+    async fn reader_mode_turns_embeds_into_cards_without_scripts() {
+        let mut config = test_support::config(json!({}));
+        config.reader_mode = true;
+        let fixture = json!({
+            "root": [
+                test_support::block_response("e1", false, json!({
+                    "type": "embed", "embed": { "url": "https://www.youtube.com/embed/abc", "caption": [] }
+                })),
+                test_support::paragraph("p1", "After the embed"),
+            ],
+        });
+        let page = fetch_fixture(&config, fixture).await.unwrap();
+        let result = test_support::result(&config, vec![test_support::post(test_support::meta("a", "2024-01-01"), &page.html)]);
+        let mut output = Output::new(true).with_contents();
+        Site::render_reader(&result, &mut output).unwrap();
+
+        let pages = written(&output);
+        assert_eq!(pages.len(), 1);
+        let (path, html) = &pages[0];
+        assert_eq!(path, "public/reader/a.html");
+        assert!(html.contains("<a href=\"https://www.youtube.com/embed/abc\" class=\"bookmark\""), "{}", html);
+        assert!(!html.contains("<iframe"), "{}", html);
+        assert!(!html.contains("<script"), "{}", html);
+        assert!(!html.contains("<link"), "{}", html);
+    }
+
+    #[tokio::test]
+    async fn tag_colors_are_consistent_across_posts_and_tag_pages() {
+        let tag = |color: &str| json!({ "tags": { "type": "multi_select", "multi_select": [{ "name": "Rust", "color": color }] } });
+        let pages = vec![
+            test_support::page("page-a", "a", &[], tag("red")),
+            test_support::page("page-b", "b", &[], tag("blue")),
+        ];
+        let server = test_support::notion_site(pages, vec![]).await;
+        let config = test_support::config(json!({}));
+
+        let result = Site::build_from(&server.url, &config).await.unwrap();
+        let color = &result.tags[0].color;
+        assert!(color.contains("red"), "{}", color);
+        assert!(result.posts.iter().all(|p| &p.meta.tags[0].color == color));
+
+        let mut output = Output::new(true).with_contents();
+        Site::render_to(&config, &result, &mut output).unwrap();
+        for (path, html) in written(&output).into_iter().filter(|(path, _)| path.ends_with(".html")) {
+            assert!(!html.contains("tag-blue"), "{}: {}", path, html);
+        }
+        let (_, tag_page) = written(&output).into_iter().find(|(path, _)| path == "public/tag/rust.html").unwrap();
+        assert!(tag_page.contains(&format!("tag-{}", color)), "{}", tag_page);
+    }
+
+    #[test]
+    fn language_subsites_cross_link_translations_and_use_their_own_tags() {
+        let config = test_support::config(json!({ "i18n": true }));
+        let mut posts: Vec<PostMetadataWithContent> = [("hello", "en"), ("ni-hao", "zh")]
+            .into_iter()
+            .map(|(slug, language)| {
+                let mut meta = test_support::meta(slug, "2024-01-01");
+                meta.language = Some(language.to_string());
+                meta.translation_key = Some("greeting".to_string());
+                meta.tags = vec![test_support::tag("Rust")];
+                test_support::post(meta, "")
+            })
+            .collect();
+        let translations = translations_of(&posts);
+        for (post, translations) in posts.iter_mut().zip(translations) {
+            post.translations = translations;
+        }
+        let result = test_support::result(&config, posts);
+        let mut output = Output::new(true).with_contents();
+        Site::render_to(&config, &result, &mut output).unwrap();
+        // Tera 自动转义属性中的 `/`，比较前还原
+        let page = |path: &str| String::from_utf8_lossy(output.contents(Path::new(path)).unwrap()).replace("&#x2F;", "/");
+
+        let en = page("public/hello.html");
+        assert!(en.contains("href=\"./ni-hao.html\" hreflang=\"zh\""), "{}", en);
+        let zh = page("public/ni-hao.html");
+        assert!(zh.contains("href=\"./hello.html\" hreflang=\"en\""), "{}", zh);
+
+        for language in ["en", "zh"] {
+            let index = page(&format!("public/{}/index.html", language));
+            assert!(index.contains("href=\"./tag/rust.html\""), "{}", index);
+            assert!(!index.contains("href=\"../tag/rust.html\""), "{}", index);
+            let tag_page = page(&format!("public/{}/tag/rust.html", language));
+            assert!(tag_page.contains("href=\"../tag/rust.html\""), "{}", tag_page);
+            assert!(tag_page.contains("<span class=\"tag-count\">1</span>"), "{}", tag_page);
+        }
+    }
+
+    #[tokio::test]
+    async fn to_do_progress_counts_nested_items() {
+        let to_do = |id: &str, checked: bool, has_children: bool| {
+            test_support::block_response(id, has_children, json!({
+                "type": "to_do",
+                "to_do": { "rich_text": [test_support::text(id)], "checked": checked, "color": "default" }
+            }))
+        };
+        let fixture = json!({
+            "root": [
+                to_do("t1", true, false),
+                to_do("t2", false, true),
+                test_support::paragraph("p1", "Notes"),
+                to_do("t3", true, false),
+            ],
+            "t2": [to_do("t4", true, false), to_do("t5", false, false)],
+        });
+        let config = test_support::config(json!({}));
+        let page = fetch_fixture(&config, fixture).await.unwrap();
+        assert_eq!((page.todos.completed, page.todos.total), (3, 5));
+
+        let plain = fetch_fixture(&config, json!({ "root": [test_support::paragraph("p1", "Notes")] })).await.unwrap();
+        assert_eq!(plain.todos.total, 0);
+    }
+
+    #[tokio::test]
+    async fn created_date_source_sorts_by_creation_time() {
+        // (标题, date 属性, 创建时间)：date 属性的顺序与创建时间相反
+        let pages = [
+            ("first", "2024-03-01", "2023-01-05T08:00:00.000Z"),
+            ("second", "2024-02-01", "2023-02-05T08:00:00.000Z"),
+            ("third", "2024-01-01", "2023-03-05T08:00:00.000Z"),
+        ]
+        .into_iter()
+        .map(|(title, date, created)| {
+            let mut page = test_support::page(title, title, &[], json!({
+                "date": { "type": "date", "date": { "start": date, "end": null, "time_zone": null } }
+            }));
+            page["created_time"] = json!(created);
+            page["last_edited_time"] = json!("2024-06-01T00:00:00.000Z");
+            page
+        })
+        .collect();
+        let server = test_support::notion_site(pages, vec![]).await;
+        let build = |source: &str| {
+            let config = test_support::config(json!({ "dateSource": source }));
+            let url = server.url.clone();
+            async move { Site::build_from(&url, &config).await.unwrap() }
+        };
+
+        let order = |result: &BuildResult| result.listed_posts().iter().map(|p| (p.slug.clone(), p.date.clone())).collect::<Vec<_>>();
+        let by_created = order(&build("created").await);
+        assert_eq!(by_created[0], ("third".to_string(), "2023-03-05".to_string()));
+        assert_eq!(by_created[2].0, "first");
+        assert_eq!(order(&build("date").await)[0].0, "first");
+        assert!(order(&build("edited").await).iter().all(|(_, date)| date == "2024-06-01"));
+    }
+
+    #[tokio::test]
+    async fn resumed_build_reuses_journaled_posts() {
+        let pages = vec![test_support::page("page-a", "a", &[], json!({})), test_support::page("page-b", "b", &[], json!({}))];
+        let server = test_support::notion_site(pages, vec![test_support::paragraph("p1", "Fetched")]).await;
+        let config = test_support::config(json!({}));
+        let path = test_support::temp_dir("resume").join("build.jsonl");
+
+        // 上次构建抓取完 page-a 后中断
+        let mut journal = Journal::open_at(&path, false).unwrap();
+        journal.record("page-a", &PageContent { html: "<p>Journaled</p>".to_string(), ..PageContent::default() }).unwrap();
+        drop(journal);
+
+        let journal = Journal::open_at(&path, true).unwrap();
+        let result = Site::build_with(&server.url, &config, None, Some(journal)).await.unwrap();
+        let contents: Vec<&str> = result.posts.iter().map(|p| p.content.as_str()).collect();
+        assert_eq!(contents[0], "<p>Journaled</p>");
+        assert!(contents[1].contains("Fetched"), "{}", contents[1]);
+        assert!(server.requests().iter().all(|r| !r.contains("/blocks/page-a/")), "{:?}", server.requests());
+        assert!(!path.exists(), "构建成功后应删除日志");
+    }
+
+    #[tokio::test]
+    async fn list_item_media_children_stay_inside_the_li() {
+        let fixture = json!({
+            "root": [test_support::block_response("li1", true, json!({
+                "type": "bulleted_list_item",
+                "bulleted_list_item": { "rich_text": [test_support::text("Screenshot")], "color": "default" }
+            }))],
+            "li1": [test_support::block_response("img1", false, json!({
+                "type": "image",
+                "image": { "type": "external", "external": { "url": "https://example.com/shot.png" }, "caption": [] }
+            }))],
+        });
+        let page = fetch_fixture(&test_support::config(json!({})), fixture).await.unwrap();
+        let li_start = page.html.find("<li").expect("应有列表项");
+        let li_end = page.html.find("</li>").expect("列表项应闭合");
+        let img = page.html.find("https://example.com/shot.png").expect("应包含图片");
+        assert!(li_start < img && img < li_end, "{}", page.html);
+        assert!(!page.html.contains("margin-left"), "{}", page.html);
+    }
+
+    #[tokio::test]
+    async fn raw_blocks_match_the_fetched_tree() {
+        let pages = vec![test_support::page("page-a", "a", &[], json!({}))];
+        let root = vec![test_support::paragraph("p1", "One"), toggle("t1", "More"), test_support::paragraph("p2", "Two")];
+        let server = MockServer::with_routes(vec![
+            (test_support::QUERY_PATH, "application/json", test_support::list(pages.clone())),
+            ("/data_sources/", "application/json", test_support::data_source(&pages)),
+            ("/blocks/t1/", "application/json", test_support::list(vec![test_support::paragraph("p3", "Hidden")])),
+            ("/blocks/", "application/json", test_support::list(root)),
+        ])
+        .await;
+
+        let result = Site::build_from(&server.url, &test_support::config(json!({ "includeRawBlocks": true }))).await.unwrap();
+        let context = serde_json::to_value(&result.posts[0]).unwrap();
+        let blocks = context["blocks"].as_array().expect("上下文中应有 blocks 数组");
+        let ids: Vec<&str> = blocks.iter().map(|b| b["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, ["p1", "t1", "p2"]);
+        assert_eq!(blocks[1]["children"][0]["id"], "p3");
+
+        let plain = Site::build_from(&server.url, &test_support::config(json!({}))).await.unwrap();
+        assert!(serde_json::to_value(&plain.posts[0]).unwrap().get("blocks").is_none());
+    }
+
+    #[tokio::test]
+    async fn render_blocks_reads_a_local_fixture() {
+        let path = test_support::temp_dir("output-stdout").join("page.json");
+        let fixture = json!({
+            "root": [test_support::paragraph("p1", "Hello"), toggle("t1", "More")],
+            "t1": [test_support::paragraph("p2", "Inside")],
+        });
+        std::fs::write(&path, fixture.to_string()).unwrap();
+
+        let html = Site::render_blocks(&test_support::config(json!({})), &path.display().to_string()).await.unwrap();
+        assert_eq!(
+            html,
+            "<p class=\"\">Hello</p>\n\
+             <details><summary>More</summary><div class=\"details-content\" style=\"padding-left: 1.2em;\"><p class=\"\">Inside</p>\n\
+             </div></details>"
+        );
+    }
+
+    #[tokio::test]
+    async fn render_blocks_fetches_a_page_id_from_notion() {
+        let server = test_support::notion_site(Vec::new(), vec![test_support::paragraph("p1", "Hello")]).await;
+        let config = test_support::config(json!({}));
+        let html = Site::render_blocks_from(&server.url, &config, "0123-4567").await.unwrap();
+        assert_eq!(html, "<p class=\"\">Hello</p>\n");
+        assert!(server.requests()[0].starts_with("GET /blocks/01234567/children"), "{:?}", server.requests());
+    }
+
+    #[tokio::test]
+    async fn coverless_posts_get_a_generated_og_image() {
+        let dir = test_support::temp_dir("og");
+        std::fs::write(dir.join("font.ttf"), test_support::font()).unwrap();
+        let mut covered = test_support::page("p2", "covered", &[], json!({}));
+        covered["cover"] = json!({ "type": "external", "external": { "url": "https://example.com/cover.png" } });
+        let server = test_support::notion_site(vec![test_support::page("p1", "plain", &[], json!({})), covered], Vec::new()).await;
+        let config = test_support::config(json!({
+            "siteUrl": "https://blog.example.com",
+            "generateOgImages": true,
+            "ogImageFont": dir.join("font.ttf").display().to_string(),
+        }));
+        let result = Site::build_from(&server.url, &config).await.unwrap();
+        let og_image = |slug: &str| result.posts.iter().find(|p| p.meta.slug == slug).unwrap().meta.og_image.clone();
+        assert_eq!(og_image("plain").as_deref(), Some("https://blog.example.com/og/plain.png"));
+        assert_eq!(og_image("covered"), None);
+
+        let mut output = Output::new(true).with_contents();
+        Site::render_to(&config, &result, &mut output).unwrap();
+        let png = output.contents(Path::new("public/og/plain.png")).expect("应生成分享卡片");
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        assert!(output.contents(Path::new("public/og/covered.png")).is_none());
+        let page = String::from_utf8_lossy(output.contents(Path::new("public/plain.html")).unwrap()).replace("&#x2F;", "/");
+        assert!(page.contains("<meta property=\"og:image\" content=\"https://blog.example.com/og/plain.png\">"), "{}", page);
+    }
+
+    #[tokio::test]
+    async fn emoji_only_titles_get_a_fallback_filename_and_tag_slug() {
+        let page = test_support::page("1a2b3c4d-5e6f-7081-92a3-b4c5d6e7f809", "🚀🎉", &["🔥"], json!({}));
+        let server = test_support::notion_site(vec![page], Vec::new()).await;
+        let result = Site::build_from(&server.url, &test_support::config(json!({}))).await.unwrap();
+        let meta = &result.posts[0].meta;
+        assert_eq!(meta.title, "🚀🎉");
+        assert_eq!(meta.url, "post-1a2b3c4d.html");
+        assert!(meta.tags[0].slug.starts_with("u-"), "{}", meta.tags[0].slug);
+    }
+
+    #[tokio::test]
+    async fn feeds_absolutize_relative_links_in_post_content() {
+        let mut link = test_support::text("About");
+        link["text"]["link"] = json!({ "url": "/about.html" });
+        link["href"] = json!("/about.html");
+        let paragraph = test_support::block_response("b1", false, json!({
+            "type": "paragraph",
+            "paragraph": { "rich_text": [link], "color": "default" }
+        }));
+        let server = test_support::notion_site(vec![test_support::page("p1", "hello", &[], json!({}))], vec![paragraph]).await;
+        let config = test_support::config(json!({ "siteUrl": "https://blog.example.com", "indexFeed": true }));
+        let result = Site::build_from(&server.url, &config).await.unwrap();
+        let mut output = Output::new(true).with_contents();
+        Site::render_to(&config, &result, &mut output).unwrap();
+
+        let rss = String::from_utf8_lossy(output.contents(Path::new("public").join(feed::RSS_PATH)).expect("应生成 feed.xml")).into_owned();
+        assert!(rss.contains("<link>https://blog.example.com/hello.html</link>"), "{}", rss);
+        assert!(rss.contains("href=&quot;https://blog.example.com/about.html&quot;"), "{}", rss);
+        assert_eq!(output.contents(Path::new("public").join(feed::INDEX_PATH)), Some(rss.as_bytes()));
+    }
+
+    #[tokio::test]
+    async fn strict_mode_fails_on_unsupported_blocks() {
+        let blocks = vec![
+            test_support::paragraph("p1", "Before"),
+            test_support::block_response("b1", false, json!({ "type": "breadcrumb", "breadcrumb": {} })),
+        ];
+        let server = test_support::notion_site(vec![test_support::page("p1", "hello", &[], json!({}))], blocks).await;
+        let lenient = Site::build_from(&server.url, &test_support::config(json!({}))).await.unwrap();
+        assert!(lenient.posts[0].content.contains("Before"), "{}", lenient.posts[0].content);
+        assert!(lenient.posts[0].content.contains(UNSUPPORTED_BLOCK), "{}", lenient.posts[0].content);
+
+        let mut config = test_support::config(json!({}));
+        config.strict = true;
+        let error = format!("{:#}", Site::build_from(&server.url, &config).await.unwrap_err());
+        assert!(error.contains("b1") && error.contains("Breadcrumb"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn template_blocks_are_omitted_unless_inlined() {
+        let fixture = json!({
+            "root": [
+                test_support::block_response("tpl", true, json!({
+                    "type": "template",
+                    "template": { "rich_text": [test_support::text("Add a task")] }
+                })),
+                test_support::paragraph("p1", "After"),
+            ],
+            "tpl": [test_support::paragraph("p2", "Template body")],
+        });
+        let omitted = fetch_fixture(&test_support::config(json!({})), fixture.clone()).await.unwrap();
+        assert!(omitted.html.contains("After"), "{}", omitted.html);
+        assert!(!omitted.html.contains("Template body"), "{}", omitted.html);
+        assert!(!omitted.html.contains("Add a task"), "{}", omitted.html);
+        assert!(!omitted.html.contains(UNSUPPORTED_BLOCK), "{}", omitted.html);
+
+        let inline = fetch_fixture(&test_support::config(json!({ "templateBlocks": "inline" })), fixture).await.unwrap();
+        assert!(inline.html.contains("Template body"), "{}", inline.html);
+        assert!(!inline.html.contains("Add a task"), "{}", inline.html);
+    }
+
+    #[tokio::test]
+    async fn heading_anchors_link_to_the_toc_id() {
+        let heading = test_support::block_response("h1", false, json!({
+            "type": "heading_2",
+            "heading_2": { "rich_text": [test_support::text("Getting Started")], "color": "default", "is_toggleable": false }
+        }));
+        let fixture = json!({ "root": [heading] });
+        let plain = fetch_fixture(&test_support::config(json!({})), fixture.clone()).await.unwrap();
+        assert!(!plain.html.contains("heading-anchor"), "{}", plain.html);
+
+        let page = fetch_fixture(&test_support::config(json!({ "headingAnchors": true })), fixture).await.unwrap();
+        let id = &page.headings[0].id;
+        assert!(page.html.contains(&format!("<h2 id=\"{}\" ", id)), "{}", page.html);
+        assert!(page.html.contains(&format!("<a class=\"heading-anchor\" href=\"#{}\"", id)), "{}", page.html);
+    }
+
+    #[tokio::test]
+    async fn precompress_writes_gzip_copies_of_rendered_pages() {
+        use std::io::Read;
+        let server = test_support::notion_site(vec![test_support::page("p1", "hello", &[], json!({}))], Vec::new()).await;
+        let config = test_support::config(json!({ "precompress": ["gzip"], "precompressMinSize": 0 }));
+        let result = Site::build_from(&server.url, &config).await.unwrap();
+        let mut output = Output::new(true).with_contents();
+        Site::render_to(&config, &result, &mut output).unwrap();
+
+        let compressed = output.contents("public/hello.html.gz").expect("应生成 hello.html.gz");
+        let mut decompressed = Vec::new();
+        flate2::read::GzDecoder::new(compressed).read_to_end(&mut decompressed).unwrap();
+        assert_eq!(Some(decompressed.as_slice()), output.contents("public/hello.html"));
+        assert!(output.contents("public/hello.html.gz.gz").is_none());
+    }
+
+    #[tokio::test]
+    async fn theme_color_and_scheme_reach_every_page() {
+        let config = test_support::config(json!({ "themeColor": "#112233", "colorScheme": "dark" }));
+        let meta = site_meta(&config, Vec::new(), false, 0);
+        assert_eq!(meta.theme_color.as_deref(), Some("#112233"));
+        assert_eq!(meta.color_scheme, crate::config::ColorScheme::Dark);
+
+        let server = test_support::notion_site(vec![test_support::page("p1", "a", &["rust"], json!({}))], Vec::new()).await;
+        let result = Site::build_from(&server.url, &config).await.unwrap();
+        let mut output = Output::new(true).with_contents();
+        Site::render_to(&config, &result, &mut output).unwrap();
+        for page in ["public/index.html", "public/a.html", "public/tag/rust.html"] {
+            let html = String::from_utf8_lossy(output.contents(page).unwrap()).into_owned();
+            assert!(html.contains("<html lang=\"en\" class=\"theme-dark\">"), "{}: {}", page, html);
+            assert!(html.contains("<meta name=\"theme-color\" content=\"#112233\">"), "{}: {}", page, html);
+        }
+    }
+
+    #[tokio::test]
+    async fn root_path_follows_each_page_depth() {
+        let server = test_support::notion_site(vec![test_support::page("p1", "hello", &["rust"], json!({}))], Vec::new()).await;
+        let config = test_support::config(json!({ "cleanUrls": true }));
+        let result = Site::build_from(&server.url, &config).await.unwrap();
+        let mut output = Output::new(true).with_contents();
+        Site::render_to(&config, &result, &mut output).unwrap();
+        let css = |path: &str| {
+            let html = String::from_utf8_lossy(output.contents(path).unwrap()).replace("&#x2F;", "/");
+            html.contains("href=\"../assets/css/SourceSansPro.css?v=")
+        };
+        assert!(css("public/hello/index.html"));
+        assert!(css("public/tag/rust.html"));
+        let index = String::from_utf8_lossy(output.contents("public/index.html").unwrap()).replace("&#x2F;", "/");
+        assert!(index.contains("href=\"./assets/css/SourceSansPro.css?v="), "{}", index);
+    }
+
+    #[tokio::test]
+    async fn meta_title_and_description_are_capped_on_a_word_boundary() {
+        let summary = "Static sites built from Notion databases ".repeat(10);
+        let title = "Word ".repeat(30);
+        let page = test_support::page("p1", title.trim(), &[], json!({}));
+        let server = test_support::notion_site(vec![page], vec![test_support::paragraph("b1", &summary)]).await;
+        let config = test_support::config(json!({}));
+        let result = Site::build_from(&server.url, &config).await.unwrap();
+        let post = &result.posts[0];
+
+        let description = post.meta_description.clone().unwrap();
+        let chars = description.chars().count();
+        assert!((META_DESCRIPTION_CHARS - 20..=META_DESCRIPTION_CHARS).contains(&chars), "{} chars: {}", chars, description);
+        let head = description.strip_suffix('…').expect("截断后应以省略号结尾");
+        assert!(summary.starts_with(&format!("{} ", head)), "{}", description);
+        assert!(post.meta_title.chars().count() <= META_TITLE_CHARS, "{}", post.meta_title);
+        assert_eq!(post.meta.title, title.trim());
+
+        let mut output = Output::new(true).with_contents();
+        Site::render_to(&config, &result, &mut output).unwrap();
+        let html = String::from_utf8_lossy(output.contents(Path::new("public").join(format!("{}.html", post.meta.slug))).unwrap()).into_owned();
+        assert!(html.contains(&format!("<meta name=\"description\" content=\"{}\">", description)), "{}", html);
+        assert!(html.contains(&format!("<title>{}&nbsp;|", post.meta_title)), "{}", html);
+    }
+
+    /// 模拟带评论接口的数据库：`comments` 为 `/comments` 开头的路由 (先匹配的在前)
+    async fn notion_site_with_comments(pages: Vec<serde_json::Value>, comments: Vec<(&'static str, &'static str, Vec<u8>)>) -> MockServer {
+        let mut routes: Vec<(&'static str, &'static str, &'static str, Vec<u8>)> = comments.into_iter()
+            .map(|(prefix, status, body)| (prefix, status, "application/json", body))
+            .collect();
+        routes.push((test_support::QUERY_PATH, "200 OK", "application/json", test_support::list(pages.clone())));
+        routes.push(("/data_sources/", "200 OK", "application/json", test_support::data_source(&pages)));
+        routes.push(("/blocks/", "200 OK", "application/json", test_support::list(Vec::new())));
+        MockServer::with_status_routes(routes).await
+    }
+
+    #[tokio::test]
+    async fn comment_counts_follow_pagination_and_reach_the_article_list() {
+        let server = notion_site_with_comments(vec![test_support::page("p1", "discussed", &[], json!({}))], vec![
+            ("/comments?block_id=p1&start_cursor=c2", "200 OK",
+                br#"{"object":"list","results":[{"id":"c3"}],"next_cursor":null,"has_more":false}"#.to_vec()),
+            ("/comments", "200 OK",
+                br#"{"object":"list","results":[{"id":"c1"},{"id":"c2"}],"next_cursor":"c2","has_more":true}"#.to_vec()),
+        ]).await;
+        let config = test_support::config(json!({ "fetchComments": true }));
+        let result = Site::build_from(&server.url, &config).await.unwrap();
+        assert_eq!(result.posts[0].meta.comment_count, Some(3));
+
+        let mut output = Output::new(true).with_contents();
+        Site::render_to(&config, &result, &mut output).unwrap();
+        let index = String::from_utf8_lossy(output.contents("public/index.html").unwrap()).into_owned();
+        assert!(index.contains("<span class=\"Article__Comments\">💬 3</span>"), "{}", index);
+    }
+
+    #[tokio::test]
+    async fn missing_comment_permission_stops_fetching_comments_without_failing_the_build() {
+        let pages = vec![test_support::page("p1", "first", &[], json!({})), test_support::page("p2", "second", &[], json!({}))];
+        let server = notion_site_with_comments(pages, vec![
+            ("/comments", "403 Forbidden",
+                br#"{"object":"error","status":403,"code":"restricted_resource","message":"Insufficient permissions"}"#.to_vec()),
+        ]).await;
+        let result = Site::build_from(&server.url, &test_support::config(json!({ "fetchComments": true }))).await.unwrap();
+        assert_eq!(result.posts.len(), 2);
+        assert!(result.posts.iter().all(|p| p.meta.comment_count.is_none()));
+        let comment_requests = server.requests().iter().filter(|r| r.starts_with("GET /comments")).count();
+        assert_eq!(comment_requests, 1, "权限不足后不应继续请求评论");
+    }
+
+    #[tokio::test]
+    async fn build_time_and_id_are_shared_by_every_page() {
+        let config = test_support::config(json!({ "buildTimeFormat": "%Y/%m/%d %H:%M:%S" }));
+        // 2024-02-29 12:34:56 UTC
+        let meta = site_meta(&config, Vec::new(), false, 1_709_210_096);
+        assert_eq!(meta.build_time, "2024/02/29 12:34:56");
+        assert_eq!(meta.build_id.len(), 8);
+        assert_ne!(site_meta(&config, Vec::new(), false, 1_709_210_097).build_id, meta.build_id);
+        assert_eq!(site_meta(&test_support::config(json!({})), Vec::new(), false, 0).build_time, "1970-01-01 00:00 UTC");
+
+        let server = test_support::notion_site(vec![test_support::page("p1", "a", &["rust"], json!({}))], Vec::new()).await;
+        let result = Site::build_from(&server.url, &config).await.unwrap();
+        assert!(!result.site_meta.build_id.is_empty());
+        let mut output = Output::new(true).with_contents();
+        Site::render_to(&config, &result, &mut output).unwrap();
+        for page in ["public/index.html", "public/a.html", "public/tag/rust.html"] {
+            let html = String::from_utf8_lossy(output.contents(page).unwrap()).replace("&#x2F;", "/");
+            assert!(html.contains(&format!("theme.css?v={}", result.site_meta.build_id)), "{}: {}", page, html);
+            assert!(html.contains(&result.site_meta.build_time), "{}: {}", page, html);
+        }
+    }
+
+    #[tokio::test]
+    async fn hidden_tags_get_no_page_and_are_not_displayed() {
+        let config = test_support::config(json!({ "hiddenTags": ["wip"] }));
+        let (visible, hidden) = split_hidden_tags(&config, vec![test_support::tag("Rust"), test_support::tag("WIP")]);
+        assert_eq!(visible.iter().map(|t| t.name.as_str()).collect::<Vec<_>>(), ["Rust"]);
+        assert_eq!(hidden.iter().map(|t| t.name.as_str()).collect::<Vec<_>>(), ["WIP"]);
+
+        let server = test_support::notion_site(vec![
+            test_support::page("p1", "tagged", &["Rust", "WIP"], json!({})),
+            test_support::page("p2", "only-hidden", &["WIP"], json!({})),
+        ], Vec::new()).await;
+        let result = Site::build_from(&server.url, &config).await.unwrap();
+        assert_eq!(result.tags.iter().map(|t| t.name.as_str()).collect::<Vec<_>>(), ["Rust"]);
+
+        let mut output = Output::new(true).with_contents();
+        Site::render_to(&config, &result, &mut output).unwrap();
+        assert!(output.contents("public/tag/rust.html").is_some());
+        assert!(output.contents("public/tag/wip.html").is_none());
+        let page = String::from_utf8_lossy(output.contents("public/tagged.html").unwrap()).replace("&#x2F;", "/");
+        assert!(page.contains("tag/rust.html"), "{}", page);
+        assert!(!page.contains("tag/wip.html") && !page.contains(">WIP<"), "{}", page);
+        assert!(output.contents("public/only-hidden.html").is_some());
+    }
+
+    #[tokio::test]
+    async fn duplicate_toggle_headings_get_distinct_ids() {
+        let toggle_heading = |id: &str| test_support::block_response(id, true, json!({
+            "type": "heading_2",
+            "heading_2": { "rich_text": [test_support::text("Details")], "color": "default", "is_toggleable": true }
+        }));
+        let fixture = json!({
+            "root": [toggle_heading("h1"), toggle_heading("h2")],
+            "h1": [test_support::paragraph("p1", "First body")],
+            "h2": [test_support::paragraph("p2", "Second body")],
+        });
+        let page = fetch_fixture(&test_support::config(json!({})), fixture).await.unwrap();
+        let ids: Vec<&str> = page.html.match_indices("<details class=\"toggle-heading\" id=\"")
+            .map(|(i, m)| page.html[i + m.len()..].split('"').next().unwrap())
+            .collect();
+        assert_eq!(ids.len(), 2, "{}", page.html);
+        assert_ne!(ids[0], ids[1]);
+        assert_eq!(ids, page.headings.iter().map(|h| format!("toggle-{}", h.id)).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn heading_ids_stay_unique_when_a_title_matches_a_suffixed_id() {
+        let heading = |id: &str, title: &str| test_support::block_response(id, false, json!({
+            "type": "heading_2",
+            "heading_2": { "rich_text": [test_support::text(title)], "color": "default", "is_toggleable": false }
+        }));
+        let fixture = json!({ "root": [heading("h1", "Intro"), heading("h2", "Intro"), heading("h3", "Intro-1")] });
+        let page = fetch_fixture(&test_support::config(json!({ "headingAnchors": true })), fixture).await.unwrap();
+        let ids: Vec<&str> = page.headings.iter().map(|h| h.id.as_str()).collect();
+        assert_eq!(ids, ["intro", "intro-1", "intro-1-1"]);
+        for id in ids {
+            assert_eq!(page.html.matches(&format!("<h2 id=\"{}\" ", id)).count(), 1, "{}", page.html);
+        }
+    }
+
+    #[tokio::test]
+    async fn rating_property_is_rendered_in_the_properties_table() {
+        let config = test_support::config(json!({ "propertiesTable": ["Rating"] }));
+        let page = test_support::page("p1", "recipe", &[], json!({ "Rating": { "id": "a1", "type": "number", "number": 4.5 } }));
+        let server = test_support::notion_site(vec![page], Vec::new()).await;
+        let result = Site::build_from(&server.url, &config).await.unwrap();
+        let mut output = Output::new(true).with_contents();
+        Site::render_to(&config, &result, &mut output).unwrap();
+        let page = String::from_utf8_lossy(output.contents("public/recipe.html").unwrap()).into_owned();
+        assert!(page.contains("<tr><th>Rating</th><td>4.5</td></tr>"), "{}", page);
+    }
+
+    #[tokio::test]
+    async fn second_build_reuses_cached_font_files() {
+        let css = "@font-face { font-family: 'Inter'; src: url(/s/inter.woff2) format('woff2'); }";
+        let fonts = test_support::MockServer::with_routes(vec![
+            ("/css", "text/css", css.as_bytes().to_vec()),
+            ("/s/inter.woff2", "font/woff2", b"woff2-data".to_vec()),
+        ])
+        .await;
+        let server = test_support::notion_site(vec![test_support::page("p1", "a", &[], json!({}))], Vec::new()).await;
+        let cache_dir = test_support::temp_dir("site-font-cache");
+        let config = test_support::config(json!({
+            "selfHostFonts": [format!("{}/css", fonts.url)],
+            "cacheDir": cache_dir.to_string_lossy(),
+        }));
+
+        // 两次独立的构建共用同一个缓存目录
+        for _ in 0..2 {
+            let result = Site::build_from(&server.url, &config).await.unwrap();
+            assert_eq!(result.fonts.as_ref().unwrap().files[0].data, b"woff2-data");
+        }
+        let downloads = fonts.requests().iter().filter(|r| r.starts_with("GET /s/inter.woff2 ")).count();
+        assert_eq!(downloads, 1, "第二次构建应直接使用缓存的字体文件");
+    }
+
+    #[tokio::test]
+    async fn link_to_page_blocks_render_as_cards() {
+        let link = |id: &str, target: &str| test_support::block_response(id, false, json!({
+            "type": "link_to_page",
+            "link_to_page": { "type": "page_id", "page_id": target }
+        }));
+        let built = link("l1", "p2");
+        let external = link("l2", "ffffffff-0000-0000-0000-000000000000");
+        let pages = vec![
+            test_support::page("p1", "Menu", &[], json!({})),
+            test_support::page("p2", "Recipe", &[], json!({})),
+        ];
+        // notionrs 无法解析「链接到页面」，抓取时再单独取回 Block 的原始数据
+        let server = MockServer::with_routes(vec![
+            (test_support::QUERY_PATH, "application/json", test_support::list(pages.clone())),
+            ("/data_sources/", "application/json", test_support::data_source(&pages)),
+            ("/blocks/l1", "application/json", built.to_string().into_bytes()),
+            ("/blocks/l2", "application/json", external.to_string().into_bytes()),
+            ("/blocks/", "application/json", test_support::list(vec![built.clone(), external.clone()])),
+        ])
+        .await;
+
+        let result = Site::build_from(&server.url, &test_support::config(json!({}))).await.unwrap();
+        let html = &result.posts.iter().find(|p| p.meta.title == "Menu").unwrap().content;
+        assert!(html.contains("<a href=\"./Recipe.html\" class=\"link-to-page\">"), "{}", html);
+        assert!(html.contains("<span class=\"link-to-page-title\">Recipe</span>"), "{}", html);
+        assert!(html.contains("<a href=\"https://www.notion.so/ffffffff000000000000000000000000\" class=\"link-to-page external\">"), "{}", html);
+        assert!(!html.contains(UNSUPPORTED_BLOCK), "{}", html);
+    }
+
+    #[tokio::test]
+    async fn render_to_reports_a_structured_error() {
+        let server = test_support::notion_site(vec![test_support::page("p1", "a", &[], json!({}))], Vec::new()).await;
+        let config = test_support::config(json!({ "fileMode": "rw-r--r--" }));
+        let result = Site::build_from(&server.url, &config).await.unwrap();
+        let error = Site::render_to(&config, &result, &mut Output::new(true).with_contents()).unwrap_err();
+        assert!(matches!(error, BuildError::Render(_)), "{:?}", error);
+        assert!(std::error::Error::source(&error).is_some());
+    }
+
+    #[tokio::test]
+    async fn hide_title_is_exposed_and_suppresses_the_header() {
+        let checkbox = json!({ "type": "checkbox", "checkbox": true });
+        let server = test_support::notion_site(vec![
+            test_support::page("p1", "Landing Title", &["rust"], json!({ "hideTitle": checkbox, "hideMeta": checkbox })),
+            test_support::page("p2", "normal", &[], json!({})),
+        ], vec![test_support::paragraph("b1", "Hero")]).await;
+        let config = test_support::config(json!({}));
+        let result = Site::build_from(&server.url, &config).await.unwrap();
+        let context = serde_json::to_value(&result.posts[0]).unwrap();
+        assert_eq!((context["hideTitle"].as_bool(), context["hideMeta"].as_bool()), (Some(true), Some(true)));
+
+        let mut output = Output::new(true).with_contents();
+        Site::render_to(&config, &result, &mut output).unwrap();
+        let page = String::from_utf8_lossy(output.contents("public/Landing_Title.html").unwrap()).into_owned();
+        assert!(!page.contains("<h1 class=\"Header__Title\">"), "{}", page);
+        assert!(!page.contains("Posted on"), "{}", page);
+        assert!(page.contains("Hero"), "{}", page);
+        let page = String::from_utf8_lossy(output.contents("public/normal.html").unwrap()).into_owned();
+        assert!(page.contains("<h1 class=\"Header__Title\">normal</h1>"), "{}", page);
+    }
+
+    #[tokio::test]
+    async fn trailing_slash_policy_applies_to_sitemap_feed_and_canonical() {
+        let server = test_support::notion_site(vec![test_support::page("p1", "my-post", &["rust"], json!({}))], Vec::new()).await;
+        for (policy, url, tag_url, tag_path) in [
+            ("always", "my-post/", "tag/rust/", "public/tag/rust/index.html"),
+            ("never", "my-post.html", "tag/rust.html", "public/tag/rust.html"),
+        ] {
+            let config = test_support::config(json!({ "siteUrl": "https://blog.example.com", "trailingSlash": policy }));
+            let result = Site::build_from(&server.url, &config).await.unwrap();
+            let mut output = Output::new(true).with_contents();
+            Site::render_to(&config, &result, &mut output).unwrap();
+
+            let absolute = format!("https://blog.example.com/{}", url);
+            let read = |path: &str| String::from_utf8_lossy(output.contents(path).unwrap()).replace("&#x2F;", "/");
+            let post_path = config.post_output_path("my-post");
+            assert!(read(&post_path).contains(&format!("<link rel=\"canonical\" href=\"{}\">", absolute)), "{}", read(&post_path));
+            assert!(read("public/sitemap.xml").contains(&format!("<loc>{}</loc>", absolute)), "{}", read("public/sitemap.xml"));
+            assert!(read("public/feed.xml").contains(&format!("<link>{}</link>", absolute)), "{}", read("public/feed.xml"));
+            let index = read("public/index.html");
+            assert!(index.contains(&format!("/{}\"", url)), "{}", index);
+            assert!(index.contains(&format!("/{}\"", tag_url)), "{}", index);
+            assert!(output.contents(tag_path).is_some(), "{}", tag_path);
+        }
+    }
+
+    #[tokio::test]
+    async fn nested_numbered_lists_cycle_marker_styles_by_depth() {
+        let item = |id: &str, label: &str, has_children: bool| test_support::block_response(id, has_children, json!({
+            "type": "numbered_list_item",
+            "numbered_list_item": { "rich_text": [test_support::text(label)], "color": "default" }
+        }));
+        let fixture = json!({
+            "root": [item("n1", "One", true), item("n5", "Two", false)],
+            "n1": [item("n2", "One.a", true)],
+            "n2": [item("n3", "One.a.i", true)],
+            "n3": [item("n4", "One.a.i.1", false)],
+        });
+        let page = fetch_fixture(&test_support::config(json!({})), fixture).await.unwrap();
+        let markers: Vec<&str> = page.html.match_indices("<ol type=\"")
+            .map(|(i, m)| &page.html[i + m.len()..i + m.len() + 1])
+            .collect();
+        // decimal → lower-alpha → lower-roman，第四层重新从 decimal 开始
+        assert_eq!(markers, ["1", "a", "i", "1"], "{}", page.html);
+        assert_eq!(page.html.matches("<ol").count(), page.html.matches("</ol>").count(), "{}", page.html);
+        let two = page.html.find("Two").unwrap();
+        assert_eq!(page.html[..two].matches("<ol").count() - page.html[..two].matches("</ol>").count(), 1, "{}", page.html);
+    }
+
+    #[tokio::test]
+    async fn check_connection_reports_success_and_auth_failure() {
+        let config = test_support::config(json!({}));
+        let id = config.get_notion_id().unwrap();
+        let server = test_support::notion_site(vec![test_support::page("p1", "Hello", &[], json!({}))], Vec::new()).await;
+        Site::check_connection(&server.url, &config, &id).await.unwrap();
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2, "{:?}", requests);
+        assert!(requests[0].starts_with(&format!("GET /data_sources/{} ", id)), "{}", requests[0]);
+        assert!(requests[1].starts_with(&format!("POST {} ", test_support::QUERY_PATH)), "{}", requests[1]);
+        assert!(requests.iter().all(|r| !r.contains("/blocks/")), "{:?}", requests);
+
+        let body = json!({ "object": "error", "status": 401, "code": "unauthorized", "message": "API token is invalid." });
+        let server = MockServer::error("401 Unauthorized", "application/json", body.to_string().into_bytes()).await;
+        let message = Site::check_connection(&server.url, &config, &id).await.unwrap_err().to_string();
+        assert_eq!(server.requests().len(), 1);
+        assert!(message.contains("Notion Token 无效"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn tag_pages_are_paginated_by_posts_per_page() {
+        let pages = (1..=25)
+            .map(|day| {
+                let date = json!({ "date": { "type": "date", "date": { "start": format!("2024-01-{:02}", day), "end": null, "time_zone": null } } });
+                test_support::page(&format!("p{}", day), &format!("post-{:02}", day), &["Rust"], date)
+            })
+            .collect();
+        let server = test_support::notion_site(pages, Vec::new()).await;
+        let config = test_support::config(json!({ "postsPerPage": 10 }));
+        let result = Site::build_from(&server.url, &config).await.unwrap();
+        let mut output = Output::new(true).with_contents();
+        Site::render_to(&config, &result, &mut output).unwrap();
+
+        let pages: BTreeMap<String, String> = written(&output)
+            .into_iter()
+            .filter(|(path, _)| path.starts_with("public/tag/rust"))
+            .map(|(path, html)| (path, html.replace("&#x2F;", "/")))
+            .collect();
+        let paths: Vec<&str> = pages.keys().map(String::as_str).collect();
+        assert_eq!(paths, ["public/tag/rust.html", "public/tag/rust/2.html", "public/tag/rust/3.html"]);
+        let count = |html: &str| (1..=25).filter(|day| html.contains(&format!("post-{:02}.html", day))).count();
+        assert_eq!(pages.values().map(|html| count(html)).collect::<Vec<_>>(), [10, 10, 5]);
+
+        let second = &pages["public/tag/rust/2.html"];
+        assert!(second.contains("href=\"../../tag/rust.html\">← Newer</a>"), "{}", second);
+        assert!(second.contains("href=\"../../tag/rust/3.html\">Older →</a>"), "{}", second);
+        assert!(second.contains("2 / 3"), "{}", second);
+    }
+
+    #[tokio::test]
+    async fn extensionless_file_is_rendered_by_sniffed_content_type() {
+        let server = test_support::MockServer::with_routes(vec![
+            ("/uploads/photo", "image/png", vec![0x89, b'P', b'N', b'G']),
+            ("/uploads/report", "application/pdf; charset=binary", b"%PDF".to_vec()),
+        ])
+        .await;
+        let file = |id: &str, name: &str| {
+            test_support::block_response(id, false, json!({
+                "type": "file",
+                "file": { "type": "external", "external": { "url": format!("{}/uploads/{}", server.url, name) }, "caption": [] }
+            }))
+        };
+        let fixture = json!({ "root": [file("f1", "photo"), file("f2", "report")] });
+
+        let config = test_support::config(json!({ "sniffFileTypes": true }));
+        let page = fetch_fixture(&config, fixture.clone()).await.unwrap();
+        assert!(page.html.contains(&format!("<img src=\"{}/uploads/photo\"", server.url)), "{}", page.html);
+        assert!(page.html.contains(&format!("href=\"{}/uploads/report\"", server.url)), "{}", page.html);
+        assert!(page.html.contains("📎 report.pdf</a>"), "{}", page.html);
+        assert!(server.requests().iter().all(|r| r.starts_with("HEAD ")), "{:?}", server.requests());
+
+        let requests = server.requests().len();
+        let page = fetch_fixture(&test_support::config(json!({})), fixture).await.unwrap();
+        assert!(!page.html.contains("<img"), "{}", page.html);
+        assert_eq!(server.requests().len(), requests);
+    }
+
+    #[tokio::test]
+    async fn quote_attribution_child_renders_as_cite() {
+        let quote = test_support::block_response("q1", true, json!({
+            "type": "quote",
+            "quote": { "rich_text": [test_support::text("Imagination is more important.")], "color": "default" }
+        }));
+        let fixture = json!({
+            "root": [quote],
+            "q1": [test_support::paragraph("p1", "— Albert Einstein")]
+        });
+
+        let config = test_support::config(json!({ "quoteCitations": true }));
+        let page = fetch_fixture(&config, fixture.clone()).await.unwrap();
+        let cite = page.html.find("<cite>— Albert Einstein</cite>").expect(&page.html);
+        assert!(cite < page.html.find("</blockquote>").unwrap(), "{}", page.html);
+
+        let page = fetch_fixture(&test_support::config(json!({})), fixture).await.unwrap();
+        assert!(!page.html.contains("<cite>"), "{}", page.html);
+        assert!(page.html.contains("— Albert Einstein"), "{}", page.html);
+    }
+
+    #[tokio::test]
+    async fn explicit_order_sorts_ahead_of_date_only_posts() {
+        let pages = [
+            ("newest", "2024-05-01", None),
+            ("pinned-second", "2024-01-01", Some(2.0)),
+            ("older", "2024-02-01", None),
+            ("pinned-first", "2023-01-01", Some(1.0)),
+            ("pinned-tie", "2024-03-01", Some(2.0)),
+        ]
+        .into_iter()
+        .map(|(title, date, order)| {
+            let mut properties = json!({ "date": { "type": "date", "date": { "start": date, "end": null, "time_zone": null } } });
+            if let Some(order) = order {
+                properties["order"] = json!({ "id": "o", "type": "number", "number": order });
+            }
+            test_support::page(title, title, &[], properties)
+        })
+        .collect();
+        let server = test_support::notion_site(pages, Vec::new()).await;
+        let config = test_support::config(json!({}));
+        let result = Site::build_from(&server.url, &config).await.unwrap();
+        let slugs: Vec<&str> = result.listed_posts().iter().map(|p| p.slug.as_str()).collect();
+        assert_eq!(slugs, ["pinned-first", "pinned-tie", "pinned-second", "newest", "older"]);
+
+        let mut output = Output::new(true).with_contents();
+        Site::render_to(&config, &result, &mut output).unwrap();
+        let index = String::from_utf8_lossy(output.contents("public/index.html").unwrap()).replace("&#x2F;", "/");
+        let positions: Vec<usize> = slugs.iter().map(|slug| index.find(&format!("{}.html", slug)).expect(slug)).collect();
+        assert!(positions.is_sorted(), "{}", index);
+    }
+
+    #[tokio::test]
+    async fn pages_over_max_blocks_per_page_are_truncated_with_a_notice() {
+        let paragraphs: Vec<serde_json::Value> = (1..=5)
+            .map(|i| test_support::paragraph(&format!("p{}", i), &format!("Paragraph {}", i)))
+            .collect();
+        let config = test_support::config(json!({ "maxBlocksPerPage": 3 }));
+
+        let page = fetch_fixture(&config, json!({ "root": paragraphs })).await.unwrap();
+        assert!(page.html.contains("Paragraph 3") && !page.html.contains("Paragraph 4"), "{}", page.html);
+        assert!(page.html.contains("<p class=\"truncated-notice\">（内容过长，其余部分已省略）</p>"), "{}", page.html);
+
+        let page = fetch_fixture(&config, json!({ "root": paragraphs[..3] })).await.unwrap();
+        assert!(page.html.contains("Paragraph 3") && !page.html.contains("truncated-notice"), "{}", page.html);
+
+        // 子 Block 同样计数
+        let fixture = json!({
+            "root": [toggle("t1", "Toggle"), test_support::paragraph("after", "After")],
+            "t1": paragraphs,
+        });
+        let page = fetch_fixture(&config, fixture).await.unwrap();
+        assert!(page.html.contains("Paragraph 2") && !page.html.contains("Paragraph 3"), "{}", page.html);
+        assert!(!page.html.contains("After") && page.html.contains("truncated-notice"), "{}", page.html);
+    }
+
+    #[tokio::test]
+    async fn max_blocks_per_page_stops_requesting_further_pages() {
+        let paragraphs = |ids: &[&str]| ids.iter().map(|id| test_support::paragraph(id, id)).collect::<Vec<_>>();
+        let first = json!({ "object": "list", "results": paragraphs(&["a", "b", "c"]), "next_cursor": "c2", "has_more": true });
+        let server = MockServer::with_routes(vec![
+            ("/blocks/root/children?start_cursor=c2", "application/json", test_support::list(paragraphs(&["d", "e"]))),
+            ("/blocks/root/children", "application/json", first.to_string().into_bytes()),
+        ])
+        .await;
+
+        let page = fetch_root(&test_support::config(json!({ "maxBlocksPerPage": 2 })), &server).await.unwrap();
+        assert!(page.html.contains("truncated-notice"), "{}", page.html);
+        assert_eq!(server.requests().len(), 1, "{:?}", server.requests());
+
+        let page = fetch_root(&test_support::config(json!({})), &server).await.unwrap();
+        assert!(page.html.contains(">e<"), "{}", page.html);
+        assert_eq!(server.requests().len(), 3, "{:?}", server.requests());
+    }
+
+    #[tokio::test]
+    async fn configured_publish_filter_and_sort_are_passed_to_the_query() {
+        let server = test_support::notion_site(vec![test_support::page("p1", "a", &[], json!({}))], Vec::new()).await;
+        let config = test_support::config(json!({
+            "query": { "filterCheckbox": "publish", "sortProperty": "date", "sortDirection": "ascending" }
+        }));
+        Site::build_from(&server.url, &config).await.unwrap();
+        let requests = server.requests();
+        let query = requests.iter().find(|r| r.starts_with(&format!("POST {} ", test_support::QUERY_PATH))).expect("应查询数据库");
+        assert!(query.contains(r#""filter":{"checkbox":{"equals":true},"property":"publish"}"#), "{}", query);
+        assert!(query.contains(r#""sorts":[{"direction":"ascending","property":"date"}]"#), "{}", query);
+
+        // 未配置时取回全部页面，不排序
+        let server = test_support::notion_site(vec![test_support::page("p1", "a", &[], json!({}))], Vec::new()).await;
+        Site::build_from(&server.url, &test_support::config(json!({}))).await.unwrap();
+        let requests = server.requests();
+        let query = requests.iter().find(|r| r.starts_with(&format!("POST {} ", test_support::QUERY_PATH))).expect("应查询数据库");
+        assert!(query.contains(r#""timestamp":"created_time""#) && !query.contains("sorts"), "{}", query);
+    }
+
+    #[tokio::test]
+    async fn code_and_equation_children_render_inside_callouts_and_quotes() {
+        let code = test_support::block_response("code", false, json!({
+            "type": "code",
+            "code": { "rich_text": [test_support::text("let x = 1;")], "caption": [], "language": "rust" }
+        }));
+        let equation = test_support::block_response("eq", false, json!({
+            "type": "equation",
+            "equation": { "expression": "E = mc^2" }
+        }));
+        let callout = test_support::block_response("c1", true, json!({
+            "type": "callout",
+            "callout": { "rich_text": [test_support::text("Note")], "icon": { "type": "emoji", "emoji": "💡" }, "color": "gray_background" }
+        }));
+        let quote = test_support::block_response("q1", true, json!({
+            "type": "quote",
+            "quote": { "rich_text": [test_support::text("Quoted")], "color": "default" }
+        }));
+        let fixture = json!({
+            "root": [callout, quote, test_support::paragraph("after", "After")],
+            "c1": [code, equation],
+            "q1": [code, equation],
+        });
+        let page = fetch_fixture(&test_support::config(json!({})), fixture).await.unwrap();
+        let html = &page.html;
+
+        let callout_start = html.find("<div class=\"callout has-children").expect(html);
+        let callout_end = html.find("</div></div></div>").expect(html);
+        let quote_start = html.find("<blockquote").expect(html);
+        let quote_end = html.find("</blockquote>").expect(html);
+        let inside = |needle: &str, start: usize, end: usize| html[start..end].contains(needle);
+        for (start, end) in [(callout_start, callout_end), (quote_start, quote_end)] {
+            assert!(inside("<pre><code class=\"language-rust\">let x = 1;</code></pre>", start, end), "{}", html);
+            assert!(inside("E = mc^2", start, end), "{}", html);
+        }
+        assert!(html.find("After").unwrap() > quote_end, "{}", html);
+    }
+}
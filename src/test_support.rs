@@ -0,0 +1,397 @@
+//! 单元测试共用的构造函数与本地模拟服务
+use serde_json::{Value, json};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::config::Config;
+use crate::model::{PostMetadata, PostMetadataWithContent, SiteMeta, Tag};
+use crate::site::BuildResult;
+use notionrs_types::prelude::Block;
+
+/// 由 JSON 构造配置：`overrides` 中的键覆盖必填项的占位值，其余选项取默认值
+pub fn config(overrides: Value) -> Config {
+    let mut value = json!({
+        "url": "https://www.notion.so/0123456789abcdef0123456789abcdef",
+        "notionToken": "secret_test",
+        "theme": "default",
+        "title": "Test Blog",
+    });
+    if let (Some(base), Some(extra)) = (value.as_object_mut(), overrides.as_object()) {
+        base.extend(extra.clone());
+    }
+    serde_json::from_value(value).expect("测试配置无效")
+}
+
+/// 一篇已发布、出现在列表中的文章的元数据
+pub fn meta(slug: &str, date: &str) -> PostMetadata {
+    PostMetadata {
+        title: slug.replace('-', " "),
+        slug: slug.to_string(),
+        url: format!("{}.html", slug),
+        date: date.to_string(),
+        tags: Vec::new(),
+        preview: format!("Preview of {}", slug),
+        excerpt_html: format!("<p>Preview of {}</p>", slug),
+        manual_excerpt: false,
+        word_count: 0,
+        char_count: 0,
+        publish: true,
+        in_menu: false,
+        in_list: true,
+        icon_url: None,
+        cover: None,
+        cover_position: "center".to_string(),
+        cover_picture: None,
+        series: None,
+        extra_head: None,
+        language: None,
+        translation_key: None,
+        todo_progress: None,
+        comment_count: None,
+        og_image: None,
+        hidden_tags: Vec::new(),
+        properties_table: Vec::new(),
+        hide_title: false,
+        hide_meta: false,
+        in_feed: false,
+        order: None,
+    }
+}
+
+/// 标签
+pub fn tag(name: &str) -> Tag {
+    Tag {
+        name: name.to_string(),
+        color: "default".to_string(),
+        slug: crate::model::slugify(name),
+        url: format!("tag/{}.html", crate::model::slugify(name)),
+    }
+}
+
+/// 带正文的文章
+pub fn post(meta: PostMetadata, content: &str) -> PostMetadataWithContent {
+    PostMetadataWithContent {
+        description: Some(meta.preview.clone()),
+        meta_title: meta.title.clone(),
+        meta_description: Some(meta.preview.clone()),
+        meta,
+        content: content.to_string(),
+        markdown: String::new(),
+        series: None,
+        headings: Vec::new(),
+        translations: Vec::new(),
+        blocks: None,
+    }
+}
+
+/// 站点信息
+pub fn site_meta(pages: Vec<PostMetadata>) -> SiteMeta {
+    SiteMeta {
+        title: "Test Blog".to_string(),
+        icon_url: None,
+        pages,
+        font_css: None,
+        theme_color: None,
+        color_scheme: Default::default(),
+        build_time: String::new(),
+        build_id: String::new(),
+        home_url: "index.html".to_string(),
+        feeds: Vec::new(),
+        favicons: Vec::new(),
+    }
+}
+
+/// 由文章构造构建结果，标签统计按 `config` 计算
+pub fn result(config: &Config, posts: Vec<PostMetadataWithContent>) -> BuildResult {
+    let mut result = BuildResult {
+        site_meta: site_meta(posts.iter().map(|p| p.meta.clone()).collect()),
+        posts,
+        tags: Vec::new(),
+        fonts: None,
+        images: Vec::new(),
+        favicon: None,
+    };
+    result.tags = crate::site::tag_stats(config, &crate::site::group_by_tag(result.listed_posts()));
+    result
+}
+
+/// Notion API 格式的纯文本 Rich Text
+pub fn text(content: &str) -> Value {
+    json!({
+        "type": "text",
+        "text": { "content": content, "link": null },
+        "annotations": {
+            "bold": false, "italic": false, "strikethrough": false,
+            "underline": false, "code": false, "color": "default"
+        },
+        "plain_text": content,
+        "href": null,
+    })
+}
+
+/// 由 Notion API 格式的 JSON 构造 Block，如 `{"type": "paragraph", "paragraph": {...}}`
+pub fn block(value: Value) -> Block {
+    serde_json::from_value(value).expect("测试 Block 无效")
+}
+
+/// Notion API 返回的完整 Block 对象 (`BlockResponse` 的 JSON)，`block` 为 `{"type": ..., ...}` 部分
+pub fn block_response(id: &str, has_children: bool, block: Value) -> Value {
+    let mut value = json!({
+        "object": "block",
+        "id": id,
+        "parent": { "type": "page_id", "page_id": "root" },
+        "created_time": "2024-01-01T00:00:00.000Z",
+        "last_edited_time": "2024-01-01T00:00:00.000Z",
+        "created_by": { "object": "user", "id": "user" },
+        "last_edited_by": { "object": "user", "id": "user" },
+        "has_children": has_children,
+        "archived": false,
+        "in_trash": false,
+    });
+    if let (Some(base), Some(block)) = (value.as_object_mut(), block.as_object()) {
+        base.extend(block.clone());
+    }
+    value
+}
+
+/// 只有一段纯文本的段落 Block 对象
+pub fn paragraph(id: &str, content: &str) -> Value {
+    block_response(id, false, json!({
+        "type": "paragraph",
+        "paragraph": { "rich_text": [text(content)], "color": "default" }
+    }))
+}
+
+/// 数据库中的一篇文章 (`PageResponse` 的 JSON)：默认已发布、出现在列表中，
+/// `properties` 中的键覆盖默认属性，如 `{"inList": {"type": "checkbox", "checkbox": false}}`
+pub fn page(id: &str, title: &str, tags: &[&str], properties: Value) -> Value {
+    let checkbox = |checked: bool| json!({ "type": "checkbox", "checkbox": checked });
+    let mut props = json!({
+        "title": { "type": "title", "title": [text(title)] },
+        "tags": {
+            "type": "multi_select",
+            "multi_select": tags.iter().map(|name| json!({ "name": name, "color": "default" })).collect::<Vec<_>>()
+        },
+        "template": { "type": "select", "select": { "name": "post", "color": "default" } },
+        "publish": checkbox(true),
+        "inMenu": checkbox(false),
+        "inList": checkbox(true),
+        "date": { "type": "date", "date": { "start": "2024-01-01", "end": null, "time_zone": null } },
+    });
+    if let (Some(base), Some(extra)) = (props.as_object_mut(), properties.as_object()) {
+        base.extend(extra.clone());
+    }
+    json!({
+        "object": "page",
+        "id": id,
+        "created_time": "2024-01-01T00:00:00.000Z",
+        "last_edited_time": "2024-01-01T00:00:00.000Z",
+        "created_by": { "object": "user", "id": "user" },
+        "last_edited_by": { "object": "user", "id": "user" },
+        "cover": null,
+        "icon": null,
+        "parent": { "type": "data_source_id", "data_source_id": "db" },
+        "archived": false,
+        "in_trash": false,
+        "is_locked": false,
+        "properties": props,
+        "url": format!("https://www.notion.so/{}", id),
+        "public_url": null,
+    })
+}
+
+/// Notion API 的列表响应 (只有一页)
+pub fn list(results: Vec<Value>) -> Vec<u8> {
+    json!({ "object": "list", "results": results, "next_cursor": null, "has_more": false })
+        .to_string()
+        .into_bytes()
+}
+
+/// `config()` 所指数据库的查询接口
+pub const QUERY_PATH: &str = "/data_sources/0123456789abcdef0123456789abcdef/query";
+
+/// Notion API 的数据源定义：属性取自默认文章与 `pages` 中各页面的属性
+pub fn data_source(pages: &[Value]) -> Vec<u8> {
+    let mut properties = serde_json::Map::new();
+    for page in std::iter::once(&page("default", "", &[], json!({}))).chain(pages) {
+        for (name, property) in page["properties"].as_object().into_iter().flatten() {
+            properties.insert(name.clone(), json!({ "id": name, "name": name, "type": property["type"] }));
+        }
+    }
+    json!({ "object": "data_source", "id": "db", "properties": properties }).to_string().into_bytes()
+}
+
+/// 模拟 `config()` 所指的 Notion 数据库：数据源定义与查询结果来自 `pages`，每个页面的子 Block 都是 `blocks`
+pub async fn notion_site(pages: Vec<Value>, blocks: Vec<Value>) -> MockServer {
+    MockServer::with_routes(vec![
+        (QUERY_PATH, "application/json", list(pages.clone())),
+        ("/data_sources/", "application/json", data_source(&pages)),
+        ("/blocks/", "application/json", list(blocks)),
+    ])
+    .await
+}
+
+/// 只含 `.notdef` 一个空白字形的最小 TrueType 字体 (head、hhea、maxp、hmtx 四张表)，
+/// 足以让字体解析与排版流程完整运行
+pub fn font() -> Vec<u8> {
+    let mut head = Vec::new();
+    head.extend(0x0001_0000u32.to_be_bytes()); // version
+    head.extend(0x0001_0000u32.to_be_bytes()); // fontRevision
+    head.extend(0u32.to_be_bytes()); // checkSumAdjustment
+    head.extend(0x5F0F_3CF5u32.to_be_bytes()); // magicNumber
+    head.extend(0u16.to_be_bytes()); // flags
+    head.extend(1000u16.to_be_bytes()); // unitsPerEm
+    head.extend([0u8; 16]); // created, modified
+    head.extend([0u8; 8]); // xMin, yMin, xMax, yMax
+    head.extend([0u8; 6]); // macStyle, lowestRecPPEM, fontDirectionHint
+    head.extend([0u8; 4]); // indexToLocFormat, glyphDataFormat
+
+    let mut hhea = Vec::new();
+    hhea.extend(0x0001_0000u32.to_be_bytes()); // version
+    hhea.extend(800i16.to_be_bytes()); // ascender
+    hhea.extend((-200i16).to_be_bytes()); // descender
+    hhea.extend([0u8; 2]); // lineGap
+    hhea.extend(500u16.to_be_bytes()); // advanceWidthMax
+    hhea.extend([0u8; 6]); // minLeftSideBearing, minRightSideBearing, xMaxExtent
+    hhea.extend(1i16.to_be_bytes()); // caretSlopeRise
+    hhea.extend([0u8; 14]); // caretSlopeRun, caretOffset, reserved, metricDataFormat
+    hhea.extend(1u16.to_be_bytes()); // numberOfHMetrics
+
+    let mut maxp = Vec::new();
+    maxp.extend(0x0000_5000u32.to_be_bytes()); // version 0.5
+    maxp.extend(1u16.to_be_bytes()); // numGlyphs
+
+    let mut hmtx = Vec::new();
+    hmtx.extend(500u16.to_be_bytes()); // advanceWidth
+    hmtx.extend(0i16.to_be_bytes()); // lsb
+
+    let tables: [(&[u8; 4], Vec<u8>); 4] = [(b"head", head), (b"hhea", hhea), (b"hmtx", hmtx), (b"maxp", maxp)];
+    let mut font = Vec::new();
+    font.extend(0x0001_0000u32.to_be_bytes());
+    font.extend((tables.len() as u16).to_be_bytes());
+    font.extend([0, 64, 0, 2, 0, 0]); // searchRange, entrySelector, rangeShift
+    let mut offset = 12 + 16 * tables.len();
+    for (tag, data) in &tables {
+        font.extend(*tag);
+        font.extend(0u32.to_be_bytes()); // checksum (解析时不校验)
+        font.extend((offset as u32).to_be_bytes());
+        font.extend((data.len() as u32).to_be_bytes());
+        offset += data.len().next_multiple_of(4);
+    }
+    for (_, data) in &tables {
+        font.extend(data);
+        font.resize(font.len().next_multiple_of(4), 0);
+    }
+    font
+}
+
+/// 测试专用的临时目录，每次调用先清空
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rsnotablog-test-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("无法创建临时目录");
+    dir
+}
+
+/// 本地 HTTP 服务：按路径前缀返回预设的响应 (没有匹配的路径时返回 404)，并记录收到的请求 (请求行、头部与正文)
+pub struct MockServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+/// 一条预设响应：路径前缀、Content-Type 与正文
+pub type Route = (&'static str, &'static str, Vec<u8>);
+
+impl MockServer {
+    /// 对任何路径都返回同一个响应
+    pub async fn start(content_type: &'static str, body: Vec<u8>) -> Self {
+        Self::with_routes(vec![("/", content_type, body)]).await
+    }
+
+    pub async fn with_routes(routes: Vec<Route>) -> Self {
+        Self::serve(routes.into_iter().map(|(prefix, content_type, body)| (prefix.to_string(), "200 OK", content_type, body)).collect()).await
+    }
+
+    /// 按路径前缀返回预设的响应，每条响应可以指定状态码，如 `("/comments", "403 Forbidden", "application/json", body)`
+    pub async fn with_status_routes(routes: Vec<(&'static str, &'static str, &'static str, Vec<u8>)>) -> Self {
+        Self::serve(routes.into_iter().map(|(prefix, status, content_type, body)| (prefix.to_string(), status, content_type, body)).collect()).await
+    }
+
+    /// 模拟 Notion 的子 Block 接口：`fixture` 为 `{ block_id: [BlockResponse JSON, ...] }`
+    pub async fn with_blocks(fixture: &Value) -> Self {
+        let routes = fixture
+            .as_object()
+            .expect("fixture 应为对象")
+            .iter()
+            .map(|(id, blocks)| (format!("/blocks/{}/children", id), "200 OK", "application/json", list(blocks.as_array().cloned().unwrap_or_default())))
+            .collect();
+        Self::serve(routes).await
+    }
+
+    /// 对任何路径都返回同一个错误响应，`status` 如 `401 Unauthorized`
+    pub async fn error(status: &'static str, content_type: &'static str, body: Vec<u8>) -> Self {
+        Self::serve(vec![("/".to_string(), status, content_type, body)]).await
+    }
+
+    async fn serve(routes: Vec<(String, &'static str, &'static str, Vec<u8>)>) -> Self {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.expect("无法监听本地端口");
+        let url = format!("http://{}", listener.local_addr().expect("无法取得本地地址"));
+        let requests: Arc<Mutex<Vec<String>>> = Arc::default();
+        let log = Arc::clone(&requests);
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let request = read_request(&mut socket).await;
+                let head_only = request.starts_with("HEAD ");
+                let path = request.split(' ').nth(1).unwrap_or("/").to_string();
+                log.lock().unwrap().push(request);
+                let route = routes.iter().find(|(prefix, _, _, _)| path.starts_with(prefix.as_str()));
+                let (status, content_type, body) = match route {
+                    Some((_, status, content_type, body)) => (*status, *content_type, body.as_slice()),
+                    None => ("404 Not Found", "text/plain", &b"not found"[..]),
+                };
+                let head = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    content_type,
+                    body.len()
+                );
+                let _ = socket.write_all(head.as_bytes()).await;
+                if !head_only {
+                    let _ = socket.write_all(body).await;
+                }
+                let _ = socket.shutdown().await;
+            }
+        });
+        MockServer { url, requests }
+    }
+
+    /// 已收到的请求
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// 读取一个 HTTP 请求：头部读到空行为止，正文按 Content-Length 读取
+async fn read_request(socket: &mut tokio::net::TcpStream) -> String {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+    while let Ok(n) = socket.read(&mut buf).await {
+        if n == 0 {
+            break;
+        }
+        data.extend_from_slice(&buf[..n]);
+        let text = String::from_utf8_lossy(&data);
+        if let Some(end) = text.find("\r\n\r\n") {
+            let length = text[..end]
+                .lines()
+                .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().to_string()))
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(0);
+            if data.len() >= end + 4 + length {
+                break;
+            }
+        }
+    }
+    String::from_utf8_lossy(&data).into_owned()
+}
@@ -0,0 +1,146 @@
+/// 是否为 CJK 字符 (中日韩统一表意文字、假名、谚文)
+pub fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{4E00}'..='\u{9FFF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{3040}'..='\u{30FF}'
+        | '\u{AC00}'..='\u{D7AF}'
+        | '\u{20000}'..='\u{2A6DF}'
+    )
+}
+
+/// 统计字数与字符数，返回 `(word_count, char_count)`。
+/// CJK 字符每个计为一个词，其余文字按空白分隔计词；字符数不含空白。
+pub fn count_words(text: &str) -> (usize, usize) {
+    let mut words = 0;
+    let mut chars = 0;
+    let mut in_word = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            in_word = false;
+            continue;
+        }
+        chars += 1;
+        if is_cjk(c) {
+            words += 1;
+            in_word = false;
+        } else if c.is_alphanumeric() && !in_word {
+            words += 1;
+            in_word = true;
+        }
+    }
+    (words, chars)
+}
+
+/// 是否为组合字符 (附加符号、零宽连接符、变体选择符)，它们必须与前一个字符保持在一起
+fn is_combining(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}'
+        | '\u{1AB0}'..='\u{1AFF}'
+        | '\u{1DC0}'..='\u{1DFF}'
+        | '\u{20D0}'..='\u{20FF}'
+        | '\u{FE20}'..='\u{FE2F}'
+        | '\u{FE00}'..='\u{FE0F}'
+        | '\u{200D}'
+    )
+}
+
+/// 是否为 Emoji (或 Emoji 序列中的修饰符、国旗与标签字符)，生成文件名与 slug 时去掉
+pub fn is_emoji(c: char) -> bool {
+    matches!(c,
+        '\u{1F000}'..='\u{1FAFF}'
+        | '\u{2600}'..='\u{27BF}'
+        | '\u{2B00}'..='\u{2BFF}'
+        | '\u{2190}'..='\u{21FF}'
+        | '\u{2300}'..='\u{23FF}'
+        | '\u{E0020}'..='\u{E007F}'
+        | '\u{20E3}'
+    ) || is_combining(c)
+}
+
+/// 稳定的短哈希 (FNV-1a，8 位十六进制)，用于无法生成可读 slug 时的兜底
+pub fn short_hash(s: &str) -> String {
+    let hash = s.bytes().fold(0xcbf29ce484222325u64, |h, b| (h ^ b as u64).wrapping_mul(0x100000001b3));
+    format!("{:08x}", hash as u32)
+}
+
+/// 按字符数截断，尽量断在单词边界，超出时以 `…` 结尾 (含省略号不超过 `max_chars`)；
+/// 连续空白合并为一个空格。CJK 文本没有空格可断时直接在字符处截断
+pub fn truncate_words(text: &str, max_chars: usize) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= max_chars {
+        return text;
+    }
+    let limit = max_chars.saturating_sub(1);
+    let cut = text.char_indices().nth(limit).map_or(text.len(), |(i, _)| i);
+    let head = &text[..cut];
+    // 截断点落在单词中间时退回到前一个空格，但不为此丢掉一半以上的内容
+    let at_boundary = text[cut..].starts_with(' ') || head.ends_with(is_cjk);
+    let head = match head.rfind(' ') {
+        Some(i) if !at_boundary && i >= cut / 2 => &head[..i],
+        _ => head,
+    };
+    format!("{}…", head.trim_end())
+}
+
+/// 显示宽度：CJK 字符 (及全角标点) 计为 2，其余计为 1，组合字符不占宽度
+pub fn char_width(c: char) -> usize {
+    if is_combining(c) {
+        0
+    } else if is_cjk(c) || matches!(c, '\u{3000}'..='\u{303F}' | '\u{FF00}'..='\u{FFEF}') {
+        2
+    } else {
+        1
+    }
+}
+
+/// 按显示宽度截断，返回不超过 `width` 的最长前缀；文本本身不超宽时返回 None。
+/// 组合字符宽度为 0，截断点总是落在有宽度的字符之前，因此不会拆开附加符号；
+/// 由零宽连接符连成的序列 (如 emoji 家庭) 则整体舍弃。
+pub fn truncate_width(text: &str, width: usize) -> Option<&str> {
+    let mut used = 0;
+    for (idx, c) in text.char_indices() {
+        used += char_width(c);
+        if used > width {
+            let mut head = &text[..idx];
+            while head.ends_with('\u{200D}') {
+                head = head.trim_end_matches('\u{200D}');
+                head = head.char_indices().last().map_or("", |(i, _)| &head[..i]);
+            }
+            return Some(head);
+        }
+    }
+    None
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_cjk_characters_as_words() {
+        assert_eq!(count_words("Hello, world!"), (2, 12));
+        assert_eq!(count_words("你好世界"), (4, 4));
+        assert_eq!(count_words("Rust 编程  guide"), (4, 11));
+        assert_eq!(count_words(""), (0, 0));
+    }
+
+    #[test]
+    fn english_and_chinese_previews_truncate_to_the_same_width() {
+        let english = truncate_width("The quick brown fox jumps over the lazy dog", 20).unwrap();
+        let chinese = truncate_width("敏捷的棕色狐狸跳过了那只懒狗然后继续奔跑", 20).unwrap();
+        assert_eq!(english, "The quick brown fox ");
+        assert_eq!(chinese, "敏捷的棕色狐狸跳过了");
+        assert_eq!(english.chars().map(char_width).sum::<usize>(), 20);
+        assert_eq!(chinese.chars().map(char_width).sum::<usize>(), 20);
+        assert_eq!(truncate_width("short", 20), None);
+    }
+
+    #[test]
+    fn width_truncation_keeps_combining_sequences_whole() {
+        // e + 组合重音符不会被拆开
+        assert_eq!(truncate_width("cafe\u{301}s", 4), Some("cafe\u{301}"));
+        // 由零宽连接符连成的 emoji 序列整体舍弃
+        assert_eq!(truncate_width("ab👩\u{200D}👧", 3), Some("ab"));
+    }
+}